@@ -0,0 +1,51 @@
+use yew::prelude::*;
+
+/// Save the open file to disk once typing pauses for the configured delay.
+/// Saves refused because another program changed the file open the
+/// `conflict` dialog, so the user can settle them.
+///
+/// Only desktop has files to save to; on the web every edit is already kept
+/// in browser storage.
+#[hook]
+pub(crate) fn use_autosave(conflict: UseStateHandle<Option<md::FileChange>>) {
+    #[cfg(feature = "web")]
+    drop(conflict);
+
+    #[cfg(not(feature = "web"))]
+    {
+        use error::UbiquityError;
+        use gloo::timers::callback::Timeout;
+        use wasm_bindgen_futures::spawn_local;
+        use crate::components::toasts::ToastProps;
+        use crate::contexts::{config::use_config, markdown::use_markdown, toasts::{use_toaster, err_modal}};
+        use crate::tauri::{auto_save, peek_file};
+
+        let md_ctx = use_markdown();
+        let config = use_config().state();
+        let toaster = use_toaster();
+
+        use_effect_with_deps(move |(markdown, enabled, delay)| {
+            // Files are only saved once they have a path, and the built-in docs never are
+            let path = markdown.key.clone().filter(|_| *enabled && markdown.dirty && !markdown.bundled);
+
+            let timeout = path.map(|path| {
+                let markdown = markdown.clone();
+                Timeout::new(*delay, move || {
+                    spawn_local(async move {
+                        match auto_save(path.to_string(), markdown.text.to_string()).await {
+                            Ok(_) => md_ctx.mark_saved(markdown).unwrap_or_else(|err| err_modal(err, toaster.clone())),
+                            Err(err) if err == UbiquityError::file_changed() => match peek_file(path).await {
+                                Ok(Some(change)) => conflict.set(Some(change)),
+                                // Saved over or changed back since
+                                Ok(None) => {}
+                                Err(err) => toaster.add_toast(ToastProps::from(err)),
+                            },
+                            Err(err) => toaster.add_toast(ToastProps::from(err)),
+                        }
+                    });
+                })
+            });
+            move || drop(timeout)
+        }, (md_ctx.state(), config.autosave, config.autosave_delay_millis));
+    }
+}
@@ -0,0 +1,319 @@
+use yew::prelude::*;
+
+/// Which side of a conflict a merge keeps
+#[cfg(not(feature = "web"))]
+#[derive(Clone, Copy, PartialEq)]
+enum Choice {
+    Mine,
+    Theirs,
+    Both,
+}
+
+/// The text of a merge, with each conflict settled by its choice
+#[cfg(not(feature = "web"))]
+fn merged_text(chunks: &[md::MergeChunk], choices: &[Choice]) -> String {
+    use md::MergeChunk;
+
+    let mut choices = choices.iter();
+    let mut lines: Vec<&String> = Vec::new();
+    for chunk in chunks {
+        match chunk {
+            MergeChunk::Resolved(resolved) => lines.extend(resolved),
+            MergeChunk::Conflict { ours, theirs, .. } => {
+                let choice = choices.next().copied().unwrap_or(Choice::Mine);
+                if choice != Choice::Theirs {
+                    lines.extend(ours);
+                }
+                if choice != Choice::Mine {
+                    lines.extend(theirs);
+                }
+            }
+        }
+    }
+
+    let mut text = lines.into_iter().map(String::as_str).collect::<Vec<&str>>().join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Read the file as it is on disk now. Ubiquity only saves over changes
+/// made by other programs once it has read them, so this is also how they
+/// are accepted.
+#[cfg(not(feature = "web"))]
+async fn reread(key: AttrValue, toaster: crate::contexts::toasts::ToasterContext) -> Option<AttrValue> {
+    use crate::components::toasts::ToastProps;
+    use crate::tauri::read_markdown_from_fs;
+
+    match read_markdown_from_fs(key).await {
+        Ok(contents) => Some(AttrValue::from(contents)),
+        Err(err) => {
+            toaster.add_toast(ToastProps::from(err));
+            None
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConflictProps {
+    /// The change being settled, shared with autosave, which finds some of them first
+    pub conflict: UseStateHandle<Option<md::FileChange>>,
+}
+
+/// Keeps the open file in step with changes other programs make to it.
+///
+/// Files without unsaved edits are reloaded. Otherwise this asks whether to
+/// reload, keep the edits, or merge the two versions line by line.
+#[cfg(not(feature = "web"))]
+#[function_component(ConflictModal)]
+pub fn conflict_modal(props: &ConflictProps) -> Html {
+    use futures::StreamExt;
+    use md::MergeChunk;
+    use wasm_bindgen_futures::spawn_local;
+    use crate::components::toasts::ToastProps;
+    use crate::contexts::{markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}};
+    use crate::tauri::{listen_for_file_changes, peek_file};
+
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
+    let conflict = props.conflict.clone();
+    let merging = use_state(|| false);
+    let choices = use_state(Vec::<Choice>::new);
+
+    // The markdown as it is now, for the listener below that outlives this render
+    let latest = use_mut_ref(Markdown::default);
+    *latest.borrow_mut() = md_ctx.state();
+
+    {
+        let md_ctx = md_ctx.clone();
+        let toaster = toaster.clone();
+        let conflict = conflict.clone();
+        let latest = latest.clone();
+        use_effect_with_deps(move |_| {
+            spawn_local(async move {
+                let mut changes = match listen_for_file_changes().await {
+                    Ok(changes) => changes,
+                    Err(err) => return toaster.add_toast(ToastProps::from(err)),
+                };
+
+                while let Some(change) = changes.next().await {
+                    let markdown = latest.borrow().clone();
+                    if markdown.key.as_deref() != Some(change.path.as_str()) {
+                        continue;
+                    }
+
+                    if markdown.dirty && markdown.text.as_str() != change.contents {
+                        conflict.set(Some(change));
+                    } else if let Some(text) = reread(AttrValue::from(change.path.clone()), toaster.clone()).await {
+                        let key = Some(AttrValue::from(change.path));
+                        md_ctx.set_markdown(Markdown::from(text, key)).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                    }
+                }
+            });
+            || ()
+        }, ());
+    }
+
+    // Files can change while Ubiquity is closed or another file is showing,
+    // so each is read again once opened. Unsaved edits are never read over,
+    // as that would let saving them overwrite the changes unasked.
+    {
+        let md_ctx = md_ctx.clone();
+        let toaster = toaster.clone();
+        let conflict = conflict.clone();
+        let latest = latest.clone();
+        use_effect_with_deps(move |(key, bundled)| {
+            if let Some(key) = key.clone() && !*bundled {
+                spawn_local(async move {
+                    if latest.borrow().dirty {
+                        let Ok(Some(change)) = peek_file(key.clone()).await else {
+                            return;
+                        };
+                        let markdown = latest.borrow().clone();
+                        if markdown.key != Some(key.clone()) {
+                            return;
+                        }
+                        if markdown.text.as_str() != change.contents {
+                            conflict.set(Some(change));
+                        } else {
+                            // The edits already match it, so there is nothing to lose
+                            reread(key, toaster).await;
+                        }
+                        return;
+                    }
+
+                    let Ok(contents) = crate::tauri::read_markdown_from_fs(key.clone()).await else {
+                        // Files that were never saved have nothing to read
+                        return;
+                    };
+                    let markdown = latest.borrow().clone();
+                    if !markdown.dirty && markdown.key == Some(key.clone()) && markdown.text.as_str() != contents {
+                        let text = AttrValue::from(contents);
+                        md_ctx.set_markdown(Markdown::from(text, Some(key))).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                    }
+                });
+            }
+            || ()
+        }, (md_ctx.state().key, md_ctx.state().bundled));
+    }
+
+    let Some(change) = (*conflict).clone() else {
+        return html! {};
+    };
+    let key = AttrValue::from(change.path.clone());
+
+    let close = {
+        let conflict = conflict.clone();
+        let merging = merging.clone();
+        move || {
+            conflict.set(None);
+            merging.set(false);
+        }
+    };
+
+    let reload = {
+        let md_ctx = md_ctx.clone();
+        let toaster = toaster.clone();
+        let key = key.clone();
+        let close = close.clone();
+        Callback::from(move |_| {
+            let (md_ctx, toaster, key) = (md_ctx.clone(), toaster.clone(), key.clone());
+            spawn_local(async move {
+                if let Some(text) = reread(key.clone(), toaster.clone()).await {
+                    md_ctx.set_markdown(Markdown::from(text, Some(key))).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                }
+            });
+            close();
+        })
+    };
+
+    let keep_mine = {
+        let toaster = toaster.clone();
+        let key = key.clone();
+        let close = close.clone();
+        Callback::from(move |_| {
+            let (toaster, key) = (toaster.clone(), key.clone());
+            spawn_local(async move {
+                reread(key, toaster).await;
+            });
+            close();
+        })
+    };
+
+    let mine = md_ctx.state().text;
+    let chunks = md::merge(&change.base, &mine, &change.contents);
+    let conflicts = chunks.iter().filter(|chunk| matches!(chunk, MergeChunk::Conflict { .. })).count();
+
+    let start_merge = {
+        let merging = merging.clone();
+        let choices = choices.clone();
+        Callback::from(move |_| {
+            choices.set(vec![Choice::Mine; conflicts]);
+            merging.set(true);
+        })
+    };
+
+    let body = if !*merging {
+        html! {
+            <>
+                <p class="py-4">
+                    {"Another program changed this file while it had unsaved edits. Reload it to see their version, keep your edits to save over it, or merge the two."}
+                </p>
+                <div class="modal-action">
+                    <button onclick={keep_mine} class="btn btn-ghost">{"Keep mine"}</button>
+                    <button onclick={reload} class="btn btn-ghost">{"Reload"}</button>
+                    <button onclick={start_merge} class="btn btn-primary">{"Merge"}</button>
+                </div>
+            </>
+        }
+    } else {
+        let mut conflict_index = 0;
+        let chunks_html = chunks.iter().map(|chunk| match chunk {
+            MergeChunk::Resolved(lines) => html! {
+                <pre class="whitespace-pre-wrap opacity-60 px-2">{lines.join("\n")}</pre>
+            },
+            MergeChunk::Conflict { ours, theirs, .. } => {
+                let index = conflict_index;
+                conflict_index += 1;
+                let chosen = choices.get(index).copied().unwrap_or(Choice::Mine);
+
+                let buttons = [(Choice::Mine, "Mine"), (Choice::Theirs, "On disk"), (Choice::Both, "Both")].into_iter().map(|(choice, name)| {
+                    let choices = choices.clone();
+                    let choose = Callback::from(move |_| {
+                        let mut new_choices = (*choices).clone();
+                        new_choices[index] = choice;
+                        choices.set(new_choices);
+                    });
+                    html! {
+                        <button onclick={choose} class={classes!("btn", "btn-xs", (choice == chosen).then_some("btn-active"))}>{name}</button>
+                    }
+                });
+                let faded = |kept: bool| classes!("whitespace-pre-wrap", "rounded", "bg-base-200", "p-2", (!kept).then_some("opacity-40"));
+
+                html! {
+                    <div class="rounded-lg border border-warning p-2 my-2">
+                        <div class="btn-group">{ for buttons }</div>
+                        <div class="grid grid-cols-2 gap-2 mt-2">
+                            <pre class={faded(chosen != Choice::Theirs)}>{ours.join("\n")}</pre>
+                            <pre class={faded(chosen != Choice::Mine)}>{theirs.join("\n")}</pre>
+                        </div>
+                    </div>
+                }
+            }
+        }).collect::<Html>();
+
+        let apply = {
+            let chunks = chunks.clone();
+            let choices = choices.clone();
+            let key = key.clone();
+            let close = close.clone();
+            Callback::from(move |_| {
+                let text = AttrValue::from(merged_text(&chunks, &choices));
+                // Still to be saved, over their version once it has been read
+                md_ctx.update_markdown(Markdown::from(text, Some(key.clone()))).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                let (toaster, key) = (toaster.clone(), key.clone());
+                spawn_local(async move {
+                    reread(key, toaster).await;
+                });
+                close();
+            })
+        };
+        let back = {
+            let merging = merging.clone();
+            Callback::from(move |_| merging.set(false))
+        };
+
+        html! {
+            <>
+                <p class="py-2">
+                    {format!("Changes made on only one side are merged. Choose what to keep for each of the {} conflicting parts.", conflicts)}
+                </p>
+                <div class="font-mono text-sm max-h-[60vh] overflow-auto">
+                    { chunks_html }
+                </div>
+                <div class="modal-action">
+                    <button onclick={back} class="btn btn-ghost">{"Back"}</button>
+                    <button onclick={apply} class="btn btn-primary">{"Apply merge"}</button>
+                </div>
+            </>
+        }
+    };
+
+    html! {
+        <div class="modal modal-open">
+            <div class="modal-box w-11/12 max-w-5xl">
+                <h3 class="font-bold text-2xl">{"File changed on disk"}</h3>
+                <p class="font-mono py-2 truncate">{&change.path}</p>
+                { body }
+            </div>
+        </div>
+    }
+}
+
+/// Files are only opened from disk on desktop.
+#[cfg(feature = "web")]
+#[function_component(ConflictModal)]
+pub fn conflict_modal(_props: &ConflictProps) -> Html {
+    html! {}
+}
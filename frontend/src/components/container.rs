@@ -39,7 +39,7 @@ pub fn container(props: &ContainerProps) -> Html {
 #[function_component(HalfWidthContainer)]
 pub fn half_width_container(props: &ContainerProps) -> Html {
     html! {
-        <div class="w-[48%] flex-none h-[calc(100vh-8.25rem)] border border-base-content rounded-xl pt-4 pb-6 px-8">
+        <div class="flex-1 min-w-0 h-[calc(100vh-8.25rem)] border border-base-content rounded-xl pt-4 pb-6 px-8">
             { props.children.clone() }
         </div>
     }
@@ -1,7 +1,7 @@
 use yew::prelude::*;
 use yew_router::prelude::use_navigator;
 
-use crate::{contexts::config::use_config, pages::Page};
+use crate::{components::{file_tree::FileTree, outline::OutlineList}, contexts::config::use_config, pages::Page};
 
 #[derive(Debug, PartialEq, Properties)]
 pub struct DrawerProps {
@@ -12,6 +12,7 @@ pub struct DrawerProps {
 #[function_component(Drawer)]
 pub fn drawer(props: &DrawerProps) -> Html {
     let theme = use_config().state().theme;
+    let is_mobile_ui = use_config().is_mobile_ui();
 
     let drawer_classes = classes!(
         "flex",
@@ -45,6 +46,14 @@ pub fn drawer(props: &DrawerProps) -> Html {
                         <Settings />
                         <About />
                     </ul>
+                    if is_mobile_ui {
+                        <div class="flex flex-col flex-1 min-h-0 mt-4">
+                            <h2 class="px-4 mb-2 font-display text-lg">{"Outline"}</h2>
+                            <OutlineList />
+                        </div>
+                    } else {
+                        <FileTree />
+                    }
                 </div>
             </div>
             </div>
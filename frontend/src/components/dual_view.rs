@@ -2,6 +2,7 @@ use yew::prelude::*;
 use crate::components::container::HalfWidthContainer;
 use crate::components::editor::editor::Editor;
 use crate::components::markdown_preview::MarkdownPreview;
+use crate::components::outline::OutlinePanel;
 
 #[function_component(DualView)]
 pub fn dual_view() -> Html {
@@ -18,6 +19,7 @@ pub fn dual_view() -> Html {
 
     html! {
         <div class={dual_view_classes}>
+            <OutlinePanel />
             <HalfWidthContainer>
                 <Editor />
             </HalfWidthContainer>
@@ -1,14 +1,24 @@
 use yew::prelude::*;
 
-use crate::components::editor::{header::header::EditorHeader, textarea::textarea::EditorTextarea};
+use crate::components::editor::{header::header::EditorHeader, status_bar::StatusBar, textarea::textarea::EditorTextarea};
+use crate::components::editor::problems::{use_lints, LintGutter, ProblemsPanel};
+use crate::components::editor::link_check::{use_link_check, LinkCheckPanel};
 
 #[function_component(Editor)]
 pub fn editor() -> Html {
+    let lints = use_lints();
+    let links = use_link_check();
 
     html! {
         <div class="flex flex-col h-full">
             <EditorHeader />
-            <EditorTextarea />
+            <div class="flex flex-1 min-h-0">
+                <EditorTextarea />
+                <LintGutter lints={lints.clone()} />
+            </div>
+            <ProblemsPanel lints={lints} />
+            <LinkCheckPanel links={links} />
+            <StatusBar />
         </div>
     }
 }
\ No newline at end of file
@@ -2,8 +2,10 @@ use yew::prelude::*;
 use crate::components::modals::add_image::ADD_IMAGE_MODAL_ID;
 use crate::components::modals::add_link::ADD_LINK_MODAL_ID;
 use crate::components::modals::table::TABLE_MODAL_ID;
+use crate::components::editor::header::toc::use_insert_toc;
+use crate::components::editor::header::format_doc::use_format_document;
 use crate::contexts::config::use_config;
-use crate::icons::{LinkIcon, ImageIcon, TableIcon, PlusIcon};
+use crate::icons::{LinkIcon, ImageIcon, TableIcon, PlusIcon, TocIcon, FormatDocumentIcon};
 
 use super::header::HeaderBtnProps;
 
@@ -24,6 +26,8 @@ pub fn add_dropdown_btn(props: &HeaderBtnProps) -> Html {
                     <LinkBtn />
                     <ImageBtn />
                     <Table />
+                    <Toc />
+                    <FormatDocument />
                 </ul>
             </div>
         </div>
@@ -64,4 +68,32 @@ pub fn table() -> Html {
             </label>
         </li>
     }
-}
\ No newline at end of file
+}
+
+#[function_component(Toc)]
+pub fn toc() -> Html {
+    let insert_toc = use_insert_toc();
+
+    html! {
+        <li>
+            <a onclick={insert_toc}>
+                <TocIcon />
+                {"Table of contents"}
+            </a>
+        </li>
+    }
+}
+
+#[function_component(FormatDocument)]
+pub fn format_document() -> Html {
+    let format_doc = use_format_document();
+
+    html! {
+        <li>
+            <a onclick={format_doc}>
+                <FormatDocumentIcon />
+                {"Format document"}
+            </a>
+        </li>
+    }
+}
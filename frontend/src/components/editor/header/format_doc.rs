@@ -0,0 +1,66 @@
+use error::UbiquityError;
+use md::RenderOptions;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::{byte_offset, utf16_offset}, textarea::EDITOR_ID};
+use crate::components::tooltip::Tooltip;
+use crate::contexts::{config::use_config, markdown::{use_markdown, Markdown, MarkdownContext}, toasts::{use_toaster, err_modal}};
+use crate::icons::FormatDocumentIcon;
+use gloo::utils::document;
+
+use super::header::HeaderBtnProps;
+
+/// Pretty-print the current markdown, returning the formatted text.
+///
+/// The caret is kept on the line it was on, as formatting moves text around within lines.
+pub(crate) fn format_document(md_state: &MarkdownContext, options: &RenderOptions) -> Result<AttrValue, UbiquityError> {
+    let markdown = md_state.state();
+    let formatted = md::format(&markdown.text, options).map_err(UbiquityError::markdown)?;
+    if formatted == markdown.text.as_str() {
+        return Ok(markdown.text);
+    }
+
+    let text_area = document().get_element_by_id(&EDITOR_ID).and_then(|editor| editor.dyn_into::<HtmlTextAreaElement>().ok());
+    if let Some(text_area) = text_area {
+        let current_value = text_area.value();
+        let caret = text_area.selection_start().unwrap().unwrap_or_default();
+        let line = md::line_at(&current_value, byte_offset(&current_value, caret));
+
+        text_area.set_value(&formatted);
+        let caret = utf16_offset(&formatted, md::line_offset(&formatted, line));
+        text_area.set_selection_range(caret, caret).unwrap();
+    }
+
+    let formatted = AttrValue::from(formatted);
+    md_state.update_markdown(Markdown::from(formatted.clone(), markdown.key))?;
+    Ok(formatted)
+}
+
+/// Format the whole document in one consistent style.
+#[hook]
+pub(crate) fn use_format_document() -> Callback<MouseEvent> {
+    let md_state = use_markdown();
+    let config = use_config();
+    let toaster = use_toaster();
+
+    Callback::from(move |_mouse_event: MouseEvent| {
+        let options = md_state.state().render_options(config.state().render);
+        if let Err(err) = format_document(&md_state, &options) {
+            err_modal(err, toaster.clone());
+        }
+    })
+}
+
+#[function_component(FormatDocBtn)]
+pub fn format_doc_btn(props: &HeaderBtnProps) -> Html {
+    let format_doc = use_format_document();
+
+    html! {
+        <Tooltip tip={"Format document"}>
+            <btn onclick={format_doc} class={props.btn_classes}>
+                <FormatDocumentIcon />
+            </btn>
+        </Tooltip>
+    }
+}
@@ -0,0 +1,50 @@
+use error::UbiquityError;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::{byte_offset, utf16_offset}, textarea::EDITOR_ID};
+use crate::components::tooltip::Tooltip;
+use crate::contexts::{markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}};
+use crate::icons::FormatTableIcon;
+use gloo::utils::document;
+
+use super::header::HeaderBtnProps;
+
+#[function_component(FormatTableBtn)]
+pub fn format_table_btn(props: &HeaderBtnProps) -> Html {
+    let md_state = use_markdown();
+    let toaster = use_toaster();
+    let format_table = Callback::from(move |_mouse_event: MouseEvent| {
+        let text_area: HtmlTextAreaElement = document().get_element_by_id(&EDITOR_ID).unwrap().dyn_into().unwrap();
+        let mut current_value = text_area.value();
+
+        let caret = text_area.selection_start().unwrap().unwrap_or_default();
+        let offset = byte_offset(&current_value, caret);
+
+        if let Some(range) = mdtg::find_table(&current_value, offset) {
+            match mdtg::format_table(&current_value[range.clone()]) {
+                Ok(table) => {
+                    let start = range.start;
+                    current_value.replace_range(range, &table);
+                    text_area.set_value(&current_value);
+
+                    let caret = utf16_offset(&current_value, start);
+                    text_area.set_selection_range(caret, caret).unwrap();
+
+                    let key = md_state.state().key;
+                    let md = Markdown::from(AttrValue::from(current_value), key);
+                    md_state.update_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                },
+                Err(err) => err_modal(UbiquityError::mdtg(err), toaster.clone()),
+            }
+        }
+    });
+
+    html! {
+        <Tooltip tip={"Format table"}>
+            <btn onclick={format_table} class={props.btn_classes}>
+                <FormatTableIcon />
+            </btn>
+        </Tooltip>
+    }
+}
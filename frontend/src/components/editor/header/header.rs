@@ -12,7 +12,11 @@ use crate::components::editor::header::font_decrease::FontDecreaseBtn;
 use crate::components::editor::header::font_increase::FontIncreaseBtn;
 use crate::components::editor::header::image::AddImageBtn;
 use crate::components::editor::header::table::AddTableBtn;
+use crate::components::editor::header::format_table::FormatTableBtn;
+use crate::components::editor::header::table_ops::TableToolbar;
 use crate::components::editor::header::link::AddLinkBtn;
+use crate::components::editor::header::toc::TocBtn;
+use crate::components::editor::header::format_doc::FormatDocBtn;
 use crate::components::divider::DividerYAxis;
 use crate::contexts::config::use_config;
 
@@ -41,6 +45,7 @@ pub fn input_header() -> Html {
                 <HeadingsDropdown btn_classes={btn_classes}/>
                 <FormattingDropdown btn_classes={btn_classes}/>
                 <AddDropdown btn_classes={btn_classes}/>
+                <TableToolbar btn_classes={btn_classes}/>
                 <FontDecreaseBtn btn_classes={btn_classes}/>
                 <FontIncreaseBtn btn_classes={btn_classes}/>
             </div>
@@ -56,7 +61,11 @@ pub fn input_header() -> Html {
                 <DividerYAxis />
                 <AddLinkBtn btn_classes={btn_classes}/>
                 <AddImageBtn btn_classes={btn_classes}/>
+                <TocBtn btn_classes={btn_classes}/>
                 <AddTableBtn btn_classes={btn_classes}/>
+                <FormatTableBtn btn_classes={btn_classes}/>
+                <TableToolbar btn_classes={btn_classes}/>
+                <FormatDocBtn btn_classes={btn_classes}/>
                 <DividerYAxis />
                 <FontDecreaseBtn btn_classes={btn_classes}/>
                 <FontIncreaseBtn btn_classes={btn_classes}/>
@@ -11,4 +11,8 @@ mod redo;
 mod undo;
 mod image;
 mod link;
-mod table;
\ No newline at end of file
+mod table;
+mod format_table;
+mod table_ops;
+pub mod toc;
+pub mod format_doc;
@@ -0,0 +1,127 @@
+use error::UbiquityError;
+use gloo::events::EventListener;
+use mdtg::{Alignment, TableOp};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::{byte_offset, utf16_offset}, textarea::EDITOR_ID};
+use crate::contexts::config::use_config;
+use crate::contexts::markdown::{use_markdown, Markdown, MarkdownContext};
+use crate::contexts::toasts::{use_toaster, err_modal, ToasterContext};
+use crate::icons::TableIcon;
+use gloo::utils::document;
+
+use super::header::HeaderBtnProps;
+
+fn editor() -> Option<HtmlTextAreaElement> {
+    document().get_element_by_id(&EDITOR_ID)?.dyn_into().ok()
+}
+
+fn caret_in_table() -> bool {
+    match editor() {
+        Some(text_area) => {
+            let text = text_area.value();
+            let caret = text_area.selection_start().unwrap().unwrap_or_default();
+            mdtg::find_table(&text, byte_offset(&text, caret)).is_some()
+        }
+        None => false,
+    }
+}
+
+fn apply_table_op(op: TableOp, md_state: &MarkdownContext, toaster: &ToasterContext) {
+    let text_area = match editor() {
+        Some(text_area) => text_area,
+        None => return,
+    };
+    let mut current_value = text_area.value();
+    let caret = text_area.selection_start().unwrap().unwrap_or_default();
+
+    match mdtg::edit_table(&current_value, byte_offset(&current_value, caret), op) {
+        Ok((range, table, table_caret)) => {
+            let start = range.start;
+            current_value.replace_range(range, &table);
+            text_area.set_value(&current_value);
+
+            let caret = utf16_offset(&current_value, start + table_caret);
+            text_area.set_selection_range(caret, caret).unwrap();
+            text_area.focus().unwrap();
+
+            let key = md_state.state().key;
+            let md = Markdown::from(AttrValue::from(current_value), key);
+            md_state.update_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+        }
+        Err(err) => err_modal(UbiquityError::mdtg(err), toaster.clone()),
+    }
+}
+
+/// Structural edits of the table containing the caret, only shown while the caret is inside of one.
+#[function_component(TableToolbar)]
+pub fn table_toolbar(props: &HeaderBtnProps) -> Html {
+    let in_table = use_state_eq(caret_in_table);
+
+    {
+        let in_table = in_table.clone();
+        use_effect_with_deps(move |_| {
+            let listener = EventListener::new(&document(), "selectionchange", move |_| {
+                in_table.set(caret_in_table());
+            });
+            move || drop(listener)
+        }, ());
+    }
+
+    let md_state = use_markdown();
+    let toaster = use_toaster();
+    let item = move |label: &'static str, op: TableOp| {
+        let md_state = md_state.clone();
+        let toaster = toaster.clone();
+        let onmousedown = Callback::from(move |mouse_event: MouseEvent| {
+            // Keep the focus, and so the caret, in the editor.
+            mouse_event.prevent_default();
+            apply_table_op(op, &md_state, &toaster);
+        });
+
+        html! {
+            <li>
+                <div onmousedown={onmousedown}>
+                    {label}
+                </div>
+            </li>
+        }
+    };
+
+    let mut dropdown_classes = classes!("dropdown");
+    if use_config().is_mobile_ui() {
+        dropdown_classes.push("dropdown-end");
+    }
+
+    html! {
+        if *in_table {
+            <div class={dropdown_classes}>
+                <label class={props.btn_classes} tabindex="0">
+                    <TableIcon />
+                </label>
+                <div class="dropdown-content z-[1] menu p-2 shadow bg-base-200 rounded-box w-52 lg:w-max">
+                    <ul tabindex="0">
+                        <li class="menu-title">{"Rows"}</li>
+                        {item("Insert row below", TableOp::InsertRow)}
+                        {item("Delete row", TableOp::DeleteRow)}
+                        <li class="menu-title">{"Columns"}</li>
+                        {item("Insert column right", TableOp::InsertColumn)}
+                        {item("Delete column", TableOp::DeleteColumn)}
+                        {item("Move column left", TableOp::MoveColumnLeft)}
+                        {item("Move column right", TableOp::MoveColumnRight)}
+                        <li class="menu-title">{"Alignment"}</li>
+                        {item("Align left", TableOp::Align(Alignment::Left))}
+                        {item("Align center", TableOp::Align(Alignment::Center))}
+                        {item("Align right", TableOp::Align(Alignment::Right))}
+                        {item("Default alignment", TableOp::Align(Alignment::None))}
+                        <li class="menu-title">{"Table"}</li>
+                        {item("Sort ascending", TableOp::Sort { descending: false })}
+                        {item("Sort descending", TableOp::Sort { descending: true })}
+                        {item("Transpose", TableOp::Transpose)}
+                    </ul>
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use error::UbiquityError;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::{byte_offset, utf16_offset}, textarea::EDITOR_ID};
+use crate::components::tooltip::Tooltip;
+use crate::contexts::{config::use_config, markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}};
+use crate::icons::TocIcon;
+use gloo::utils::document;
+
+use super::header::HeaderBtnProps;
+
+/// Insert a table of contents at the caret, or refresh the one between the toc markers.
+#[hook]
+pub(crate) fn use_insert_toc() -> Callback<MouseEvent> {
+    let md_state = use_markdown();
+    let config = use_config();
+    let toaster = use_toaster();
+
+    Callback::from(move |_mouse_event: MouseEvent| {
+        let text_area: HtmlTextAreaElement = document().get_element_by_id(&EDITOR_ID).unwrap().dyn_into().unwrap();
+        let current_value = text_area.value();
+
+        let caret = text_area.selection_start().unwrap().unwrap_or_default();
+        let offset = byte_offset(&current_value, caret);
+        let options = md_state.state().render_options(config.state().render);
+
+        match md::insert_or_update_toc(&current_value, offset, &options) {
+            Ok((new_value, offset)) => {
+                text_area.set_value(&new_value);
+                let caret = utf16_offset(&new_value, offset);
+                text_area.set_selection_range(caret, caret).unwrap();
+
+                let key = md_state.state().key;
+                let md = Markdown::from(AttrValue::from(new_value), key);
+                md_state.update_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+            },
+            Err(err) => err_modal(UbiquityError::markdown(err), toaster.clone()),
+        }
+    })
+}
+
+#[function_component(TocBtn)]
+pub fn toc_btn(props: &HeaderBtnProps) -> Html {
+    let insert_toc = use_insert_toc();
+
+    html! {
+        <Tooltip tip={"Insert/Update table of contents"}>
+            <btn onclick={insert_toc} class={props.btn_classes}>
+                <TocIcon />
+            </btn>
+        </Tooltip>
+    }
+}
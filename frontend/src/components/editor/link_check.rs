@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use md::{DocLink, LinkStatus, LinkTarget};
+use yew::prelude::*;
+use crate::components::editor::{problems::select_source, status_bar::plural};
+use crate::contexts::{config::use_config, markdown::use_markdown};
+
+#[derive(Properties, PartialEq)]
+pub struct LinkCheckProps {
+    pub links: Rc<Vec<DocLink>>,
+}
+
+/// Every link and image in the current markdown. On desktop, relative files
+/// are looked for next to the document once it has been saved.
+#[hook]
+pub fn use_link_check() -> Rc<Vec<DocLink>> {
+    let markdown = use_markdown().state();
+    let options = markdown.render_options(use_config().state().render);
+    let links = use_memo(|(text, options)| md::links(text, options).unwrap_or_default(), (markdown.text.clone(), options));
+
+    // Whether each linked file exists, by path
+    let found = use_state_eq(HashMap::<String, bool>::new);
+
+    #[cfg(not(feature = "web"))]
+    {
+        use wasm_bindgen_futures::spawn_local;
+        use crate::tauri::files_exist;
+
+        let paths = use_memo(|links| {
+            let mut paths: Vec<String> = links
+                .iter()
+                .filter_map(|link| match &link.target {
+                    LinkTarget::File(path) => Some(path.clone()),
+                    _ => None,
+                })
+                .collect();
+            paths.sort();
+            paths.dedup();
+            paths
+        }, links.clone());
+
+        let found = found.clone();
+        // Only looked for when the files linked to change, rather than on every keystroke
+        use_effect_with_deps(move |(key, paths)| {
+            if let Some(base) = key.clone() && !paths.is_empty() {
+                let paths = (**paths).clone();
+                spawn_local(async move {
+                    if let Ok(exists) = files_exist(base.to_string(), paths.clone()).await {
+                        found.set(paths.into_iter().zip(exists).collect());
+                    }
+                });
+            }
+            || ()
+        }, (markdown.key.clone().filter(|_| !markdown.bundled), paths));
+    }
+
+    use_memo(|(links, found)| {
+        links
+            .iter()
+            .cloned()
+            .map(|mut link| {
+                if let LinkTarget::File(path) = &link.target {
+                    link.status = match found.get(path) {
+                        Some(true) => LinkStatus::Ok,
+                        Some(false) => LinkStatus::Broken,
+                        None => LinkStatus::Unchecked,
+                    };
+                }
+                link
+            })
+            .collect()
+    }, (links, (*found).clone()))
+}
+
+/// Why a file link is unchecked, as files can only be looked for on desktop
+#[cfg(not(feature = "web"))]
+const UNCHECKED_FILE: &str = "Save the document to look for this file";
+#[cfg(feature = "web")]
+const UNCHECKED_FILE: &str = "Files are only looked for in the desktop app";
+
+/// Why a link is listed
+fn reason(link: &DocLink) -> String {
+    match (&link.target, link.status) {
+        (LinkTarget::Anchor(id), _) => format!("No heading or element has the id \"{}\"", id),
+        (LinkTarget::File(_), LinkStatus::Broken) => String::from("File not found"),
+        (LinkTarget::File(_), _) => String::from(UNCHECKED_FILE),
+        (LinkTarget::Remote, _) => String::from("Not fetched"),
+    }
+}
+
+/// A collapsible list of broken links, and of the links that could not be checked.
+#[function_component(LinkCheckPanel)]
+pub fn link_check_panel(props: &LinkCheckProps) -> Html {
+    let broken = props.links.iter().filter(|link| link.status == LinkStatus::Broken).count();
+    let unchecked = props.links.iter().filter(|link| link.status == LinkStatus::Unchecked).count();
+
+    if broken == 0 && unchecked == 0 {
+        return html! {};
+    }
+
+    let title = match broken {
+        0 => format!("No broken links, {} not checked", unchecked),
+        _ => format!("{}, {} not checked", plural(broken, "broken link"), unchecked),
+    };
+
+    // Broken links first, as they are the ones to fix
+    let mut listed: Vec<&DocLink> = props.links.iter().filter(|link| link.status != LinkStatus::Ok).collect();
+    listed.sort_by_key(|link| link.status != LinkStatus::Broken);
+
+    let items = listed.into_iter().map(|link| {
+        let select = {
+            let (range, line) = (link.range.clone(), link.line);
+            Callback::from(move |_| select_source(&range, line))
+        };
+        let (badge, label) = match (&link.target, link.status) {
+            (_, LinkStatus::Broken) => ("badge-error", "Broken"),
+            (LinkTarget::Remote, _) => ("badge-ghost", "External"),
+            _ => ("badge-ghost", "File"),
+        };
+
+        html! {
+            <li>
+                <a onclick={select} class="flex items-center gap-2">
+                    <span class="font-mono opacity-60 whitespace-nowrap">{format!("Ln {}", link.line)}</span>
+                    <span class={classes!("badge", "badge-sm", badge)}>{label}</span>
+                    <span class="font-mono truncate">{&link.url}</span>
+                    <span class="flex-1 opacity-60">{reason(link)}</span>
+                </a>
+            </li>
+        }
+    });
+
+    html! {
+        <div class="collapse collapse-arrow flex-none rounded-none border-t border-base-content/20 print:hidden">
+            <input type="checkbox" />
+            <div class="collapse-title text-sm min-h-0 py-2">
+                {title}
+            </div>
+            <div class="collapse-content">
+                <ul class="menu menu-xs flex-nowrap max-h-48 overflow-y-auto">
+                    { for items }
+                </ul>
+            </div>
+        </div>
+    }
+}
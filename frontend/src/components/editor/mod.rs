@@ -1,3 +1,6 @@
 pub mod header;
+pub mod link_check;
+pub mod problems;
+pub mod status_bar;
 pub mod textarea;
 pub mod editor;
\ No newline at end of file
@@ -0,0 +1,152 @@
+use std::ops::Range;
+use std::rc::Rc;
+use error::UbiquityError;
+use gloo::utils::document;
+use lint::Lint;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::status_bar::plural;
+use crate::components::editor::textarea::{caret::utf16_offset, textarea::EDITOR_ID};
+use crate::contexts::{config::use_config, markdown::{use_markdown, Markdown, MarkdownContext}, toasts::{use_toaster, err_modal}};
+
+#[derive(Properties, PartialEq)]
+pub struct LintProps {
+    pub lints: Rc<Vec<Lint>>,
+}
+
+/// The problems the enabled lint rules find in the current markdown
+#[hook]
+pub fn use_lints() -> Rc<Vec<Lint>> {
+    let markdown = use_markdown().state();
+    let config = use_config().state();
+    let render_options = markdown.render_options(config.render);
+    use_memo(
+        |(text, options, render_options)| lint::lint(text, options, render_options).unwrap_or_default(),
+        (markdown.text, config.lint, render_options),
+    )
+}
+
+/// Select a range of the text in the editor and scroll its line into view
+pub(crate) fn select_source(range: &Range<usize>, line: usize) {
+    let text_area: HtmlTextAreaElement = match document().get_element_by_id(&EDITOR_ID).and_then(|editor| editor.dyn_into().ok()) {
+        Some(text_area) => text_area,
+        None => return,
+    };
+    let text = text_area.value();
+
+    text_area.focus().unwrap();
+    text_area
+        .set_selection_range(utf16_offset(&text, range.start), utf16_offset(&text, range.end))
+        .unwrap();
+
+    // Wrapped lines make this approximate, but it lands the line in view
+    let lines = text.lines().count().max(1);
+    let scroll_top = line.saturating_sub(1) as f64 / lines as f64 * text_area.scroll_height() as f64;
+    text_area.set_scroll_top(scroll_top as i32);
+}
+
+/// Replace the current markdown's text, keeping its key
+fn update_text(markdown_ctx: &MarkdownContext, text: String) -> Result<(), UbiquityError> {
+    let md = Markdown::from(AttrValue::from(text), markdown_ctx.state().key);
+    markdown_ctx.update_markdown(md)
+}
+
+/// A strip beside the editor marking where in the document each problem is.
+///
+/// Markers are placed by line rather than by pixel, as wrapped lines make the
+/// textarea's line positions expensive to measure.
+#[function_component(LintGutter)]
+pub fn lint_gutter(props: &LintProps) -> Html {
+    let lines = use_markdown().state().text.lines().count().max(1);
+
+    let markers = props.lints.iter().map(|lint| {
+        let onclick = {
+            let lint = lint.clone();
+            Callback::from(move |_| select_source(&lint.range, lint.line))
+        };
+        let top = format!("top: {}%", (lint.line - 1) as f64 / lines as f64 * 100.0);
+        let tip = format!("Ln {}: {} ({})", lint.line, lint.message, lint.rule.code());
+
+        html! {
+            <div class="absolute left-0 w-2 h-1 rounded bg-warning cursor-pointer" style={top} title={tip} onclick={onclick} />
+        }
+    });
+
+    html! {
+        <div class="relative flex-none w-2 my-2 print:hidden">
+            { for markers }
+        </div>
+    }
+}
+
+/// A collapsible list of the problems in the document, with their fixes.
+#[function_component(ProblemsPanel)]
+pub fn problems_panel(props: &LintProps) -> Html {
+    let markdown_ctx = use_markdown();
+    let config = use_config().state();
+    let lint_options = config.lint;
+    let render_options = markdown_ctx.state().render_options(config.render);
+    let toaster = use_toaster();
+
+    if props.lints.is_empty() {
+        return html! {};
+    }
+
+    let fix_all = {
+        let (markdown_ctx, toaster) = (markdown_ctx.clone(), toaster.clone());
+        Callback::from(move |_: MouseEvent| {
+            lint::fix_all(&markdown_ctx.state().text, &lint_options, &render_options)
+                .map_err(UbiquityError::markdown)
+                .and_then(|text| update_text(&markdown_ctx, text))
+                .unwrap_or_else(|err| err_modal(err, toaster.clone()));
+        })
+    };
+
+    let items = props.lints.iter().map(|lint| {
+        let select = {
+            let lint = lint.clone();
+            Callback::from(move |_| select_source(&lint.range, lint.line))
+        };
+        let fix = lint.fix.is_some().then(|| {
+            let (markdown_ctx, toaster, lint) = (markdown_ctx.clone(), toaster.clone(), lint.clone());
+            Callback::from(move |event: MouseEvent| {
+                event.stop_propagation();
+                let text = lint::apply_fixes(&markdown_ctx.state().text, &[lint.clone()]);
+                update_text(&markdown_ctx, text).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+            })
+        });
+
+        html! {
+            <li>
+                <a onclick={select} class="flex items-center gap-2">
+                    <span class="font-mono opacity-60 whitespace-nowrap">{format!("Ln {}, Col {}", lint.line, lint.column)}</span>
+                    <span class="badge badge-sm badge-warning" title={lint.rule.description()}>{lint.rule.code()}</span>
+                    <span class="flex-1">{&lint.message}</span>
+                    if let Some(fix) = fix {
+                        <button class="btn btn-xs btn-ghost" onclick={fix}>{"Fix"}</button>
+                    }
+                </a>
+            </li>
+        }
+    });
+
+    html! {
+        <div class="collapse collapse-arrow flex-none rounded-none border-t border-base-content/20 print:hidden">
+            <input type="checkbox" />
+            <div class="collapse-title text-sm min-h-0 py-2">
+                {plural(props.lints.len(), "problem")}
+            </div>
+            <div class="collapse-content">
+                if props.lints.iter().any(|lint| lint.fix.is_some()) {
+                    <div class="flex justify-end">
+                        <button class="btn btn-xs" onclick={fix_all}>{"Fix all"}</button>
+                    </div>
+                }
+                <ul class="menu menu-xs flex-nowrap max-h-48 overflow-y-auto">
+                    { for items }
+                </ul>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,127 @@
+use gloo::events::EventListener;
+use gloo::timers::callback::Timeout;
+use gloo::utils::document;
+use md::Stats;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::byte_offset, textarea::EDITOR_ID};
+use crate::contexts::{config::use_config, markdown::use_markdown};
+
+/// Documents longer than this are counted once typing pauses, rather than on every keystroke
+const DEBOUNCE_BYTES: usize = 50_000;
+const DEBOUNCE_MILLIS: u32 = 300;
+
+/// Where the caret is, and what is selected
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Caret {
+    line: usize,
+    column: usize,
+    selection: Option<String>,
+}
+
+fn caret() -> Option<Caret> {
+    let text_area: HtmlTextAreaElement = document().get_element_by_id(&EDITOR_ID)?.dyn_into().ok()?;
+    let text = text_area.value();
+    let start = byte_offset(&text, text_area.selection_start().ok()??);
+    let end = byte_offset(&text, text_area.selection_end().ok()??);
+
+    Some(Caret {
+        line: md::line_at(&text, end),
+        column: md::column_at(&text, end),
+        selection: (start < end).then(|| text[start..end].to_string()),
+    })
+}
+
+pub(crate) fn plural(count: usize, word: &str) -> String {
+    match count {
+        1 => format!("1 {}", word),
+        _ => format!("{} {}s", count, word),
+    }
+}
+
+/// Counts of the document, or of the selection when there is one, and the caret's position.
+#[function_component(StatusBar)]
+pub fn status_bar() -> Html {
+    let markdown = use_markdown().state();
+    let render_options = markdown.render_options(use_config().state().render);
+
+    let stats = use_state_eq(Stats::default);
+    {
+        let stats = stats.clone();
+        use_effect_with_deps(move |(text, options)| {
+            let count = {
+                let (text, options) = (text.clone(), options.clone());
+                move || stats.set(md::stats(&text, &options).unwrap_or_default())
+            };
+
+            let timeout = match text.len() > DEBOUNCE_BYTES {
+                true => Some(Timeout::new(DEBOUNCE_MILLIS, count)),
+                false => {
+                    count();
+                    None
+                }
+            };
+            move || drop(timeout)
+        }, (markdown.text.clone(), render_options.clone()));
+    }
+
+    let caret_state = use_state_eq(|| caret().unwrap_or_default());
+    {
+        let caret_state = caret_state.clone();
+        use_effect_with_deps(move |_| {
+            let listener = EventListener::new(&document(), "selectionchange", move |_| {
+                caret_state.set(caret().unwrap_or_default());
+            });
+            move || drop(listener)
+        }, ());
+    }
+
+    // Dragging a selection changes it many times a second, so it is counted once it settles
+    let selection_stats = use_state_eq(|| None::<Stats>);
+    {
+        let selection_stats = selection_stats.clone();
+        use_effect_with_deps(move |(selection, options)| {
+            let timeout = match selection {
+                Some(selection) => {
+                    let (selection, options) = (selection.clone(), options.clone());
+                    Some(Timeout::new(DEBOUNCE_MILLIS, move || {
+                        selection_stats.set(Some(md::stats(&selection, &options).unwrap_or_default()))
+                    }))
+                }
+                None => {
+                    selection_stats.set(None);
+                    None
+                }
+            };
+            move || drop(timeout)
+        }, (caret_state.selection.clone(), render_options));
+    }
+
+    let counts = match *selection_stats {
+        Some(selected) => vec![
+            format!("{} selected", plural(selected.words, "word")),
+            plural(selected.characters, "character"),
+            plural(selected.lines, "line"),
+            plural(selected.paragraphs, "paragraph"),
+        ],
+        None => vec![
+            plural(stats.words, "word"),
+            plural(stats.characters, "character"),
+            plural(stats.lines, "line"),
+            plural(stats.paragraphs, "paragraph"),
+            format!("{} min read", stats.reading_time()),
+        ],
+    };
+
+    html! {
+        <div class="flex flex-none justify-between gap-4 px-4 py-1 text-xs font-mono opacity-70 print:hidden">
+            <div class="flex flex-wrap gap-x-4">
+                { for counts.into_iter().map(|count| html! { <span>{count}</span> }) }
+            </div>
+            if caret_state.line > 0 {
+                <span class="whitespace-nowrap">{format!("Ln {}, Col {}", caret_state.line, caret_state.column)}</span>
+            }
+        </div>
+    }
+}
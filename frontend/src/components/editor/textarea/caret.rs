@@ -0,0 +1,19 @@
+/// Convert a textarea selection offset, counted in UTF-16 code units,
+/// into a byte offset into the same text.
+pub fn byte_offset(text: &str, utf16_offset: u32) -> usize {
+    let mut utf16_count = 0;
+
+    for (byte_index, c) in text.char_indices() {
+        if utf16_count >= utf16_offset as usize {
+            return byte_index;
+        }
+        utf16_count += c.len_utf16();
+    }
+
+    text.len()
+}
+
+/// Convert a byte offset into a textarea selection offset, counted in UTF-16 code units.
+pub fn utf16_offset(text: &str, byte_offset: usize) -> u32 {
+    text[..byte_offset].encode_utf16().count() as u32
+}
@@ -1 +1,2 @@
+pub mod caret;
 pub mod textarea;
\ No newline at end of file
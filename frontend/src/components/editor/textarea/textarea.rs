@@ -1,13 +1,33 @@
 use gloo::console::debug;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlTextAreaElement, HtmlDocument};
+use web_sys::{HtmlTextAreaElement, HtmlDocument, ClipboardEvent};
 use yew::prelude::*;
+use error::UbiquityError;
+use crate::components::editor::textarea::caret;
 use crate::contexts::config::use_config;
 use crate::contexts::{toasts::{use_toaster, err_modal}, markdown::{use_markdown, Markdown}};
 use gloo::utils::document;
 
 pub const EDITOR_ID: AttrValue = AttrValue::Static("editor");
 
+/// The newlines to put before and after a pasted table, so it is kept apart
+/// from the text around it by a blank line and is not read as part of a paragraph.
+fn block_padding(before: &str, after: &str) -> (&'static str, &'static str) {
+    let lead = match before {
+        "" => "",
+        _ if before.ends_with("\n\n") => "",
+        _ if before.ends_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    let trail = match after {
+        "" => "",
+        _ if after.starts_with("\n\n") => "",
+        _ if after.starts_with('\n') => "\n",
+        _ => "\n\n",
+    };
+    (lead, trail)
+}
+
 #[function_component(EditorTextarea)]
 pub fn editor_textarea() -> Html {
     let md_text = use_markdown().state().text;
@@ -63,6 +83,38 @@ pub fn editor_textarea() -> Html {
         }
     });
 
+    let markdown_ctx = use_markdown();
+    let toaster = use_toaster();
+    // Cells copied from a spreadsheet arrive as TSV, so paste them as a markdown table instead.
+    let onpaste = Callback::from(move |event: Event| {
+        let event: ClipboardEvent = event.dyn_into().unwrap();
+        let pasted = match event.clipboard_data().and_then(|data| data.get_data("text/plain").ok()) {
+            Some(pasted) if mdtg::is_tsv(&pasted) => pasted,
+            _ => return,
+        };
+
+        event.prevent_default();
+        let table = match mdtg::Table::from_csv(&pasted, Some('\t'), None) {
+            Ok(table) => table.to_string(),
+            Err(err) => return err_modal(UbiquityError::mdtg(err), toaster.clone()),
+        };
+
+        let text_area: HtmlTextAreaElement = document().get_element_by_id(&EDITOR_ID).unwrap().dyn_into().unwrap();
+        let mut current_value = text_area.value();
+        let start = caret::byte_offset(&current_value, text_area.selection_start().unwrap().unwrap_or_default());
+        let end = caret::byte_offset(&current_value, text_area.selection_end().unwrap().unwrap_or_default());
+
+        let (lead, trail) = block_padding(&current_value[..start], &current_value[end..]);
+        current_value.replace_range(start..end, &format!("{}{}{}", lead, table, trail));
+        text_area.set_value(&current_value);
+        let caret = caret::utf16_offset(&current_value, start + lead.len() + table.len());
+        text_area.set_selection_range(caret, caret).unwrap();
+
+        let key = markdown_ctx.state().key;
+        let md = Markdown::from(AttrValue::from(current_value), key);
+        markdown_ctx.update_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+    });
+
     let node_ref: NodeRef = NodeRef::default();
     let node_ref_clone = node_ref.clone();
     
@@ -91,7 +143,7 @@ pub fn editor_textarea() -> Html {
     );
 
     html! {
-        <textarea ref={node_ref} id={EDITOR_ID} onkeydown={key_check} oninput={oninput} spellcheck={"false"}
+        <textarea ref={node_ref} id={EDITOR_ID} onkeydown={key_check} oninput={oninput} onpaste={onpaste} spellcheck={"false"}
             class={classes}>
         // Do NOT put strings here some browsers won't process them.
         </textarea>
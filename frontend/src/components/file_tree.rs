@@ -0,0 +1,571 @@
+use yew::prelude::*;
+
+/// A folder of a workspace, built from the paths of the markdown files in
+/// it. Folders without markdown files are left out.
+#[cfg(not(feature = "web"))]
+#[derive(Default)]
+struct Folder {
+    folders: std::collections::BTreeMap<String, Folder>,
+    files: Vec<md::WorkspaceFile>,
+}
+
+#[cfg(not(feature = "web"))]
+impl Folder {
+    fn from(files: &[md::WorkspaceFile]) -> Self {
+        let mut root = Self::default();
+        for file in files {
+            let mut folder = &mut root;
+            let mut names: Vec<&str> = file.relative.split('/').collect();
+            names.pop();
+            for name in names {
+                folder = folder.folders.entry(name.to_string()).or_default();
+            }
+            folder.files.push(file.clone());
+        }
+        root
+    }
+
+    /// The tree as the rows of a list, leaving out what is in closed folders
+    fn rows(&self, path: &str, depth: usize, closed: &std::collections::HashSet<String>, rows: &mut Vec<Row>) {
+        for (name, folder) in &self.folders {
+            let folder_path = join(path, name);
+            let open = !closed.contains(&folder_path);
+            rows.push(Row { path: folder_path.clone(), name: name.clone(), depth, file: None, open });
+            if open {
+                folder.rows(&folder_path, depth + 1, closed, rows);
+            }
+        }
+        for file in &self.files {
+            let name = file.relative.rsplit('/').next().unwrap_or_default().to_string();
+            rows.push(Row { path: file.relative.clone(), name, depth, file: Some(file.path.clone()), open: false });
+        }
+    }
+
+    /// Every folder, relative to the root, which is `""`
+    fn paths(&self, path: &str, paths: &mut Vec<String>) {
+        paths.push(path.to_string());
+        for (name, folder) in &self.folders {
+            folder.paths(&join(path, name), paths);
+        }
+    }
+}
+
+#[cfg(not(feature = "web"))]
+struct Row {
+    /// Relative to the root
+    path: String,
+    name: String,
+    depth: usize,
+    /// The full path of a file, or `None` for a folder
+    file: Option<String>,
+    open: bool,
+}
+
+/// A file or folder to change, relative to the root
+#[cfg(not(feature = "web"))]
+#[derive(Clone, PartialEq)]
+struct Selected {
+    path: String,
+    is_folder: bool,
+}
+
+#[cfg(not(feature = "web"))]
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Choose,
+    Create,
+    Rename,
+    Move,
+    Delete,
+}
+
+/// What an action did, once it is done
+#[cfg(not(feature = "web"))]
+enum Done {
+    Created(String),
+    Moved(String),
+    Deleted,
+}
+
+#[cfg(not(feature = "web"))]
+fn join(folder: &str, name: &str) -> String {
+    match folder.is_empty() {
+        true => name.to_string(),
+        false => format!("{}/{}", folder, name),
+    }
+}
+
+/// Where a path ends up when `from` is moved to `to`, if it moves with it
+#[cfg(not(feature = "web"))]
+fn moved(path: &str, from: &str, to: &str) -> Option<String> {
+    if path == from {
+        return Some(to.to_string());
+    }
+    let rest = path.strip_prefix(from)?.strip_prefix('/')?;
+    Some(format!("{}/{}", to, rest))
+}
+
+/// Documents are open under their full paths. Those inside what moved follow
+/// it, keeping their edits, and the one showing stays showing.
+///
+/// Returns the paths they were open under.
+#[cfg(not(feature = "web"))]
+fn follow_move(
+    before: &[md::WorkspaceFile],
+    after: &[md::WorkspaceFile],
+    from: &str,
+    to: &str,
+    showing: &crate::contexts::markdown::Markdown,
+    md_ctx: &crate::contexts::markdown::MarkdownContext,
+) -> Result<Vec<String>, error::UbiquityError> {
+    use crate::contexts::markdown::Markdown;
+
+    let mut old_paths = Vec::new();
+    for key in Markdown::read_all_markdown_keys() {
+        let Some(file) = before.iter().find(|file| file.path == key.as_str()) else {
+            continue;
+        };
+        let Some(new_file) = moved(&file.relative, from, to).and_then(|relative| after.iter().find(|file| file.relative == relative)) else {
+            continue;
+        };
+
+        let is_showing = showing.key.as_ref() == Some(&key);
+        let markdown = if is_showing { showing.clone() } else { Markdown::load_from_storage(key.clone()) };
+        markdown.remove_from_browser_storage();
+        let markdown = Markdown { key: Some(AttrValue::from(new_file.path.clone())), ..markdown };
+        if is_showing {
+            md_ctx.set_markdown(markdown)?;
+        } else {
+            markdown.save_to_browser_storage()?;
+        }
+        old_paths.push(file.path.clone());
+    }
+    Ok(old_paths)
+}
+
+/// Close a deleted file, so nothing saves it back. If it was showing, the
+/// last document opened shows instead.
+#[cfg(not(feature = "web"))]
+fn close_deleted(
+    before: &[md::WorkspaceFile],
+    path: &str,
+    showing: &crate::contexts::markdown::Markdown,
+    md_ctx: &crate::contexts::markdown::MarkdownContext,
+) -> Result<(), error::UbiquityError> {
+    use crate::contexts::markdown::Markdown;
+
+    let Some(file) = before.iter().find(|file| file.relative == path) else {
+        return Ok(());
+    };
+    let key = AttrValue::from(file.path.clone());
+    if Markdown::read_all_markdown_keys().contains(&key) {
+        Markdown::load_from_storage(key.clone()).remove_from_browser_storage();
+    }
+    if showing.key == Some(key) {
+        md_ctx.set_markdown(Markdown::load_latest_from_storage().unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "web"))]
+fn close_drawer() {
+    use gloo::utils::document;
+    use wasm_bindgen::JsCast;
+    use web_sys::HtmlInputElement;
+
+    if let Some(drawer) = document().get_element_by_id("drawer-input").and_then(|drawer| drawer.dyn_into::<HtmlInputElement>().ok()) {
+        drawer.set_checked(false);
+    }
+}
+
+/// Pick a folder and remember it as the workspace
+#[cfg(not(feature = "web"))]
+fn open_folder(config: crate::contexts::config::ConfigContext, toaster: crate::contexts::toasts::ToasterContext) {
+    use error::UbiquityError;
+    use std::path::PathBuf;
+    use wasm_bindgen_futures::spawn_local;
+    use crate::components::toasts::ToastProps;
+    use crate::contexts::toasts::err_modal;
+    use crate::tauri::open_folder_dialog;
+
+    spawn_local(async move {
+        match open_folder_dialog().await {
+            Ok(workspace) => config.set_workspace(Some(PathBuf::from(workspace.root))).unwrap_or_else(|err| err_modal(err, toaster)),
+            Err(err) => {
+                if err != UbiquityError::no_folder_selected() {
+                    toaster.add_toast(ToastProps::from(err));
+                }
+            }
+        }
+    });
+}
+
+/// The markdown files of the folder open as a workspace, which can be
+/// opened, created, renamed, moved and deleted from here.
+#[cfg(not(feature = "web"))]
+#[function_component(FileTree)]
+pub fn file_tree() -> Html {
+    use std::collections::HashSet;
+    use md::Workspace;
+    use yew_router::prelude::use_navigator;
+    use wasm_bindgen_futures::spawn_local;
+    use web_sys::HtmlInputElement;
+    use crate::components::toasts::ToastProps;
+    use crate::components::tooltip::Tooltip;
+    use crate::contexts::{config::use_config, markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}};
+    use crate::icons::{EllipsisIcon, FolderAddIcon, MinusIcon, PlusIcon};
+    use crate::pages::Page;
+    use crate::tauri::{close_folder, create_workspace_file, delete_path, discard_recovery, move_path, read_markdown_from_fs, rename_path, scan_workspace};
+
+    let config = use_config();
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
+    let nav = use_navigator().unwrap();
+    let workspace = use_state(|| None::<Workspace>);
+    let closed = use_state(HashSet::<String>::new);
+    let selected = use_state(|| None::<Selected>);
+    let action = use_state(|| Action::Choose);
+    let name = use_state(String::new);
+
+    let root = config.state().workspace;
+    {
+        let workspace = workspace.clone();
+        let toaster = toaster.clone();
+        // Only the backend reaches into the folder, so it is scanned from there
+        use_effect_with_deps(move |root| {
+            if root.is_some() {
+                spawn_local(async move {
+                    match scan_workspace().await {
+                        Ok(scanned) => workspace.set(Some(scanned)),
+                        Err(err) => {
+                            workspace.set(None);
+                            toaster.add_toast(ToastProps::from(err));
+                        }
+                    }
+                });
+            } else {
+                workspace.set(None);
+            }
+            || ()
+        }, root);
+    }
+
+    let open_folder_cb = {
+        let config = config.clone();
+        let toaster = toaster.clone();
+        Callback::from(move |_| open_folder(config.clone(), toaster.clone()))
+    };
+
+    let Some(current) = (*workspace).clone() else {
+        return html! {
+            <div class="flex flex-col mt-4 px-4">
+                <button onclick={open_folder_cb} class="btn btn-ghost btn-sm normal-case">
+                    <FolderAddIcon />
+                    {"Open Folder"}
+                </button>
+            </div>
+        };
+    };
+
+    let close_folder = {
+        let config = config.clone();
+        let toaster = toaster.clone();
+        Callback::from(move |_| {
+            let (config, toaster) = (config.clone(), toaster.clone());
+            spawn_local(async move {
+                match close_folder().await {
+                    Ok(()) => config.set_workspace(None).unwrap_or_else(|err| err_modal(err, toaster)),
+                    Err(err) => toaster.add_toast(ToastProps::from(err)),
+                }
+            });
+        })
+    };
+
+    let choose = {
+        let selected = selected.clone();
+        let action = action.clone();
+        let name = name.clone();
+        move |path: String, is_folder: bool, chosen: Action| {
+            let selected = selected.clone();
+            let action = action.clone();
+            let name = name.clone();
+            Callback::from(move |_| {
+                let file_name = path.rsplit('/').next().unwrap_or_default().to_string();
+                name.set(if chosen == Action::Rename { file_name } else { String::new() });
+                selected.set(Some(Selected { path: path.clone(), is_folder }));
+                action.set(chosen);
+            })
+        }
+    };
+
+    let tree = Folder::from(&current.files);
+    let mut rows = Vec::new();
+    tree.rows("", 0, &closed, &mut rows);
+    let current_key = md_ctx.state().key;
+
+    let rows_html = rows.into_iter().map(|row| {
+        let indent = format!("padding-left: {}rem", 1.0 + row.depth as f32);
+        let actions = choose(row.path.clone(), row.file.is_none(), Action::Choose);
+
+        let onclick = match row.file.clone() {
+            Some(path) => {
+                let key = AttrValue::from(path);
+                let md_ctx = md_ctx.clone();
+                let toaster = toaster.clone();
+                let nav = nav.clone();
+                Callback::from(move |_| {
+                    close_drawer();
+                    nav.replace(&Page::Home);
+                    if md_ctx.state().key == Some(key.clone()) {
+                        return;
+                    }
+                    let (md_ctx, toaster, key) = (md_ctx.clone(), toaster.clone(), key.clone());
+                    spawn_local(async move {
+                        match read_markdown_from_fs(key.clone()).await {
+                            Ok(text) => {
+                                let md = Markdown::from(AttrValue::from(text), Some(key));
+                                md_ctx.add_markdown(md.clone()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                                md_ctx.set_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                            },
+                            Err(err) => toaster.add_toast(ToastProps::from(err)),
+                        }
+                    });
+                })
+            },
+            None => {
+                let closed = closed.clone();
+                let path = row.path.clone();
+                Callback::from(move |_| {
+                    let mut new_closed = (*closed).clone();
+                    if !new_closed.remove(&path) {
+                        new_closed.insert(path.clone());
+                    }
+                    closed.set(new_closed);
+                })
+            },
+        };
+
+        let is_open_file = row.file.is_some() && row.file.as_deref() == current_key.as_deref();
+        let marker = match (&row.file, row.open) {
+            (Some(_), _) => "",
+            (None, true) => "▾ ",
+            (None, false) => "▸ ",
+        };
+
+        html! {
+            <li>
+                <div class={classes!("flex", "justify-between", "py-1", is_open_file.then_some("active"))} style={indent}>
+                    <span onclick={onclick} class="flex-1 truncate" title={row.path.clone()}>
+                        {marker}{row.name}
+                    </span>
+                    <span onclick={actions} class="opacity-60 hover:opacity-100">
+                        <EllipsisIcon />
+                    </span>
+                </div>
+            </li>
+        }
+    }).collect::<Html>();
+
+    let folder_name = std::path::Path::new(&current.root).file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_else(|| current.root.clone());
+
+    let modal = match (*selected).clone() {
+        None => html! {},
+        Some(item) => {
+            let cancel = {
+                let selected = selected.clone();
+                Callback::from(move |_| selected.set(None))
+            };
+
+            let confirm = {
+                let selected = selected.clone();
+                let workspace = workspace.clone();
+                let md_ctx = md_ctx.clone();
+                let toaster = toaster.clone();
+                let item = item.clone();
+                let current = current.clone();
+                let (chosen, name) = (*action, (*name).clone());
+                Callback::from(move |_| {
+                    selected.set(None);
+                    let markdown = md_ctx.state();
+                    let (workspace, md_ctx, toaster, item, current, name) = (workspace.clone(), md_ctx.clone(), toaster.clone(), item.clone(), current.clone(), name.clone());
+                    spawn_local(async move {
+                        let done = match chosen {
+                            Action::Create => create_workspace_file(join(&item.path, &name)).await.map(Done::Created),
+                            Action::Rename => rename_path(item.path.clone(), name).await.map(Done::Moved),
+                            Action::Move => move_path(item.path.clone(), name).await.map(Done::Moved),
+                            Action::Delete => delete_path(item.path.clone()).await.map(|_| Done::Deleted),
+                            Action::Choose => return,
+                        };
+                        let done = match done {
+                            Ok(done) => done,
+                            Err(err) => return toaster.add_toast(ToastProps::from(err)),
+                        };
+
+                        let scanned = match scan_workspace().await {
+                            Ok(scanned) => scanned,
+                            Err(err) => return toaster.add_toast(ToastProps::from(err)),
+                        };
+                        workspace.set(Some(scanned.clone()));
+
+                        match done {
+                            Done::Created(path) => {
+                                let md = Markdown::from(AttrValue::from(""), Some(AttrValue::from(path)));
+                                md_ctx.add_markdown(md.clone()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                                md_ctx.set_markdown(md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+                            },
+                            Done::Moved(to) => {
+                                let old_paths = match follow_move(&current.files, &scanned.files, &item.path, &to, &markdown, &md_ctx) {
+                                    Ok(old_paths) => old_paths,
+                                    Err(err) => return err_modal(err, toaster),
+                                };
+                                // Recovering their edits would put the files back where they were
+                                for path in old_paths {
+                                    if let Err(err) = discard_recovery(path).await {
+                                        toaster.add_toast(ToastProps::from(err));
+                                    }
+                                }
+                            },
+                            Done::Deleted => close_deleted(&current.files, &item.path, &markdown, &md_ctx).unwrap_or_else(|err| err_modal(err, toaster.clone())),
+                        }
+                    });
+                })
+            };
+
+            let set_name = {
+                let name = name.clone();
+                Callback::from(move |e: InputEvent| {
+                    let input: HtmlInputElement = e.target_unchecked_into();
+                    name.set(input.value());
+                })
+            };
+
+            // Whether there is enough to go ahead with
+            let (title, body, confirm_name, ready) = match *action {
+                Action::Choose => {
+                    let buttons = match item.is_folder {
+                        true => vec![(Action::Create, "New file here"), (Action::Rename, "Rename"), (Action::Move, "Move")],
+                        false => vec![(Action::Rename, "Rename"), (Action::Move, "Move"), (Action::Delete, "Delete")],
+                    };
+                    let buttons = buttons.into_iter().map(|(chosen, label)| html! {
+                        <button onclick={choose(item.path.clone(), item.is_folder, chosen)} class="btn btn-ghost justify-start">{label}</button>
+                    }).collect::<Html>();
+                    ("", html! { <div class="flex flex-col py-2">{buttons}</div> }, None, false)
+                },
+                Action::Create => {
+                    let body = html! {
+                        <input type="text" placeholder="notes.md" value={(*name).clone()} oninput={set_name} class="input input-bordered w-full my-4" />
+                    };
+                    ("New file", body, Some("Create"), !name.trim().is_empty())
+                },
+                Action::Rename => {
+                    let body = html! {
+                        <input type="text" value={(*name).clone()} oninput={set_name} class="input input-bordered w-full my-4" />
+                    };
+                    ("Rename", body, Some("Rename"), !name.trim().is_empty())
+                },
+                Action::Move => {
+                    let mut folders = Vec::new();
+                    tree.paths("", &mut folders);
+                    // Not into itself, nor where it already is
+                    let parent = item.path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or_default();
+                    folders.retain(|folder| moved(folder, &item.path, "").is_none() && folder != parent);
+                    let ready = folders.contains(&name);
+                    let folders = folders.into_iter()
+                        .map(|folder| {
+                            let chosen = *name == folder;
+                            let choose_folder = {
+                                let name = name.clone();
+                                let folder = folder.clone();
+                                Callback::from(move |_| name.set(folder.clone()))
+                            };
+                            let label = if folder.is_empty() { folder_name.clone() } else { folder.clone() };
+                            html! {
+                                <label class="label cursor-pointer">
+                                    <span class="label-text font-mono truncate">{label}</span>
+                                    <input type="radio" name="move-to" class="radio" checked={chosen} onchange={choose_folder} />
+                                </label>
+                            }
+                        }).collect::<Html>();
+                    ("Move to", html! { <div class="max-h-64 overflow-auto py-2">{folders}</div> }, Some("Move"), ready)
+                },
+                Action::Delete => {
+                    let body = html! { <p class="py-4">{"The file is deleted from disk, which cannot be undone."}</p> };
+                    ("Delete", body, Some("Delete"), true)
+                },
+            };
+
+            let item_name = if item.path.is_empty() { folder_name.clone() } else { item.path.clone() };
+            html! {
+                <div class="modal modal-open">
+                    <div class="modal-box">
+                        if !title.is_empty() {
+                            <h3 class="font-bold text-2xl">{title}</h3>
+                        }
+                        <p class="font-mono py-2 truncate">{item_name}</p>
+                        { body }
+                        <div class="modal-action">
+                            <button onclick={cancel} class="btn btn-ghost">{"Cancel"}</button>
+                            if let Some(confirm_name) = confirm_name {
+                                <button onclick={confirm} disabled={!ready} class={classes!("btn", if *action == Action::Delete { "btn-error" } else { "btn-primary" })}>{confirm_name}</button>
+                            }
+                        </div>
+                    </div>
+                </div>
+            }
+        },
+    };
+
+    html! {
+        <div class="flex flex-col flex-1 min-h-0 mt-4">
+            <div class="flex items-center justify-between px-4 mb-2">
+                <h2 class="font-display text-lg truncate" title={current.root.clone()}>{&folder_name}</h2>
+                <div class="flex">
+                    <Tooltip tip={"New File"}>
+                        <button onclick={choose(String::new(), true, Action::Create)} class="btn btn-ghost btn-xs"><PlusIcon /></button>
+                    </Tooltip>
+                    <Tooltip tip={"Open Folder"}>
+                        <button onclick={open_folder_cb} class="btn btn-ghost btn-xs"><FolderAddIcon /></button>
+                    </Tooltip>
+                    <Tooltip tip={"Close Folder"}>
+                        <button onclick={close_folder} class="btn btn-ghost btn-xs"><MinusIcon /></button>
+                    </Tooltip>
+                </div>
+            </div>
+            <ul class="menu menu-compact flex-nowrap overflow-auto w-full">
+                { rows_html }
+            </ul>
+            { modal }
+        </div>
+    }
+}
+
+/// Folders are only opened from disk on desktop.
+#[cfg(feature = "web")]
+#[function_component(FileTree)]
+pub fn file_tree() -> Html {
+    html! {}
+}
+
+#[cfg(not(feature = "web"))]
+#[function_component(OpenFolderBtn)]
+pub fn open_folder_btn() -> Html {
+    use crate::contexts::{config::use_config, toasts::use_toaster};
+
+    let config = use_config();
+    let toaster = use_toaster();
+    let open = Callback::from(move |_| open_folder(config.clone(), toaster.clone()));
+
+    html! {
+        <li>
+            <div onclick={open}>
+                {"Open Folder"}
+            </div>
+        </li>
+    }
+}
+
+#[cfg(feature = "web")]
+#[function_component(OpenFolderBtn)]
+pub fn open_folder_btn() -> Html {
+    html! {}
+}
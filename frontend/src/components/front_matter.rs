@@ -0,0 +1,106 @@
+use std::rc::Rc;
+use error::UbiquityError;
+use md::FrontMatter;
+use yew::prelude::*;
+use crate::contexts::markdown::use_markdown;
+
+/// The front matter of the current markdown, parsed again only when the text changes
+#[hook]
+fn use_front_matter() -> Rc<Result<Option<FrontMatter>, UbiquityError>> {
+    let markdown = use_markdown().state();
+    use_memo(|markdown| markdown.front_matter(), markdown)
+}
+
+/// The author and date joined into one line, as shown under a title
+fn byline(front_matter: &FrontMatter) -> Option<String> {
+    let parts: Vec<&str> = [&front_matter.author, &front_matter.date]
+        .into_iter()
+        .filter_map(|part| part.as_deref())
+        .collect();
+
+    (!parts.is_empty()).then(|| parts.join(" · "))
+}
+
+fn tag_badges(front_matter: &FrontMatter) -> Html {
+    front_matter
+        .tags
+        .iter()
+        .map(|tag| html! { <span class="badge badge-outline">{tag}</span> })
+        .collect()
+}
+
+/// The preview's view of the front matter: a collapsed card listing every
+/// field, or the reason the block could not be read.
+#[function_component(FrontMatterCard)]
+pub fn front_matter_card() -> Html {
+    let front_matter = use_front_matter();
+
+    match front_matter.as_ref() {
+        Ok(Some(front_matter)) => {
+            let rows = front_matter.fields.iter().map(|(key, value)| html! {
+                <tr>
+                    <th class="font-mono">{key}</th>
+                    <td class="whitespace-pre-wrap break-words">{value}</td>
+                </tr>
+            });
+
+            html! {
+                <div class="collapse collapse-arrow bg-base-200 rounded-xl mb-4 print:hidden">
+                    <input type="checkbox" />
+                    <div class="collapse-title">
+                        <div class="flex items-center gap-2">
+                            <span class="font-medium">{front_matter.title.as_deref().unwrap_or("Metadata")}</span>
+                            <span class="badge badge-sm badge-ghost">{front_matter.format.name()}</span>
+                        </div>
+                        if let Some(byline) = byline(front_matter) {
+                            <div class="text-sm opacity-70">{byline}</div>
+                        }
+                    </div>
+                    <div class="collapse-content">
+                        if !front_matter.tags.is_empty() {
+                            <div class="flex flex-wrap gap-1 mb-2">{tag_badges(front_matter)}</div>
+                        }
+                        <table class="table table-xs">
+                            <tbody>{ for rows }</tbody>
+                        </table>
+                    </div>
+                </div>
+            }
+        }
+        Ok(None) => html! {},
+        Err(err) => html! {
+            <div class="alert alert-error mb-4 print:hidden">
+                <div>
+                    <p class="font-bold">{&err.title}</p>
+                    <p>{&err.human_description}</p>
+                    if let Some(verbose_description) = &err.verbose_description {
+                        <p class="text-sm font-mono">{verbose_description}</p>
+                    }
+                </div>
+            </div>
+        },
+    }
+}
+
+/// The title, byline and tags from the front matter, heading the exported pdf.
+#[function_component(FrontMatterHeader)]
+pub fn front_matter_header() -> Html {
+    let front_matter = use_front_matter();
+
+    match front_matter.as_ref() {
+        Ok(Some(front_matter)) => html! {
+            <header>
+                if let Some(title) = &front_matter.title {
+                    <h1>{title}</h1>
+                }
+                if let Some(byline) = byline(front_matter) {
+                    <p class="lead">{byline}</p>
+                }
+                if !front_matter.tags.is_empty() {
+                    <div class="flex flex-wrap gap-1 not-prose">{tag_badges(front_matter)}</div>
+                }
+            </header>
+        },
+        _ => html! {},
+    }
+}
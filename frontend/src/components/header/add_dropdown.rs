@@ -7,6 +7,7 @@ use crate::icons::{AddFileIcon, RESPONSIVE_ICON_LG};
 use crate::tauri::read_markdown_from_fs;
 use wasm_bindgen_futures::spawn_local;
 use crate::components::tooltip::Tooltip;
+use crate::components::file_tree::OpenFolderBtn;
 
 #[function_component(AddFileDropdown)]
 pub fn add_file_dropdown() -> Html {
@@ -51,11 +52,22 @@ pub fn add_file_dropdown() -> Html {
         });
 
         let file_name = recent_file.clone();
+        // Documents with front matter are listed by their title, over the file name
+        let title = Markdown::load_from_storage(file_name.clone())
+            .front_matter()
+            .ok()
+            .flatten()
+            .and_then(|front_matter| front_matter.title);
         let html = html! {
             <li>
                 <a>
                     <div onclick={read_file}>
-                    {file_name}
+                    if let Some(title) = title {
+                        <div>{title}</div>
+                        <div class="text-xs opacity-60">{file_name}</div>
+                    } else {
+                        {file_name}
+                    }
                     </div>
                 </a>
             </li>
@@ -79,6 +91,7 @@ pub fn add_file_dropdown() -> Html {
                 <ul tabindex="0">
                     <CreateFileBtn />
                     <AddFileBtn />
+                    <OpenFolderBtn />
                 </ul>
                 if !recent_files.is_empty() {
                     <ul tabindex="0">
@@ -169,7 +182,7 @@ pub fn add_file_btn() -> Html {
         spawn_local(async move {
             let file_str = read_as_text(&blob).await.unwrap();
             let text = AttrValue::from(file_str);
-            let markdown = Markdown { key, text };
+            let markdown = Markdown::from(text, key);
             markdown_ctx.add_markdown(markdown);
         });
     });
@@ -4,8 +4,8 @@ use urlencoding::encode;
 use crate::components::tooltip::Tooltip;
 
 use crate::{
-    components::{theme_card::ThemeDropdownItem, header::{save_btn::SaveBtn, add_dropdown::AddFileDropdown}},
-    icons::{EllipsisIcon, PaletteIcon, RESPONSIVE_ICON_LG},
+    components::{theme_card::ThemeDropdownItem, header::{save_btn::SaveBtn, save_status::SaveStatus, add_dropdown::AddFileDropdown}},
+    icons::{EllipsisIcon, FolderTreeIcon, PaletteIcon, RESPONSIVE_ICON_LG},
     Page, contexts::markdown::use_markdown,
 };
 
@@ -37,8 +37,16 @@ pub fn desktop_header() -> Html {
     html! {
         <div class="navbar bg-base-300">
             <div class="navbar-start">
+                if cfg!(not(feature = "web")) {
+                    <Tooltip tip={"Files"}>
+                        <label for="drawer-input" class="btn btn-ghost rounded-btn">
+                            <FolderTreeIcon classes={RESPONSIVE_ICON_LG} />
+                        </label>
+                    </Tooltip>
+                }
                 <AddFileDropdown />
                 <SaveBtn />
+                <SaveStatus />
             </div>
 
             <div class="navbar-center">
@@ -3,7 +3,7 @@ use yew_router::prelude::use_navigator;
 use urlencoding::encode;
 
 use crate::{
-    components::header::{save_btn::SaveBtn, add_dropdown::AddFileDropdown, desktop::DOWNLOAD_ANCHOR_ID},
+    components::header::{save_btn::SaveBtn, save_status::SaveStatus, add_dropdown::AddFileDropdown, desktop::DOWNLOAD_ANCHOR_ID},
     icons::{HamburgerIcon, WrenchIcon, RESPONSIVE_ICON_LG},
     Page, contexts::markdown::use_markdown,
 };
@@ -37,6 +37,7 @@ pub fn mobile_header() -> Html {
                 <HamburgerIcon classes={RESPONSIVE_ICON_LG}/>
             </label>
             <div class={header_end_classes}>
+                <SaveStatus />
                 <AddFileDropdown />
                 <SaveBtn />
                 <btn onclick={settings_cb} class={header_btn_classes}>
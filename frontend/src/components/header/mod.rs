@@ -1,4 +1,5 @@
 pub mod save_btn;
+pub mod save_status;
 pub mod add_dropdown;
 pub mod mobile;
 pub mod desktop;
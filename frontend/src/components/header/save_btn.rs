@@ -1,38 +1,121 @@
 use gloo::utils::window;
-use md::DOCS_KEY;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 use crate::contexts::{markdown::use_markdown, toasts::{use_toaster, err_modal}};
-use crate::contexts::markdown::Markdown;
+use crate::contexts::markdown::{Markdown, MarkdownContext};
 use crate::icons::SaveIcon;
 use crate::components::tooltip::Tooltip;
+use crate::components::editor::textarea::{caret, textarea::EDITOR_ID};
+use crate::components::editor::header::format_doc::format_document;
+use config::Config;
+use error::UbiquityError;
+use mdtg::ExportFormat;
+use web_sys::HtmlTextAreaElement;
+
+/// Export the table under the caret, or every table in the document when the caret is outside of one.
+fn export_tables(format: ExportFormat) -> Result<String, UbiquityError> {
+    let editor: HtmlTextAreaElement = gloo::utils::document().get_element_by_id(&EDITOR_ID).unwrap().dyn_into().unwrap();
+    let text = editor.value();
+    let offset = editor
+        .selection_start()
+        .unwrap()
+        .map(|caret| caret::byte_offset(&text, caret))
+        .filter(|offset| mdtg::find_table(&text, *offset).is_some());
+
+    mdtg::export_tables(&text, offset, format).map_err(UbiquityError::mdtg)
+}
+
+/// Print the document to pdf. The front matter title becomes the page title
+/// while printing, as that is what the pdf is named after.
+fn print_pdf(markdown: &Markdown) -> Result<(), UbiquityError> {
+    let title = markdown.front_matter()?.and_then(|front_matter| front_matter.title);
+    let document = gloo::utils::document();
+    let page_title = document.title();
+
+    if let Some(title) = title {
+        document.set_title(&title);
+    }
+    window().print().unwrap();
+    document.set_title(&page_title);
+
+    Ok(())
+}
+
+/// The text to save, formatted first when format on save is turned on
+fn text_to_save(md_ctx: &MarkdownContext, config: &Config) -> Result<AttrValue, UbiquityError> {
+    let markdown = md_ctx.state();
+    match config.format_on_save {
+        true => format_document(md_ctx, &markdown.render_options(config.render.clone())),
+        false => Ok(markdown.text),
+    }
+}
 
 #[cfg(feature = "web")]
 #[function_component(SaveBtn)]
 pub fn save_btn() -> Html {
     use gloo::utils::document;
     use web_sys::{HtmlInputElement, HtmlAnchorElement};
-    use md::DOCS_KEY;
     use crate::contexts::config::use_config;
 
     use crate::icons::RESPONSIVE_ICON_LG;
 
-    let key = use_markdown().state().key;
+    let md_ctx = use_markdown();
+    let config = use_config();
+    let toaster = use_toaster();
     let save = Callback::from(move |_| {
-        let key = key.clone();
-        if let Some(key) = key && !key.eq(&DOCS_KEY) {
+        let text = match text_to_save(&md_ctx, &config.state()) {
+            Ok(text) => text,
+            Err(err) => return err_modal(err, toaster.clone()),
+        };
+        let markdown = md_ctx.state();
+        // The bundled docs are saved as a new file, whatever their key
+        if let Some(key) = markdown.key && !markdown.bundled {
+            // The download link only picks up the new text on the next render, so point it at the text being saved
             let anchor: HtmlAnchorElement = document().get_element_by_id("dl").unwrap().dyn_into().unwrap();
+            anchor.set_href(&format!("data:attachment/text,{}", urlencoding::encode(&text)));
             anchor.click();
+            md_ctx.mark_saved(Markdown::from(text, Some(key))).unwrap_or_else(|err| err_modal(err, toaster.clone()));
         } else {
             let input: HtmlInputElement = document().get_element_by_id("save-modal").unwrap().dyn_into().unwrap();
             input.set_checked(true);
         }
     });
     
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
     let export_pdf = Callback::from(move |_| {
-        window().print();
+        print_pdf(&md_ctx.state()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
     });
+
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
+    let export = move |format: ExportFormat| {
+        let md_ctx = md_ctx.clone();
+        let toaster = toaster.clone();
+        Callback::from(move |_: MouseEvent| {
+            // Named after the document's title when its front matter has one
+            let name = match md_ctx.state().front_matter() {
+                Ok(Some(md::FrontMatter { title: Some(title), .. })) => format!("{}-tables", md::slugify(&title)),
+                _ => String::from("tables"),
+            };
+            match export_tables(format) {
+                Ok(contents) => {
+                    let mime = match format {
+                        ExportFormat::Csv => "text/csv",
+                        ExportFormat::Json => "application/json",
+                    };
+                    let anchor: HtmlAnchorElement = document().create_element("a").unwrap().dyn_into().unwrap();
+                    anchor.set_href(&format!("data:{};charset=utf-8,{}", mime, urlencoding::encode(&contents)));
+                    anchor.set_download(&format!("{}.{}", name, format.extension()));
+                    anchor.click();
+                }
+                Err(err) => err_modal(err, toaster.clone()),
+            }
+        })
+    };
+    let export_csv = export(ExportFormat::Csv);
+    let export_json = export(ExportFormat::Json);
     
     let mut dropdown_classes = classes!("dropdown");
     if use_config().is_mobile_ui() {
@@ -64,6 +147,16 @@ pub fn save_btn() -> Html {
                             {"Export as PDF"}
                         </div>
                     </li>
+                    <li>
+                        <div onclick={export_csv}>
+                            {"Export tables as CSV"}
+                        </div>
+                    </li>
+                    <li>
+                        <div onclick={export_json}>
+                            {"Export tables as JSON"}
+                        </div>
+                    </li>
                 </ul>
             </div>
         </div>
@@ -73,24 +166,30 @@ pub fn save_btn() -> Html {
 #[cfg(not(feature = "web"))]
 #[function_component(SaveBtn)]
 pub fn save_btn() -> Html {
-    use error::UbiquityError;
-    use gloo::utils::{window, document};
-    use crate::{tauri::save_markdown_to_fs, components::toasts::ToastProps, icons::RESPONSIVE_ICON_LG, contexts::config::use_config};
+    use crate::{tauri::{save_markdown_to_fs, export_to_fs}, components::toasts::{ToastProps, ToastType}, icons::RESPONSIVE_ICON_LG, contexts::config::use_config};
 
     let md_ctx = use_markdown();
+    let config = use_config();
     let toaster = use_toaster();
     let save_fs: Callback<MouseEvent> = Callback::from(move |_| {
         let clone = md_ctx.clone();
-        let markdown = clone.state();
+        let text = match text_to_save(&clone, &config.state()) {
+            Ok(text) => text,
+            Err(err) => return err_modal(err, toaster.clone()),
+        };
         let toaster = toaster.clone();
-        let key = clone.state().key;
+        let markdown = clone.state();
+        // The bundled docs are saved as a new file, whatever their key
+        let key = markdown.key.filter(|_| !markdown.bundled);
+        let backups = config.state().backups;
         spawn_local(async move {
-            let save_as_markdown = Markdown::from(markdown.text.clone(), key);
-            let path: Result<String, UbiquityError> = save_markdown_to_fs(save_as_markdown).await;
+            let save_as_markdown = Markdown::from(text.clone(), key);
+            let path: Result<String, UbiquityError> = save_markdown_to_fs(save_as_markdown, backups).await;
             match path {
                 Ok(path) => {
                     let key = AttrValue::from(path);
                     clone.update_key(key.clone());
+                    clone.mark_saved(Markdown::from(text, Some(key))).unwrap_or_else(|err| err_modal(err, toaster.clone()));
                 },
                 Err(err) => {
                     if err != UbiquityError::no_save_path_selected() {
@@ -103,18 +202,23 @@ pub fn save_btn() -> Html {
     });
 
     let md_ctx = use_markdown();
+    let config = use_config();
     let toaster = use_toaster();
     let save_as_fs: Callback<MouseEvent> = Callback::from(move |_| {
         let clone = md_ctx.clone();
-        let markdown = clone.state();
+        let text = match text_to_save(&clone, &config.state()) {
+            Ok(text) => text,
+            Err(err) => return err_modal(err, toaster.clone()),
+        };
         let toaster = toaster.clone();
+        let backups = config.state().backups;
         spawn_local(async move {
-            let save_as_markdown = Markdown::from(markdown.text.clone(), None);
-            let path: Result<String, UbiquityError> = save_markdown_to_fs(save_as_markdown).await;
+            let save_as_markdown = Markdown::from(text.clone(), None);
+            let path: Result<String, UbiquityError> = save_markdown_to_fs(save_as_markdown, backups).await;
             match path {
                 Ok(path) => {
                     let key = Some(AttrValue::from(path));
-                    let new_md = Markdown::from(markdown.text, key);
+                    let new_md = Markdown::from(text, key);
                     clone.add_markdown(new_md.clone()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
                     clone.set_markdown(new_md).unwrap_or_else(|err| err_modal(err, toaster.clone()));
                 },
@@ -128,9 +232,40 @@ pub fn save_btn() -> Html {
         });
     });
 
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
     let export_pdf: Callback<MouseEvent> = Callback::from(move |_| {
-        window().print().unwrap();
+        print_pdf(&md_ctx.state()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
     });
+
+    let toaster = use_toaster();
+    let export = move |format: ExportFormat| {
+        let toaster = toaster.clone();
+        Callback::from(move |_: MouseEvent| {
+            let contents = match export_tables(format) {
+                Ok(contents) => contents,
+                Err(err) => return err_modal(err, toaster.clone()),
+            };
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match export_to_fs(contents, format.extension().to_string()).await {
+                    Ok(path) => {
+                        let title = AttrValue::from("Tables exported");
+                        let description = AttrValue::from(path);
+                        toaster.add_toast(ToastProps { toast_type: ToastType::Success, title, description, verbose: None });
+                    },
+                    Err(err) => {
+                        if err != UbiquityError::no_save_path_selected() {
+                            let toast = ToastProps::from(err);
+                            toaster.add_toast(toast);
+                        }
+                    }
+                }
+            });
+        })
+    };
+    let export_csv = export(ExportFormat::Csv);
+    let export_json = export(ExportFormat::Json);
     
     let mut dropdown_classes = classes!("dropdown");
     if use_config().is_mobile_ui() {
@@ -161,6 +296,16 @@ pub fn save_btn() -> Html {
                             {"Export as PDF"}
                         </div>
                     </li>
+                    <li>
+                        <div onclick={export_csv}>
+                            {"Export tables as CSV"}
+                        </div>
+                    </li>
+                    <li>
+                        <div onclick={export_json}>
+                            {"Export tables as JSON"}
+                        </div>
+                    </li>
                 </ul>
             </div>
         </div>
@@ -0,0 +1,29 @@
+use yew::prelude::*;
+
+/// A dot showing whether the open file has edits that have not been saved yet.
+#[cfg(not(feature = "web"))]
+#[function_component(SaveStatus)]
+pub fn save_status() -> Html {
+    use crate::components::tooltip::Tooltip;
+    use crate::contexts::markdown::use_markdown;
+
+    let dirty = use_markdown().state().dirty;
+
+    let (tip, badge) = match dirty {
+        true => ("Unsaved changes", "badge-warning"),
+        false => ("All changes saved", "badge-success"),
+    };
+
+    html! {
+        <Tooltip tip={tip}>
+            <span class={classes!("badge", "badge-xs", badge)} aria-label={tip} />
+        </Tooltip>
+    }
+}
+
+/// Every edit on the web is kept in browser storage as it is made, so there is never anything unsaved.
+#[cfg(feature = "web")]
+#[function_component(SaveStatus)]
+pub fn save_status() -> Html {
+    html! {}
+}
\ No newline at end of file
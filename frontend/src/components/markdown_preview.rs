@@ -1,23 +1,51 @@
+use gloo::timers::callback::Timeout;
+use md::RenderOptions;
 use yew::prelude::*;
-use markdown::{self, Options, ParseOptions, CompileOptions};
 
+use crate::components::front_matter::FrontMatterCard;
+use crate::components::scroll_sync::{place_caret_at_block, use_scroll_sync, PREVIEW_SCROLL_ID};
 use crate::{contexts::{markdown::use_markdown, config::use_config, toasts::{use_toaster, err_modal}}, icons::*, components::tooltip::Tooltip};
 
+/// How long typing has to pause for before the preview is rendered again
+const RENDER_DELAY_MILLIS: u32 = 150;
+
 /// A HTML preview of the user's markdown.
 #[function_component(MarkdownPreview)]
 pub fn markdown_preview() -> Html {
     let markdown = use_markdown().state();
 
-    let compile = CompileOptions {
-        allow_dangerous_html: true,
-        allow_dangerous_protocol: true,
-        ..CompileOptions::default()
+    let config = use_config().state();
+    let render_options = RenderOptions { source_lines: true, ..markdown.render_options(config.render) };
+
+    let rendered = {
+        let (text, options) = (markdown.text.clone(), render_options.clone());
+        use_state(move || md::render(&text, &options))
+    };
+    {
+        let rendered = rendered.clone();
+        // Opening or saving a file shows it straight away, while edits wait for typing to pause
+        use_effect_with_deps(move |(text, options, dirty)| {
+            let render = {
+                let (text, options) = (text.clone(), options.clone());
+                move || rendered.set(md::render(&text, &options))
+            };
+
+            let timeout = match dirty {
+                true => Some(Timeout::new(RENDER_DELAY_MILLIS, render)),
+                false => {
+                    render();
+                    None
+                }
+            };
+            move || drop(timeout)
+        }, (markdown.text.clone(), render_options, markdown.dirty));
+    }
+    let md_html = match &*rendered {
+        Ok(rendered) => Html::from_html_unchecked(AttrValue::from(rendered.html.clone())),
+        Err(err) => html! { <p class="text-error">{err}</p> },
     };
-    let parse = ParseOptions::gfm();
-    let options = &Options { compile, parse };
 
-    let md = markdown::to_html_with_options(&markdown.text, options).unwrap();
-    let md_html = Html::from_html_unchecked(AttrValue::from(md));
+    use_scroll_sync(config.scroll_sync);
 
     let prose_size = use_config().state().md_preview_font_size;
     let classes = classes!(
@@ -67,8 +95,9 @@ pub fn markdown_preview() -> Html {
                     <btn onclick={increase_prose_size} class={btn_classes.clone()}><FontIncreaseIcon/></btn>
                 </Tooltip>
             </div>
-            <div class="overflow-auto">
-                <article id="preview" class={classes}>
+            <div id={PREVIEW_SCROLL_ID} class="overflow-auto">
+                <FrontMatterCard />
+                <article id="preview" class={classes} onclick={Callback::from(place_caret_at_block)}>
                     { md_html }
                 </article>
             </div>
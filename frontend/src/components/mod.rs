@@ -1,13 +1,20 @@
 // pub mod btn;
+pub mod conflict;
 pub mod container;
 pub mod divider;
 pub mod drawer;
 pub mod dual_view;
+pub mod file_tree;
+pub mod front_matter;
 pub mod header;
 pub mod link_btn;
 pub mod markdown_preview;
+pub mod outline;
 pub mod editor;
 pub mod modals;
+pub mod scroll_sync;
+pub mod autosave;
+pub mod recovery;
 pub mod single_view;
 pub mod toasts;
 pub mod theme_card;
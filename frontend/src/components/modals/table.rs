@@ -2,7 +2,7 @@ use lazy_static::__Deref;
 use yew::prelude::*;
 use crate::{contexts::{markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}}, components::{editor::textarea::textarea::EDITOR_ID, modals::utils::close_modal}};
 use web_sys::{HtmlInputElement, HtmlParagraphElement, HtmlTextAreaElement};
-use gloo::{utils::document, console::debug};
+use gloo::utils::document;
 use wasm_bindgen::JsCast;
 
 
@@ -13,6 +13,9 @@ const ROW_TEXTBOX_ID: AttrValue = AttrValue::Static("row_input");
 
 const TABLE_PREVIEW_ID: AttrValue = AttrValue::Static("table_preview");
 
+const CSV_TEXTAREA_ID: AttrValue = AttrValue::Static("csv_input");
+const PLACEHOLDER_CSV: AttrValue = AttrValue::Static("Name,Age,City\nLuka,23,Sydney");
+
 #[function_component(TableModal)]
 pub fn table_modal() -> Html {
     let columns_state: UseStateHandle<Vec<Column>> = use_state_eq(|| {
@@ -20,11 +23,13 @@ pub fn table_modal() -> Html {
     });
     let col_amount: u32 = columns_state.len() as u32;
     let row_amount: UseStateHandle<u32> = use_state_eq(|| 1);
-
+    let tab: UseStateHandle<Tab> = use_state_eq(|| Tab::Generate);
+    let csv: UseStateHandle<String> = use_state_eq(String::new);
 
     let table_input = use_memo(
         |deps| {
         let mut columns_alignment_string = String::from("");
+        let mut headers: Vec<String> = Vec::new();
     
         columns_state.iter().for_each(|column| {
             let alignment = match column.alignment {
@@ -33,26 +38,59 @@ pub fn table_modal() -> Html {
                 Alignment::Right => 'r',
             };
             columns_alignment_string.push(alignment);
+            headers.push(mdtg::escape_cell(&column.header));
         });
 
-            format!("{}{} x {}", deps.0, columns_alignment_string, deps.1.to_string())
+            format!("{}{} x {} [{}]", deps.0, columns_alignment_string, deps.1.to_string(), headers.join(", "))
         },
         (col_amount, row_amount.clone(), columns_state.clone()),
     );
 
     let table = use_memo(
-        |table| {
-            let table_result = mdtg::get_table(table.to_string());
-            if let Ok(table) = table_result {
-                table
+        |table| mdtg::get_table(table.to_string()),
+        table_input.clone(),
+    );
+
+    let (table_preview, table_error) = match table.as_ref() {
+        Ok(table) => (table.clone(), None),
+        Err(err) => (String::new(), Some(err.annotate(&table_input))),
+    };
+
+    let csv_table = use_memo(
+        |csv| {
+            if csv.trim().is_empty() {
+                Ok(String::new())
             } else {
-                debug!("Error making table: {}", table_result.unwrap_err().to_string());
-                "".to_string()
+                mdtg::Table::from_csv(csv, None, None).map(|table| table.to_string())
             }
         },
-        table_input.clone(),
+        (*csv).clone(),
     );
 
+    let on_csv_input = Callback::from({
+        let csv = csv.clone();
+
+        move |_| {
+            let csv_textarea: HtmlTextAreaElement = document().get_element_by_id(&CSV_TEXTAREA_ID).unwrap().dyn_into().unwrap();
+            csv.set(csv_textarea.value());
+        }
+    });
+
+    let generate_tab = Callback::from({
+        let tab = tab.clone();
+        move |_| tab.set(Tab::Generate)
+    });
+
+    let csv_tab = Callback::from({
+        let tab = tab.clone();
+        move |_| tab.set(Tab::Csv)
+    });
+
+    let (csv_preview, csv_error) = match csv_table.as_ref() {
+        Ok(table) => (table.clone(), None),
+        Err(err) => (String::new(), Some(err.clone())),
+    };
+
     let mut col_alignment_html: Vec<Html> = Vec::new();
 
     let mut i = 0;
@@ -69,6 +107,19 @@ pub fn table_modal() -> Html {
             Alignment::Center => "1",
             Alignment::Right => "2",
         };
+        let current_header = AttrValue::from(current_column.header.clone());
+
+        let onheaderinput = Callback::from({
+            let columns_state: UseStateHandle<Vec<Column>> = columns_state.clone();
+
+            move |input_event: InputEvent| {
+                let target: HtmlInputElement = input_event.target().unwrap().dyn_into().unwrap();
+                let mut new_columns = columns_state.deref().to_owned();
+
+                new_columns[i as usize].header = target.value();
+                columns_state.set(new_columns);
+            }
+        });
         
         let oninput = Callback::from({
             let columns_state: UseStateHandle<Vec<Column>> = columns_state.clone();
@@ -85,23 +136,30 @@ pub fn table_modal() -> Html {
                         Alignment::Center
                     }
                 };
-                let column = Column::from(alignment);
                 let mut new_columns = columns_state.deref().to_owned();
 
-                new_columns.remove(i as usize);
-                new_columns.insert(i as usize, column);
+                new_columns[i as usize].alignment = alignment;
                 columns_state.set(new_columns);
 
             }
         });
 
         let html = html! {
-            <div class="w-48">
-                <input {oninput} type="range" min="0" max="2" value={current_alignment} class="range range-xs" />
-                <div class="flex justify-between text-xs px-2 font-light text-sm">
-                    <span>{"Left"}</span>
-                    <span>{"Center"}</span>
-                    <span>{"Right"}</span>
+            <div class="flex flex-row items-end space-x-8">
+                <div class="form-control w-40">
+                    <label class="label">
+                        <span class="label-text">{title}</span>
+                    </label>
+                    <input oninput={onheaderinput} type="text" placeholder={"Header"} value={current_header}
+                        class="input input-bordered input-sm w-40" />
+                </div>
+                <div class="w-48">
+                    <input {oninput} type="range" min="0" max="2" value={current_alignment} class="range range-xs" />
+                    <div class="flex justify-between text-xs px-2 font-light text-sm">
+                        <span>{"Left"}</span>
+                        <span>{"Center"}</span>
+                        <span>{"Right"}</span>
+                    </div>
                 </div>
             </div>
         };
@@ -138,9 +196,13 @@ pub fn table_modal() -> Html {
 
     let markdown_ctx = use_markdown();
     let toaster = use_toaster();
-    let table = table.clone();
     let key = markdown_ctx.state().key;
+    let tab_clone = tab.clone();
     let insert_table = Callback::from(move |_| {
+        if *tab_clone == Tab::Generate && let Err(err) = table.as_ref() {
+            return err_modal(err.to_ubiquity_error(&table_input), toaster.clone());
+        }
+
         let link_title_input: HtmlParagraphElement = document().get_element_by_id(&TABLE_PREVIEW_ID).unwrap().dyn_into().unwrap();
         let table = link_title_input.text_content().unwrap();
 
@@ -165,6 +227,37 @@ pub fn table_modal() -> Html {
             <div class="modal">
                 <div class="modal-box">
                     <h1 class="font-bold text-2xl">{"Add Table"}</h1>
+                    <div class="tabs mt-4">
+                        <a onclick={generate_tab} class={classes!("tab", "tab-bordered", (*tab == Tab::Generate).then_some("tab-active"))}>{"Generate"}</a>
+                        <a onclick={csv_tab} class={classes!("tab", "tab-bordered", (*tab == Tab::Csv).then_some("tab-active"))}>{"Insert from CSV"}</a>
+                    </div>
+                    if *tab == Tab::Csv {
+                        <div class="flex flex-col space-y-8 mt-4">
+                            <div class="form-control">
+                                <label class="label">
+                                    <span class="label-text">{"Paste CSV or TSV, e.g. a spreadsheet export."}</span>
+                                </label>
+                                <textarea oninput={on_csv_input} id={CSV_TEXTAREA_ID} placeholder={PLACEHOLDER_CSV} value={(*csv).clone()}
+                                    class="textarea textarea-bordered textarea-primary font-mono h-24" />
+                                if let Some(err) = csv_error {
+                                    <label class="label">
+                                        <span class="label-text-alt text-error">{err}</span>
+                                    </label>
+                                }
+                            </div>
+
+                            <div class="flex flex-col space-y-4">
+                                <h2 class="text-xl">{"Preview"}</h2>
+                                <div class="bg-base-300 p-8 rounded-xl">
+                                    <div class="h-24 lg:h-36 2xl:h-48 overflow-auto">
+                                        <p class="whitespace-pre font-mono" id={TABLE_PREVIEW_ID}>
+                                            {csv_preview}
+                                        </p>
+                                    </div>
+                                </div>
+                            </div>
+                        </div>
+                    } else {
                     <div class="flex flex-col space-y-8 mt-4">
 
                         <div class="flex flex-row space-x-12">
@@ -185,7 +278,7 @@ pub fn table_modal() -> Html {
                         </div>
 
                         <div class="flex flex-col space-y-4">
-                            <h2 class="text-xl">{"Alignment"}</h2>
+                            <h2 class="text-xl">{"Headers & Alignment"}</h2>
                             <div class="flex flex-col space-y-4">
                                 {col_alignment_html}
                             </div>
@@ -195,16 +288,20 @@ pub fn table_modal() -> Html {
                             <h2 class="text-xl">{"Preview"}</h2>
                             <div class="bg-base-300 p-8 rounded-xl">
                                 <div class="h-24 lg:h-36 2xl:h-48 overflow-auto">
+                                    if let Some(err) = table_error.clone() {
+                                        <pre class="text-error font-mono whitespace-pre">{err}</pre>
+                                    }
                                     <p class="whitespace-pre-wrap" id={TABLE_PREVIEW_ID}>
-                                        {table}
+                                        {table_preview}
                                     </p>
                                 </div>
                             </div>
                         </div>
                     </div>
+                    }
                     <div class="modal-action">
                         <label for={TABLE_MODAL_ID} class="btn btn-ghost">{"Cancel"}</label>
-                        <button onclick={insert_table} class="btn">{"Generate"}</button>
+                        <button onclick={insert_table} class="btn">{if *tab == Tab::Csv { "Insert" } else { "Generate" }}</button>
                     </div>
                 </div>
             </div>
@@ -239,12 +336,14 @@ pub fn table_modal() -> Html {
 
 #[derive(PartialEq, Clone)]
 struct Column {
-    pub alignment: Alignment
+    pub alignment: Alignment,
+    pub header: String
 }
 
 impl Column {
     pub fn from(alignment: Alignment) -> Self {
-        Self { alignment }
+        let header = String::new();
+        Self { alignment, header }
     }
 }
 
@@ -255,3 +354,8 @@ enum Alignment {
     Right
 }
 
+#[derive(PartialEq, Clone, Copy)]
+enum Tab {
+    Generate,
+    Csv
+}
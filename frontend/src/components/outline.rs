@@ -0,0 +1,126 @@
+use gloo::events::EventListener;
+use gloo::utils::document;
+use md::{HtmlPolicy, OutlineHeading};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::{byte_offset, utf16_offset}, textarea::EDITOR_ID};
+use crate::components::tooltip::Tooltip;
+use crate::contexts::{config::use_config, markdown::use_markdown, toasts::{use_toaster, err_modal}};
+use crate::icons::{CloseIcon, TocIcon};
+
+fn editor() -> Option<HtmlTextAreaElement> {
+    document().get_element_by_id(&EDITOR_ID)?.dyn_into().ok()
+}
+
+/// The heading whose section contains the editor's caret
+fn current_heading(headings: &[OutlineHeading]) -> Option<usize> {
+    let text_area = editor()?;
+    let text = text_area.value();
+    let caret = text_area.selection_start().ok()??;
+    md::current_heading(headings, byte_offset(&text, caret))
+}
+
+/// Move the caret to the heading's line and scroll both the editor and the preview to it
+fn go_to_heading(heading: &OutlineHeading, policy: HtmlPolicy) {
+    if let Some(text_area) = editor() {
+        let text = text_area.value();
+        let caret = utf16_offset(&text, heading.offset);
+
+        text_area.focus().unwrap();
+        text_area.set_selection_range(caret, caret).unwrap();
+
+        // Wrapped lines make this approximate, but it lands the heading in view
+        let lines = text.lines().count().max(1);
+        let scroll_top = heading.line.saturating_sub(1) as f64 / lines as f64 * text_area.scroll_height() as f64;
+        text_area.set_scroll_top(scroll_top as i32);
+    }
+
+    if let Some(element) = document().get_element_by_id(&md::element_id(&heading.id, policy)) {
+        element.scroll_into_view();
+    }
+}
+
+/// The heading tree of the current markdown, highlighting the section the caret is in.
+#[function_component(OutlineList)]
+pub fn outline_list() -> Html {
+    let markdown = use_markdown().state();
+    let render_options = markdown.render_options(use_config().state().render);
+    let policy = render_options.html;
+    let headings = use_memo(
+        |(text, options)| md::outline(text, options).unwrap_or_default(),
+        (markdown.text.clone(), render_options),
+    );
+
+    let current = use_state_eq(|| current_heading(&headings));
+
+    {
+        let current = current.clone();
+        use_effect_with_deps(move |headings| {
+            current.set(current_heading(headings));
+
+            let headings = headings.clone();
+            let listener = EventListener::new(&document(), "selectionchange", move |_| {
+                current.set(current_heading(&headings));
+            });
+            move || drop(listener)
+        }, headings.clone());
+    }
+
+    let top_level = headings.iter().map(|heading| heading.level).min().unwrap_or(1);
+    let items = headings.iter().enumerate().map(|(i, heading)| {
+        let onclick = {
+            let heading = heading.clone();
+            Callback::from(move |_| go_to_heading(&heading, policy))
+        };
+
+        let indent = format!("padding-left: {}rem", 1.0 + (heading.level - top_level) as f32 * 0.75);
+        let classes = classes!((*current == Some(i)).then_some("active"));
+
+        html! {
+            <li>
+                <a onclick={onclick} class={classes} style={indent}>{&heading.text}</a>
+            </li>
+        }
+    });
+
+    html! {
+        <ul class="menu menu-sm flex-nowrap overflow-y-auto">
+            if headings.is_empty() {
+                <li class="disabled"><span>{"No headings"}</span></li>
+            } else {
+                { for items }
+            }
+        </ul>
+    }
+}
+
+/// A collapsible panel holding the outline, shown beside the editor and preview.
+#[function_component(OutlinePanel)]
+pub fn outline_panel() -> Html {
+    let config_ctx = use_config();
+    let show_outline = config_ctx.state().outline;
+
+    let toaster = use_toaster();
+    let toggle_outline = Callback::from(move |_| {
+        config_ctx.toggle_outline().unwrap_or_else(|err| err_modal(err, toaster.clone()));
+    });
+
+    html! {
+        if show_outline {
+            <div class="flex flex-col flex-none w-56 h-[calc(100vh-8.25rem)] border border-base-content rounded-xl py-4">
+                <div class="flex items-center justify-between px-4 mb-2">
+                    <span class="font-display text-lg">{"Outline"}</span>
+                    <Tooltip tip={"Hide outline"}>
+                        <btn onclick={toggle_outline} class="btn btn-sm btn-ghost"><CloseIcon /></btn>
+                    </Tooltip>
+                </div>
+                <OutlineList />
+            </div>
+        } else {
+            <Tooltip tip={"Show outline"}>
+                <btn onclick={toggle_outline} class="btn btn-sm btn-ghost"><TocIcon /></btn>
+            </Tooltip>
+        }
+    }
+}
@@ -1,21 +1,20 @@
 use yew::prelude::*;
-use markdown::{self, Options, ParseOptions, CompileOptions};
-use crate::contexts::markdown::use_markdown;
+use crate::components::front_matter::FrontMatterHeader;
+use crate::contexts::{markdown::use_markdown, config::use_config};
 
 #[function_component(Pdf)]
 pub fn pdf() -> Html {
     let markdown = use_markdown().state();
 
-    let compile = CompileOptions {
-        allow_dangerous_html: true,
-        allow_dangerous_protocol: true,
-        ..CompileOptions::default()
+    let render_options = markdown.render_options(use_config().state().render);
+    let rendered = use_memo(
+        |(text, options)| md::render(text, options),
+        (markdown.text.clone(), render_options),
+    );
+    let md_html = match rendered.as_ref() {
+        Ok(rendered) => Html::from_html_unchecked(AttrValue::from(rendered.html.clone())),
+        Err(err) => html! { <p class="text-error">{err}</p> },
     };
-    let parse = ParseOptions::gfm();
-    let options = &Options { compile, parse };
-
-    let md = markdown::to_html_with_options(&markdown.text, options).unwrap();
-    let md_html = Html::from_html_unchecked(AttrValue::from(md));
     
     let classes = classes!(
         "prose",
@@ -37,6 +36,7 @@ pub fn pdf() -> Html {
     
     html! {
         <article data-theme={"light"} id="preview" class={classes}>
+            <FrontMatterHeader />
             { md_html }
         </article>
     }
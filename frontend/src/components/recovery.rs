@@ -0,0 +1,176 @@
+use yew::prelude::*;
+
+/// How long typing has to pause for before unsaved edits are journaled
+#[cfg(not(feature = "web"))]
+const JOURNAL_DELAY_MILLIS: u32 = 300;
+
+/// Lines shown either side of a change in the preview
+#[cfg(not(feature = "web"))]
+const CONTEXT_LINES: usize = 2;
+
+/// Keep a journal of the open file's unsaved edits, so they can be
+/// recovered if Ubiquity closes before they are saved.
+///
+/// Saving a file clears its journal, so only dirty files are recorded.
+#[hook]
+pub(crate) fn use_recovery_journal() {
+    #[cfg(not(feature = "web"))]
+    {
+        use gloo::timers::callback::Timeout;
+        use wasm_bindgen_futures::spawn_local;
+        use crate::components::toasts::ToastProps;
+        use crate::contexts::{markdown::use_markdown, toasts::use_toaster};
+        use crate::tauri::record_edit;
+
+        let md_ctx = use_markdown();
+        let toaster = use_toaster();
+
+        use_effect_with_deps(move |markdown| {
+            let path = markdown.key.clone().filter(|_| markdown.dirty && !markdown.bundled);
+
+            let timeout = path.map(|path| {
+                let text = markdown.text.to_string();
+                Timeout::new(JOURNAL_DELAY_MILLIS, move || {
+                    spawn_local(async move {
+                        if let Err(err) = record_edit(path.to_string(), text).await {
+                            toaster.add_toast(ToastProps::from(err));
+                        }
+                    });
+                })
+            });
+            move || drop(timeout)
+        }, md_ctx.state());
+    }
+}
+
+/// The changed lines and a little around them, with `None` where unchanged lines are left out
+#[cfg(not(feature = "web"))]
+fn preview(lines: &[md::DiffLine]) -> Vec<Option<&md::DiffLine>> {
+    use md::DiffKind;
+
+    let near_change = |i: usize| {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + CONTEXT_LINES + 1).min(lines.len());
+        lines[start..end].iter().any(|line| line.kind != DiffKind::Same)
+    };
+
+    let mut shown = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if near_change(i) {
+            shown.push(Some(line));
+        } else if shown.last().map_or(true, Option::is_some) {
+            shown.push(None);
+        }
+    }
+    shown
+}
+
+/// Offers to restore or discard the edits that were never saved before
+/// Ubiquity last closed, one file at a time, showing how they differ from
+/// the file on disk.
+#[cfg(not(feature = "web"))]
+#[function_component(RecoveryModal)]
+pub fn recovery_modal() -> Html {
+    use md::{DiffKind, Recovery};
+    use wasm_bindgen_futures::spawn_local;
+    use crate::components::toasts::ToastProps;
+    use crate::contexts::{markdown::{use_markdown, Markdown}, toasts::{use_toaster, err_modal}};
+    use crate::tauri::{discard_recovery, pending_recoveries};
+
+    let md_ctx = use_markdown();
+    let toaster = use_toaster();
+    let recoveries = use_state(Vec::<Recovery>::new);
+
+    {
+        let recoveries = recoveries.clone();
+        let toaster = toaster.clone();
+        use_effect_with_deps(move |_| {
+            spawn_local(async move {
+                match pending_recoveries().await {
+                    Ok(pending) => recoveries.set(pending),
+                    Err(err) => toaster.add_toast(ToastProps::from(err)),
+                }
+            });
+            || ()
+        }, ());
+    }
+
+    let Some(recovery) = recoveries.first().cloned() else {
+        return html! {};
+    };
+
+    let next = {
+        let recoveries = recoveries.clone();
+        move || recoveries.set(recoveries[1..].to_vec())
+    };
+
+    let restore = {
+        let recovery = recovery.clone();
+        let toaster = toaster.clone();
+        let next = next.clone();
+        Callback::from(move |_| {
+            let text = AttrValue::from(recovery.contents.clone());
+            let key = Some(AttrValue::from(recovery.path.clone()));
+            // Still unsaved, so its journal is kept until it is
+            let markdown = Markdown { dirty: true, ..Markdown::from(text, key) };
+            md_ctx.add_markdown(markdown.clone()).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+            md_ctx.set_markdown(markdown).unwrap_or_else(|err| err_modal(err, toaster.clone()));
+            next();
+        })
+    };
+
+    let discard = {
+        let path = recovery.path.clone();
+        Callback::from(move |_| {
+            let (path, toaster) = (path.clone(), toaster.clone());
+            spawn_local(async move {
+                if let Err(err) = discard_recovery(path).await {
+                    toaster.add_toast(ToastProps::from(err));
+                }
+            });
+            next();
+        })
+    };
+
+    let lines = md::diff(recovery.saved.as_deref().unwrap_or_default(), &recovery.contents);
+    let diff = preview(&lines).into_iter().map(|line| match line {
+        Some(line) => {
+            let (sign, class) = match line.kind {
+                DiffKind::Same => (' ', None),
+                DiffKind::Removed => ('-', Some("bg-error/20")),
+                DiffKind::Added => ('+', Some("bg-success/20")),
+            };
+            html! { <div class={classes!("whitespace-pre", class)}>{format!("{} {}", sign, line.text)}</div> }
+        }
+        None => html! { <div class="opacity-50">{"⋯"}</div> },
+    });
+
+    let description = match recovery.saved {
+        Some(_) => "These edits were never saved before Ubiquity closed. Restoring them opens the file with the edits, ready to save.",
+        None => "These edits were never saved before Ubiquity closed, and the file is no longer on disk. Restoring them opens the edits, ready to save again.",
+    };
+
+    html! {
+        <div class="modal modal-open">
+            <div class="modal-box w-11/12 max-w-5xl">
+                <h3 class="font-bold text-2xl">{"Recover unsaved edits"}</h3>
+                <p class="font-mono py-2 truncate">{&recovery.path}</p>
+                <p class="pb-4">{description}</p>
+                <div class="font-mono text-sm max-h-96 overflow-auto rounded-lg bg-base-200 p-2">
+                    { for diff }
+                </div>
+                <div class="modal-action">
+                    <button onclick={discard} class="btn btn-ghost">{"Discard"}</button>
+                    <button onclick={restore} class="btn btn-primary">{"Restore"}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Every edit on the web is already kept in browser storage, so there is nothing to recover.
+#[cfg(feature = "web")]
+#[function_component(RecoveryModal)]
+pub fn recovery_modal() -> Html {
+    html! {}
+}
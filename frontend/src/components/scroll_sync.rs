@@ -0,0 +1,154 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use gloo::events::EventListener;
+use gloo::utils::document;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlTextAreaElement};
+use yew::prelude::*;
+use crate::components::editor::textarea::{caret::utf16_offset, textarea::EDITOR_ID};
+
+/// The id of the element that scrolls the preview
+pub const PREVIEW_SCROLL_ID: AttrValue = AttrValue::Static("preview-scroll");
+
+fn editor() -> Option<HtmlTextAreaElement> {
+    document().get_element_by_id(&EDITOR_ID)?.dyn_into().ok()
+}
+
+fn preview() -> Option<Element> {
+    document().get_element_by_id(&PREVIEW_SCROLL_ID)
+}
+
+fn line_count(text_area: &HtmlTextAreaElement) -> f64 {
+    text_area.value().lines().count().max(1) as f64
+}
+
+/// Pairs of source line and scroll position for every rendered block with a
+/// `data-line`, along with the start and end of the document.
+fn preview_points(preview: &Element, lines: f64) -> Vec<(f64, f64)> {
+    let top = preview.get_bounding_client_rect().top() - preview.scroll_top() as f64;
+    let mut points = vec![(1.0, 0.0)];
+
+    if let Ok(blocks) = preview.query_selector_all("[data-line]") {
+        for i in 0..blocks.length() {
+            let block = match blocks.item(i).and_then(|node| node.dyn_into::<Element>().ok()) {
+                Some(block) => block,
+                None => continue,
+            };
+            let line = block.get_attribute("data-line").and_then(|line| line.parse::<f64>().ok());
+            let y = block.get_bounding_client_rect().top() - top;
+
+            if let Some(line) = line && points.last().is_some_and(|(last_line, last_y)| line > *last_line && y >= *last_y) {
+                points.push((line, y));
+            }
+        }
+    }
+
+    points.push((lines + 1.0, preview.scroll_height() as f64));
+    points
+}
+
+/// Map a value through straight lines drawn between the points
+fn interpolate(points: &[(f64, f64)], value: f64) -> f64 {
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if value <= x1 {
+            return match x1 - x0 {
+                span if span > 0.0 => y0 + (value - x0).max(0.0) / span * (y1 - y0),
+                _ => y0,
+            };
+        }
+    }
+    points.last().map_or(0.0, |(_, y)| *y)
+}
+
+/// Scroll an element unless it is already there, returning whether it moved
+fn scroll_to(element: &Element, top: f64) -> bool {
+    let top = top.round() as i32;
+    let moved = element.scroll_top() != top;
+    if moved {
+        element.set_scroll_top(top);
+    }
+    moved
+}
+
+/// Keep the editor and the preview scrolled to the same part of the document.
+///
+/// The editor's position is approximated from its scroll fraction, as wrapped
+/// lines make exact line positions expensive to measure.
+#[hook]
+pub(crate) fn use_scroll_sync(enabled: bool) {
+    use_effect_with_deps(move |enabled| {
+        let mut listeners = Vec::new();
+
+        if *enabled && let Some(text_area) = editor() && let Some(preview) = preview() {
+            // A scroll we caused fires an event of its own, which must not be synced back
+            let ignore_editor = Rc::new(Cell::new(false));
+            let ignore_preview = Rc::new(Cell::new(false));
+
+            {
+                let (text_area, preview) = (text_area.clone(), preview.clone());
+                let (ignore_editor, ignore_preview) = (ignore_editor.clone(), ignore_preview.clone());
+                listeners.push(EventListener::new(&text_area.clone(), "scroll", move |_| {
+                    if ignore_editor.replace(false) {
+                        return;
+                    }
+                    let lines = line_count(&text_area);
+                    let line = text_area.scroll_top() as f64 / text_area.scroll_height().max(1) as f64 * lines + 1.0;
+                    let top = interpolate(&preview_points(&preview, lines), line);
+                    ignore_preview.set(scroll_to(&preview, top));
+                }));
+            }
+
+            listeners.push(EventListener::new(&preview.clone(), "scroll", move |_| {
+                if ignore_preview.replace(false) {
+                    return;
+                }
+                let lines = line_count(&text_area);
+                let points: Vec<(f64, f64)> = preview_points(&preview, lines).into_iter().map(|(line, y)| (y, line)).collect();
+                let line = interpolate(&points, preview.scroll_top() as f64);
+                let top = (line - 1.0) / lines * text_area.scroll_height() as f64;
+                ignore_editor.set(scroll_to(&text_area, top));
+            }));
+        }
+
+        move || drop(listeners)
+    }, enabled);
+}
+
+/// Put the editor's caret at the start of the source of the clicked block
+pub(crate) fn place_caret_at_block(mouse_event: MouseEvent) {
+    let target = match mouse_event.target().and_then(|target| target.dyn_into::<Element>().ok()) {
+        Some(target) => target,
+        None => return,
+    };
+
+    // Links keep working as links. Unless HTML is trusted, the ids of raw
+    // HTML and headings are prefixed, so links to those are followed here, as
+    // GitHub does. The prefixed id is looked for first, as the plain one could
+    // be one of the app's own elements.
+    if let Some(link) = target.closest("a").ok().flatten() {
+        let anchor = link.get_attribute("href").and_then(|href| href.strip_prefix('#').map(str::to_string));
+        if let Some(anchor) = anchor
+            && let Some(element) = document().get_element_by_id(&format!("{}{}", md::ID_PREFIX, anchor))
+        {
+            mouse_event.prevent_default();
+            element.scroll_into_view();
+        }
+        return;
+    }
+
+    let line = target
+        .closest("[data-line]")
+        .ok()
+        .flatten()
+        .and_then(|block| block.get_attribute("data-line"))
+        .and_then(|line| line.parse::<usize>().ok());
+
+    if let Some(line) = line && let Some(text_area) = editor() {
+        let text = text_area.value();
+        let caret = utf16_offset(&text, md::line_offset(&text, line));
+
+        text_area.focus().unwrap();
+        text_area.set_selection_range(caret, caret).unwrap();
+    }
+}
@@ -3,6 +3,7 @@ use yew::prelude::*;
 use crate::components::container::Container;
 use crate::components::editor::editor::Editor;
 use crate::components::markdown_preview::MarkdownPreview;
+use crate::components::outline::OutlinePanel;
 use crate::contexts::config::use_config;
 
 #[function_component(SingleView)]
@@ -10,12 +11,15 @@ pub fn single_view() -> Html {
     let preview = use_config().state().view == View::Preview;
     
     html! {
-        <Container>
-            if preview {
-                <MarkdownPreview />
-            } else {
-                <Editor />
-            }
-        </Container>
+        <div class="flex flex-row w-full justify-center items-center space-x-4">
+            <OutlinePanel />
+            <Container>
+                if preview {
+                    <MarkdownPreview />
+                } else {
+                    <Editor />
+                }
+            </Container>
+        </div>
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
 use std::ops::Deref;
 use std::path::PathBuf;
 use config::{Config, View};
+use lint::Rule;
+use md::{BackupPolicy, HtmlPolicy};
 use error::UbiquityError;
 use gloo::{storage::{LocalStorage, Storage}, utils::window};
 use web_sys::Navigator;
@@ -54,6 +56,62 @@ impl ConfigContext {
         Ok(())
     }
 
+    pub fn toggle_scroll_sync(&self) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.scroll_sync = !self.inner.scroll_sync;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn toggle_format_on_save(&self) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.format_on_save = !self.inner.format_on_save;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn toggle_autosave(&self) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.autosave = !self.inner.autosave;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn set_autosave_delay(&self, millis: u32) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.autosave_delay_millis = millis;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn set_backups(&self, backups: BackupPolicy) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.backups = backups;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn set_workspace(&self, workspace: Option<PathBuf>) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.workspace = workspace;
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn toggle_lint_rule(&self, rule: Rule) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.lint.toggle(rule);
+        self.set(new_config)?;
+        Ok(())
+    }
+
+    pub fn toggle_outline(&self) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.outline = !self.inner.outline;
+        self.set(new_config)?;
+        Ok(())
+    }
+
 
     pub fn toggle_view(&self) -> Result<(), UbiquityError> {
         let mut new_config = self.state();
@@ -72,6 +130,13 @@ impl ConfigContext {
         Ok(())
     }
 
+    pub fn set_html_policy(&self, policy: HtmlPolicy) -> Result<(), UbiquityError> {
+        let mut new_config = self.state();
+        new_config.render.html = policy;
+        self.set(new_config)?;
+        Ok(())
+    }
+
     pub fn is_single_view(&self) -> bool {
         match &self.state().view {
             View::Dual => false,
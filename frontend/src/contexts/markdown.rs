@@ -1,36 +1,59 @@
 use std::collections::HashMap;
 use std::ops::Deref;
 use error::UbiquityError;
-use md::{DOCS_STR, DOCS_KEY};
+use md::{DOCS_STR, DOCS_KEY, FrontMatter, HtmlPolicy, RenderOptions};
 use serde::Deserialize;
 use serde_json::Value;
 use yew::prelude::*;
 use gloo::storage::LocalStorage;
 use gloo::storage::Storage;
+use gloo::utils::document;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+use crate::components::editor::textarea::textarea::EDITOR_ID;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Markdown {
     pub text: AttrValue,
-    pub key: Option<AttrValue>
+    pub key: Option<AttrValue>,
+    /// Whether there are edits that have not been saved
+    pub dirty: bool,
+    /// Whether this is the documentation Ubiquity ships with. Only
+    /// [`Markdown::default`] makes it, as keys can be chosen by users.
+    pub bundled: bool
 }
 
 impl Default for Markdown {
     fn default() -> Self {
         let text = AttrValue::from(DOCS_STR);
         let key = Some(AttrValue::from(DOCS_KEY));
-        Self { text, key }    
+        Self { text, key, dirty: false, bundled: true }
     }
 }
 
 impl Markdown {    
     pub fn from(text: AttrValue, key: Option<AttrValue>) -> Self {
-        Self { text, key }
+        Self { text, key, dirty: false, bundled: false }
     }
 
     pub fn current(&self) -> &Self {
         &self
     }
 
+    /// The options to render this markdown with. The built-in docs ship with
+    /// the app, so their HTML is trusted whatever the configured policy is.
+    pub fn render_options(&self, options: RenderOptions) -> RenderOptions {
+        match self.bundled {
+            true => RenderOptions { html: HtmlPolicy::Trust, ..options },
+            false => options,
+        }
+    }
+
+    /// The front matter at the top of this markdown, if it has any
+    pub fn front_matter(&self) -> Result<Option<FrontMatter>, UbiquityError> {
+        md::front_matter(&self.text).map_err(UbiquityError::front_matter)
+    }
+
     pub fn read_all_markdown_keys() -> Vec<AttrValue> {
         let storage_vec: HashMap<String, Value>  = LocalStorage::get_all().unwrap();
         let mut markdown_keys_vec: Vec<AttrValue> = Vec::new();
@@ -49,7 +72,7 @@ impl Markdown {
         let text_str: String = LocalStorage::get(key_str).unwrap();
         let text = AttrValue::from(text_str);
         let key = Some(key);
-        Markdown { text, key }
+        Markdown::from(text, key)
     }
 
     pub fn load_latest_from_storage() -> Option<Markdown> {
@@ -80,8 +103,9 @@ impl MarkdownContext {
         Self { inner }
     }
 
+    /// Replace the markdown with an edited version, which makes it dirty
     pub fn update_markdown(&self, md: Markdown) -> Result<(), UbiquityError> {
-        self.inner.set(md);
+        self.inner.set(Markdown { dirty: true, ..md });
         self.save_to_browser_storage()?;
         Ok(())
     }
@@ -93,6 +117,20 @@ impl MarkdownContext {
     }
 
 
+    /// Mark the markdown as saved. Saves finish asynchronously, so this is
+    /// skipped when the editor's text has changed since `saved` was written.
+    pub fn mark_saved(&self, saved: Markdown) -> Result<(), UbiquityError> {
+        let editor_text = document()
+            .get_element_by_id(&EDITOR_ID)
+            .and_then(|editor| editor.dyn_into::<HtmlTextAreaElement>().ok())
+            .map(|text_area| text_area.value());
+
+        if editor_text.is_some_and(|text| text != saved.text.as_str()) {
+            return Ok(());
+        }
+        self.set_markdown(Markdown { dirty: false, ..saved })
+    }
+
     pub fn update_key(&self, key: AttrValue) {
         let text = self.text.clone();
         let key = Some(key);
@@ -159,4 +197,23 @@ pub(crate) fn use_markdown() -> MarkdownContext {
 #[derive(Deserialize)]
 pub struct BrowserStorageItem {
     pub keys: Vec<String>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_the_bundled_docs_are_trusted() {
+        let options = RenderOptions::default();
+        assert_eq!(Markdown::default().render_options(options.clone()).html, HtmlPolicy::Trust);
+
+        // A file uploaded or created under the docs' name is still sanitised
+        let text = AttrValue::from("<script>alert(1)</script>\n\n<img src=x onerror=alert(1)>\n");
+        let named_like_docs = Markdown::from(text, Some(AttrValue::from(DOCS_KEY)));
+        let render_options = named_like_docs.render_options(options);
+        assert_eq!(render_options.html, HtmlPolicy::Sanitise);
+
+        let html = md::render(&named_like_docs.text, &render_options).unwrap().html;
+        assert!(!html.contains("<script") && !html.contains("onerror"));
+    }
+}
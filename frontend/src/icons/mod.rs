@@ -197,6 +197,18 @@ pub fn folder_add_icon(props: &IconProps) -> Html {
     }
 }
 
+#[function_component(FolderTreeIcon)]
+pub fn folder_tree_icon(props: &IconProps) -> Html {
+    html! {
+        <Svg classes={&props.classes}>
+            <path d="M20 10a1 1 0 0 0 1-1V6a1 1 0 0 0-1-1h-2.5a1 1 0 0 1-.8-.4l-.9-1.2A1 1 0 0 0 15 3h-2a1 1 0 0 0-1 1v5a1 1 0 0 0 1 1Z"></path>
+            <path d="M20 21a1 1 0 0 0 1-1v-3a1 1 0 0 0-1-1h-2.9a1 1 0 0 1-.88-.55l-.42-.85a1 1 0 0 0-.92-.6H13a1 1 0 0 0-1 1v5a1 1 0 0 0 1 1Z"></path>
+            <path d="M3 5a2 2 0 0 0 2 2h3"></path>
+            <path d="M3 3v13a2 2 0 0 0 2 2h3"></path>
+        </Svg>
+    }
+}
+
 #[function_component(PlaylistAddIcon)]
 pub fn playlist_icon(props: &IconProps) -> Html {
     html! {
@@ -433,6 +445,36 @@ pub fn add_image_icon(props: &IconProps) -> Html {
     }
 }
 
+#[function_component(FormatTableIcon)]
+pub fn format_table_icon(props: &IconProps) -> Html {
+    html! {
+        <Svg classes={&props.classes}>
+            <line x1="21" x2="3" y1="6" y2="6"/><line x1="21" x2="3" y1="12" y2="12"/><line x1="21" x2="3" y1="18" y2="18"/>
+            <line x1="9" x2="9" y1="3" y2="21"/><line x1="15" x2="15" y1="3" y2="21"/>
+        </Svg>
+    }
+}
+
+#[function_component(TocIcon)]
+pub fn toc_icon(props: &IconProps) -> Html {
+    html! {
+        <Svg classes={&props.classes}>
+            <path d="M21 12h-8"/><path d="M21 6H8"/><path d="M21 18h-8"/>
+            <path d="M3 6v4c0 1.1.9 2 2 2h3"/><path d="M3 10v6c0 1.1.9 2 2 2h3"/>
+        </Svg>
+    }
+}
+
+#[function_component(FormatDocumentIcon)]
+pub fn format_document_icon(props: &IconProps) -> Html {
+    html! {
+        <Svg classes={&props.classes}>
+            <path d="m21.64 3.64-1.28-1.28a1.21 1.21 0 0 0-1.72 0L2.36 18.64a1.21 1.21 0 0 0 0 1.72l1.28 1.28a1.2 1.2 0 0 0 1.72 0L21.64 5.36a1.2 1.2 0 0 0 0-1.72Z"/>
+            <path d="m14 7 3 3"/><path d="M5 6v4"/><path d="M19 14v4"/><path d="M10 2v2"/><path d="M7 8H3"/><path d="M21 16h-4"/><path d="M11 3H9"/>
+        </Svg>
+    }
+}
+
 #[function_component(MoneroQr)]
 pub fn monero_qr() -> Html {
     // (https://fukuchi.org/works/qrencode/index.html)
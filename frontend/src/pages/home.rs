@@ -1,8 +1,11 @@
+use crate::components::autosave::use_autosave;
+use crate::components::conflict::ConflictModal;
 use crate::components::drawer::Drawer;
 use crate::components::dual_view::DualView;
 use crate::components::footer::Footer;
 use crate::components::header::header::Header;
 use crate::components::modals::modals::Modals;
+use crate::components::recovery::{use_recovery_journal, RecoveryModal};
 use crate::components::single_view::SingleView;
 use crate::components::toasts::Toaster;
 use crate::contexts::config::use_config;
@@ -13,6 +16,10 @@ use yew::prelude::*;
 
 #[function_component(Home)]
 pub fn home() -> Html {
+    // Found both by the dialog and by autosave, when a save is refused
+    let conflict = use_state(|| None::<md::FileChange>);
+    use_autosave(conflict.clone());
+    use_recovery_journal();
     let is_mobile_ui = use_config().is_mobile_ui();
     html! {
         if is_mobile_ui {
@@ -25,6 +32,8 @@ pub fn home() -> Html {
                                 <Footer />
                             </div>
                             <Modals />
+                            <RecoveryModal />
+                            <ConflictModal conflict={conflict.clone()} />
                             <Toaster />
                     </Background>
                 </Drawer>
@@ -32,14 +41,18 @@ pub fn home() -> Html {
             </>
         } else {
             <>
-            <Background>
-                <Header />
-                <div class="h-[calc(100vh-4rem)] flex flex-col content-center align-center items-center justify-center">
-                    <DualView />
-                </div>
-                <Modals />
-                <Toaster />
-            </Background>
+            <Drawer>
+                <Background>
+                    <Header />
+                    <div class="h-[calc(100vh-4rem)] flex flex-col content-center align-center items-center justify-center">
+                        <DualView />
+                    </div>
+                    <Modals />
+                    <RecoveryModal />
+                    <ConflictModal conflict={conflict.clone()} />
+                    <Toaster />
+                </Background>
+            </Drawer>
             <Pdf />
             </>
         }
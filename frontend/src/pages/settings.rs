@@ -1,4 +1,6 @@
 use config::View;
+use lint::Rule;
+use md::{BackupPolicy, HtmlPolicy};
 use yew::prelude::*;
 use crate::components::drawer::Drawer;
 use crate::components::{theme_card::ThemeCard, header::header::Header};
@@ -15,6 +17,9 @@ pub fn settings() -> Html {
                     <SettingsContainer>
                         <ThemeSettings />
                         <LayoutSettings />
+                        <EditorSettings />
+                        <PreviewSettings />
+                        <LintSettings />
                     </SettingsContainer>
                 </SettingsPage>
             </Background>
@@ -123,11 +128,19 @@ pub fn layout_settings() -> Html {
     let view = config.view;
     let is_single_view = view != View::Dual;
     let mobile_ui = config.mobile_ui;
+    let scroll_sync = config.scroll_sync;
 
     // let toggle_view = Callback::from(move |_| {
     //     let _ = config_context.toggle_view();
     // });
 
+    let toggle_scroll_sync = {
+        let config_context = config_context.clone();
+        Callback::from(move |_| {
+            let _ = config_context.toggle_scroll_sync();
+        })
+    };
+
     let toggle_mobile_ui = Callback::from(move |_| {
         let _ = config_context.toggle_mobile_ui();
     });
@@ -145,8 +158,198 @@ pub fn layout_settings() -> Html {
                         onclick={toggle_mobile_ui} />
                 </label>
                 <div class="divider" />
+                <label class="cursor-pointer label">
+                    <span class="font-mono text-2xl">{"Scroll sync"}</span>
+                    <input type="checkbox" class="toggle toggle-primary" checked={scroll_sync}
+                        onclick={toggle_scroll_sync} />
+                </label>
+                <div class="divider" />
+            </div>
+        </div>
+
+    }
+}
+
+/// How long typing can pause for before an autosave, in milliseconds
+const AUTOSAVE_DELAYS: [(u32, &str); 4] = [
+    (500, "Half a second"),
+    (1000, "1 second"),
+    (2000, "2 seconds"),
+    (5000, "5 seconds"),
+];
+
+const BACKUP_POLICIES: [(BackupPolicy, &str); 4] = [
+    (BackupPolicy::Off, "No backups"),
+    (BackupPolicy::Single, "Keep one .bak file"),
+    (BackupPolicy::Numbered(5), "Keep the last 5 versions"),
+    (BackupPolicy::Numbered(20), "Keep the last 20 versions"),
+];
+
+#[function_component(EditorSettings)]
+pub fn editor_settings() -> Html {
+    let config_context = use_config();
+    let config = config_context.state();
+    let format_on_save = config.format_on_save;
+    let autosave = config.autosave;
+
+    let toggle_format_on_save = {
+        let config_context = config_context.clone();
+        Callback::from(move |_| {
+            let _ = config_context.toggle_format_on_save();
+        })
+    };
+
+    let toggle_autosave = {
+        let config_context = config_context.clone();
+        Callback::from(move |_| {
+            let _ = config_context.toggle_autosave();
+        })
+    };
+
+    let delay_options = AUTOSAVE_DELAYS.into_iter().map(|(millis, name)| {
+        let config_context = config_context.clone();
+        let set_delay = Callback::from(move |_| {
+            let _ = config_context.set_autosave_delay(millis);
+        });
+
+        html! {
+            <label class="cursor-pointer label">
+                <span class="font-mono text-xl">{name}</span>
+                <input type="radio" name="autosave-delay" class="radio radio-primary" disabled={!autosave}
+                    checked={millis == config.autosave_delay_millis} onclick={set_delay} />
+            </label>
+        }
+    });
+
+    let backup_options = BACKUP_POLICIES.into_iter().map(|(policy, name)| {
+        let config_context = config_context.clone();
+        let set_backups = Callback::from(move |_| {
+            let _ = config_context.set_backups(policy);
+        });
+
+        html! {
+            <label class="cursor-pointer label">
+                <span class="font-mono text-xl">{name}</span>
+                <input type="radio" name="backups" class="radio radio-primary"
+                    checked={policy == config.backups} onclick={set_backups} />
+            </label>
+        }
+    });
+
+    let classes = classes!("flex", "flex-col");
+
+    html! {
+        <div class={classes}>
+            <SettingsHeader text={"Editor"} />
+            <div class="divider" />
+            <div class="form-control w-full">
+                <label class="cursor-pointer label">
+                    <span class="font-mono text-2xl">{"Format on save"}</span>
+                    <input type="checkbox" class="toggle toggle-primary" checked={format_on_save}
+                        onclick={toggle_format_on_save} />
+                </label>
+                <span class="label-text-alt">
+                    {"Tidies headings, lists, emphasis, code fences, tables and blank lines whenever the document is saved."}
+                </span>
+                // Only desktop saves to files; the web version keeps every edit in browser storage
+                if cfg!(not(feature = "web")) {
+                    <div class="divider" />
+                    <label class="cursor-pointer label">
+                        <span class="font-mono text-2xl">{"Autosave"}</span>
+                        <input type="checkbox" class="toggle toggle-primary" checked={autosave}
+                            onclick={toggle_autosave} />
+                    </label>
+                    <span class="label-text-alt">{"Save once typing pauses for:"}</span>
+                    { for delay_options }
+                    <div class="divider" />
+                    <span class="font-mono text-2xl">{"Backups"}</span>
+                    <span class="label-text-alt">
+                        {"Copies of the previous version, kept next to the file whenever it is saved. Autosaves do not make backups."}
+                    </span>
+                    { for backup_options }
+                }
+                <div class="divider" />
             </div>
         </div>
+    }
+}
+
+#[function_component(PreviewSettings)]
+pub fn preview_settings() -> Html {
+    let config_context = use_config();
+    let html_policy = config_context.state().render.html;
+
+    let policies = [
+        (HtmlPolicy::Escape, "Show HTML as text"),
+        (HtmlPolicy::Sanitise, "Sanitise HTML"),
+        (HtmlPolicy::Trust, "Trust HTML"),
+    ];
+
+    let policy_options = policies.into_iter().map(|(policy, name)| {
+        let config_context = config_context.clone();
+        let set_policy = Callback::from(move |_| {
+            let _ = config_context.set_html_policy(policy);
+        });
+
+        html! {
+            <label class="cursor-pointer label">
+                <span class="font-mono text-2xl">{name}</span>
+                <input type="radio" name="html-policy" class="radio radio-primary"
+                    checked={policy == html_policy} onclick={set_policy} />
+            </label>
+        }
+    });
+
+    let classes = classes!("flex", "flex-col");
 
+    html! {
+        <div class={classes}>
+            <SettingsHeader text={"Preview"} />
+            <div class="divider" />
+            <div class="form-control w-full">
+                { for policy_options }
+                <span class="label-text-alt">
+                    {"Sanitising keeps safe HTML such as tables and images, but removes scripts, iframes and event handlers."}
+                </span>
+                <div class="divider" />
+            </div>
+        </div>
     }
-}
\ No newline at end of file
+}
+
+#[function_component(LintSettings)]
+pub fn lint_settings() -> Html {
+    let config_context = use_config();
+    let lint_options = config_context.state().lint;
+
+    let rules = Rule::ALL.into_iter().map(|rule| {
+        let config_context = config_context.clone();
+        let toggle_rule = Callback::from(move |_| {
+            let _ = config_context.toggle_lint_rule(rule);
+        });
+
+        html! {
+            <label class="cursor-pointer label">
+                <div class="flex flex-col">
+                    <span class="font-mono text-2xl">{rule.name()}</span>
+                    <span class="label-text-alt">{format!("{} · {}", rule.code(), rule.description())}</span>
+                </div>
+                <input type="checkbox" class="toggle toggle-primary" checked={lint_options.is_enabled(rule)}
+                    onclick={toggle_rule} />
+            </label>
+        }
+    });
+
+    let classes = classes!("flex", "flex-col");
+
+    html! {
+        <div class={classes}>
+            <SettingsHeader text={"Lint"} />
+            <div class="divider" />
+            <div class="form-control w-full">
+                { for rules }
+                <div class="divider" />
+            </div>
+        </div>
+    }
+}
@@ -1,16 +1,17 @@
 use crate::contexts::markdown::Markdown;
 use error::UbiquityError;
+use futures::{Stream, StreamExt};
 use tauri_sys::tauri::invoke;
 use yew::AttrValue;
 use md::*;
 
-pub async fn save_markdown_to_fs(markdown: Markdown) -> Result<String, UbiquityError> {
+pub async fn save_markdown_to_fs(markdown: Markdown, backups: BackupPolicy) -> Result<String, UbiquityError> {
     let contents = markdown.text.to_string();
     let path = match markdown.key {
         Some(key) => Some(key.to_string()),
         None => None
     };
-    let markdown: &MarkdownFile = &MarkdownFile { path, contents };
+    let markdown: &SaveFile = &SaveFile { path, contents, backups };
     let save_file: Result<String, tauri_sys::error::Error> = invoke("save_file", markdown).await;
     match save_file {
         Ok(path) => Ok(path),
@@ -18,6 +19,137 @@ pub async fn save_markdown_to_fs(markdown: Markdown) -> Result<String, UbiquityE
     }
 }
 
+pub async fn export_to_fs(contents: String, extension: String) -> Result<String, UbiquityError> {
+    let export_file: &ExportFile = &ExportFile { contents, extension };
+    let path: Result<String, tauri_sys::error::Error> = invoke("export_file", export_file).await;
+    match path {
+        Ok(path) => Ok(path),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Returns whether the file was written, which it is not when its contents were already the same
+pub async fn auto_save(path: String, contents: String) -> Result<bool, UbiquityError> {
+    let markdown_file = &MarkdownFile { path: Some(path), contents };
+    let saved: Result<bool, tauri_sys::error::Error> = invoke("auto_save", markdown_file).await;
+    match saved {
+        Ok(saved) => Ok(saved),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+pub async fn record_edit(path: String, contents: String) -> Result<(), UbiquityError> {
+    let markdown_file = &MarkdownFile { path: Some(path), contents };
+    let recorded: Result<(), tauri_sys::error::Error> = invoke("record_edit", markdown_file).await;
+    match recorded {
+        Ok(()) => Ok(()),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+pub async fn discard_recovery(path: String) -> Result<(), UbiquityError> {
+    let markdown_path = &MarkdownPath { path };
+    let discarded: Result<(), tauri_sys::error::Error> = invoke("discard_recovery", markdown_path).await;
+    match discarded {
+        Ok(()) => Ok(()),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Unsaved edits kept from before Ubiquity last closed
+pub async fn pending_recoveries() -> Result<Vec<Recovery>, UbiquityError> {
+    let recoveries: Result<Vec<Recovery>, tauri_sys::error::Error> = invoke("pending_recoveries", &()).await;
+    match recoveries {
+        Ok(recoveries) => Ok(recoveries),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Pick a folder to open as a workspace
+pub async fn open_folder_dialog() -> Result<Workspace, UbiquityError> {
+    let workspace: Result<Workspace, tauri_sys::error::Error> = invoke("open_folder_dialog", &()).await;
+    match workspace {
+        Ok(workspace) => Ok(workspace),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Stop the backend reaching into the folder open as a workspace
+pub async fn close_folder() -> Result<(), UbiquityError> {
+    let closed: Result<(), tauri_sys::error::Error> = invoke("close_folder", &()).await;
+    match closed {
+        Ok(()) => Ok(()),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// The folder the backend has open, which may differ from the one last
+/// remembered here
+pub async fn scan_workspace() -> Result<Workspace, UbiquityError> {
+    let workspace: Result<Workspace, tauri_sys::error::Error> = invoke("scan_workspace", &()).await;
+    match workspace {
+        Ok(workspace) => Ok(workspace),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Returns the new file's full path
+pub async fn create_workspace_file(path: String) -> Result<String, UbiquityError> {
+    let workspace_path = &WorkspacePath { path };
+    let created: Result<String, tauri_sys::error::Error> = invoke("create_workspace_file", workspace_path).await;
+    match created {
+        Ok(path) => Ok(path),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Returns the new path, relative to the root
+pub async fn rename_path(path: String, name: String) -> Result<String, UbiquityError> {
+    let rename_path = &RenamePath { path, name };
+    let renamed: Result<String, tauri_sys::error::Error> = invoke("rename_path", rename_path).await;
+    match renamed {
+        Ok(path) => Ok(path),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Returns the new path, relative to the root
+pub async fn move_path(path: String, folder: String) -> Result<String, UbiquityError> {
+    let move_path = &MovePath { path, folder };
+    let moved: Result<String, tauri_sys::error::Error> = invoke("move_path", move_path).await;
+    match moved {
+        Ok(path) => Ok(path),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+pub async fn delete_path(path: String) -> Result<(), UbiquityError> {
+    let workspace_path = &WorkspacePath { path };
+    let deleted: Result<(), tauri_sys::error::Error> = invoke("delete_path", workspace_path).await;
+    match deleted {
+        Ok(()) => Ok(()),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+pub async fn files_exist(base: String, paths: Vec<String>) -> Result<Vec<bool>, UbiquityError> {
+    let linked_files = &LinkedFiles { base, paths };
+    let exists: Result<Vec<bool>, tauri_sys::error::Error> = invoke("files_exist", linked_files).await;
+    match exists {
+        Ok(exists) => Ok(exists),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
+/// Files that other programs change while they are open
+pub async fn listen_for_file_changes() -> Result<impl Stream<Item = FileChange>, UbiquityError> {
+    let events = tauri_sys::event::listen::<FileChange>(FILE_CHANGED_EVENT).await;
+    match events {
+        Ok(events) => Ok(events.map(|event| event.payload)),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
 pub async fn read_markdown_from_fs(key: AttrValue) -> Result<String, UbiquityError> {
     let path = key.to_string();
     let markdown_file = &MarkdownPath { path };
@@ -28,10 +160,20 @@ pub async fn read_markdown_from_fs(key: AttrValue) -> Result<String, UbiquityErr
     }
 }
 
+pub async fn peek_file(key: AttrValue) -> Result<Option<FileChange>, UbiquityError> {
+    let path = key.to_string();
+    let markdown_file = &MarkdownPath { path };
+    let change: Result<Option<FileChange>, tauri_sys::error::Error> = invoke("peek_file", markdown_file).await;
+    match change {
+        Ok(change) => Ok(change),
+        Err(tauri_error) => Err(UbiquityError::from(tauri_error))
+    }
+}
+
 pub async fn create_new_markdown_file() -> Result<String, UbiquityError> {
     let contents = String::from("");
     let path = None;
-    let markdown: &MarkdownFile = &MarkdownFile { path, contents };
+    let markdown: &SaveFile = &SaveFile { path, contents, backups: BackupPolicy::Off };
     let save_file: Result<String, tauri_sys::error::Error> = invoke("save_file", markdown).await;
     match save_file {
         Ok(path) => Ok(path),
@@ -46,7 +188,7 @@ pub async fn import_markdown_file() -> Result<Markdown, UbiquityError> {
         Ok(markdown_file) => {
             let key = Some(AttrValue::from(markdown_file.path.unwrap()));
             let text = AttrValue::from(markdown_file.contents);
-            let markdown = Markdown {key, text};
+            let markdown = Markdown::from(text, key);
             Ok(markdown)
         }
         Err(tauri_error) => {
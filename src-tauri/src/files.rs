@@ -0,0 +1,180 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions, Permissions},
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use error::UbiquityError;
+use md::BackupPolicy;
+
+/// Save a file, first keeping its previous contents as the backup policy asks.
+///
+/// Backups are taken before anything is written, so a failed save leaves
+/// both the file and its newest backup as they were.
+pub fn save(path: &Path, contents: &str, backups: BackupPolicy) -> Result<(), UbiquityError> {
+    let path = resolve(path);
+    back_up(&path, backups)?;
+    write_atomic(&path, contents.as_bytes())
+}
+
+/// Write an autosave to disk, unless the file already holds the same contents.
+///
+/// Returns whether the file was written, so saves that change nothing leave
+/// the file's modification time alone. Autosaves never take backups, which
+/// would otherwise only ever hold the text from a moment ago.
+pub fn auto_save(path: &Path, contents: &str) -> Result<bool, UbiquityError> {
+    let path = resolve(path);
+    match fs::read(&path) {
+        Ok(current) if current == contents.as_bytes() => return Ok(false),
+        Ok(_) => {}
+        Err(err) if err.kind() == ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    write_atomic(&path, contents.as_bytes())?;
+    Ok(true)
+}
+
+/// Replace a file's contents so that it ends up either as it was or fully
+/// written, never truncated.
+///
+/// The contents go to a temporary file next to it, which is flushed to disk
+/// and then renamed over the original. The original's permissions are kept.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), UbiquityError> {
+    write_atomic_with(path, contents, |file, contents| file.write_all(contents))
+}
+
+/// [`write_atomic`] for files only the user should be able to read, which on
+/// unix are given mode `0600` whatever the file had before
+pub fn write_private(path: &Path, contents: &[u8]) -> Result<(), UbiquityError> {
+    #[cfg(unix)]
+    let permissions = {
+        use std::os::unix::fs::PermissionsExt;
+        Some(Permissions::from_mode(0o600))
+    };
+    #[cfg(not(unix))]
+    let permissions = None;
+
+    write_atomic_as(path, contents, permissions, |file, contents| file.write_all(contents))
+}
+
+/// [`write_atomic`], with the writing of the temporary file left to `write`
+pub fn write_atomic_with<W>(path: &Path, contents: &[u8], write: W) -> Result<(), UbiquityError>
+where
+    W: FnOnce(&mut File, &[u8]) -> io::Result<()>,
+{
+    let permissions = match fs::metadata(path) {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+    write_atomic_as(path, contents, permissions, write)
+}
+
+/// Write atomically, giving the file `permissions`, or the defaults when `None`
+fn write_atomic_as<W>(path: &Path, contents: &[u8], permissions: Option<Permissions>, write: W) -> Result<(), UbiquityError>
+where
+    W: FnOnce(&mut File, &[u8]) -> io::Result<()>,
+{
+    let temp = temp_path(path);
+    let written = write_temp(&temp, contents, permissions, write).and_then(|_| fs::rename(&temp, path));
+
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(err.into());
+    }
+
+    sync_dir(path);
+    Ok(())
+}
+
+/// The temporary file is created with its final mode, so the contents are
+/// never readable by anyone the file itself is not readable by
+fn write_temp<W>(temp: &Path, contents: &[u8], permissions: Option<Permissions>, write: W) -> io::Result<()>
+where
+    W: FnOnce(&mut File, &[u8]) -> io::Result<()>,
+{
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Some(permissions) = &permissions {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(permissions.mode());
+    }
+
+    let mut file = options.open(temp)?;
+    write(&mut file, contents)?;
+    file.sync_all()?;
+
+    // The mode given on creation is narrowed by the umask, and read-only files need their flag set last
+    match permissions {
+        Some(permissions) => file.set_permissions(permissions),
+        None => Ok(()),
+    }
+}
+
+/// Keep a copy of the file as it is now. Nothing is kept for files that do not exist yet.
+fn back_up(path: &Path, backups: BackupPolicy) -> Result<(), UbiquityError> {
+    if matches!(backups, BackupPolicy::Off | BackupPolicy::Numbered(0)) || !path.is_file() {
+        return Ok(());
+    }
+
+    if let BackupPolicy::Numbered(count) = backups {
+        // Make room for the newest, letting the oldest be replaced
+        for number in (1..count).rev() {
+            let older = backup_path(path, BackupPolicy::Numbered(number));
+            if older.exists() {
+                fs::rename(&older, backup_path(path, BackupPolicy::Numbered(number + 1)))?;
+            }
+        }
+    }
+
+    let newest = match backups {
+        BackupPolicy::Numbered(_) => BackupPolicy::Numbered(1),
+        _ => backups,
+    };
+    let backup = backup_path(path, newest);
+    let permissions = fs::metadata(path)?.permissions();
+    write_atomic_as(&backup, &fs::read(path)?, Some(permissions), |file, contents| file.write_all(contents))
+}
+
+/// Where a backup of `path` is kept: `notes.md.bak`, or `notes.md.1.bak`
+/// for the newest of several numbered backups.
+pub fn backup_path(path: &Path, backups: BackupPolicy) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    if let BackupPolicy::Numbered(number) = backups {
+        name.push(format!(".{}", number));
+    }
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// A hidden file in the same folder, so the rename stays on one filesystem.
+/// Each write gets its own, as an autosave and a save can overlap.
+fn temp_path(path: &Path) -> PathBuf {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}-{}.tmp", process::id(), WRITES.fetch_add(1, Ordering::Relaxed)));
+    path.with_file_name(name)
+}
+
+/// Write through symlinks, rather than replacing them with a file
+fn resolve(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Make the rename itself survive a crash. Not every platform can open a folder to flush it.
+fn sync_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let _ = File::open(dir).and_then(|dir| dir.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
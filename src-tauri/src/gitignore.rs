@@ -0,0 +1,155 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A line of a `.gitignore` file
+struct Rule {
+    pattern: Vec<char>,
+    /// Starts with `!`, so it brings back paths an earlier rule ignored
+    negated: bool,
+    /// Ends with `/`, so it only ignores folders
+    folders_only: bool,
+    /// Has a `/` before its end, so it matches from the `.gitignore` file's
+    /// folder rather than against names at any depth
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        // `\#` and `\!` start patterns that really begin with those characters
+        let line = line.strip_prefix('\\').filter(|line| line.starts_with(['#', '!'])).unwrap_or(line);
+        let (folders_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.strip_prefix('/').unwrap_or(line);
+
+        if pattern.is_empty() {
+            return None;
+        }
+        Some(Self { pattern: pattern.chars().collect(), negated, folders_only, anchored })
+    }
+
+    /// Whether the rule matches a path, given relative to its `.gitignore` file's folder
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.folders_only && !is_dir {
+            return false;
+        }
+
+        let path: Vec<char> = match self.anchored {
+            true => path.chars().collect(),
+            false => path.rsplit('/').next().unwrap_or(path).chars().collect(),
+        };
+        glob(&self.pattern, &path)
+    }
+}
+
+/// Match a gitignore glob, where `*` and `?` stay within one folder and `**` crosses them
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob(rest, text) || text.iter().enumerate().any(|(i, c)| *c == '/' && glob(rest, &text[i + 1..]))
+        }
+        ['*', '*'] => true,
+        ['*', rest @ ..] => (0..=text.len()).take_while(|i| *i == 0 || text[i - 1] != '/').any(|i| glob(rest, &text[i..])),
+        ['?', rest @ ..] => matches!(text.first(), Some(c) if *c != '/') && glob(rest, &text[1..]),
+        ['[', set @ ..] => match text.first() {
+            Some(&c) if c != '/' => match class(set, c) {
+                Some((true, len)) => glob(&set[len..], &text[1..]),
+                Some((false, _)) => false,
+                // Without a closing bracket, it is just a bracket
+                None => c == '[' && glob(set, &text[1..]),
+            },
+            _ => false,
+        },
+        ['\\', c, rest @ ..] | [c, rest @ ..] => text.first() == Some(c) && glob(rest, &text[1..]),
+    }
+}
+
+/// Whether `c` is in the bracket expression starting `set`, and how long the
+/// expression is up to and including its `]`. `None` when it is never closed.
+fn class(set: &[char], c: char) -> Option<(bool, usize)> {
+    let (negated, start) = match set.first() {
+        Some('!' | '^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut found = false;
+    let mut i = start;
+    loop {
+        let first = *set.get(i)?;
+        // A `]` straight after the opening bracket is part of the set
+        if first == ']' && i > start {
+            return Some((found != negated, i + 1));
+        }
+
+        if set.get(i + 1) == Some(&'-') && set.get(i + 2).is_some_and(|last| *last != ']') {
+            found |= (first..=set[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            found |= first == c;
+            i += 1;
+        }
+    }
+}
+
+/// The `.gitignore` files of a folder and the folders inside it, read as
+/// they are first needed.
+pub struct Ignores {
+    root: PathBuf,
+    rules: HashMap<PathBuf, Vec<Rule>>,
+}
+
+impl Ignores {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf(), rules: HashMap::new() }
+    }
+
+    /// Whether a path inside the root is ignored, by the `.gitignore` files of
+    /// the folders above it. Deeper files and later lines win, as in git.
+    ///
+    /// Paths in ignored folders are not checked, as the folder's contents
+    /// are left out with it.
+    pub fn is_ignored(&mut self, path: &Path, is_dir: bool) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let names: Vec<String> = relative.iter().map(|name| name.to_string_lossy().into_owned()).collect();
+        if names.last().is_some_and(|name| name == ".git") {
+            return true;
+        }
+
+        let mut ignored = false;
+        for depth in 0..names.len() {
+            let folder: PathBuf = names[..depth].iter().collect();
+            let below = names[depth..].join("/");
+            for rule in self.rules(&folder) {
+                if rule.matches(&below, is_dir) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+
+    fn rules(&mut self, folder: &Path) -> &[Rule] {
+        let root = &self.root;
+        self.rules.entry(folder.to_path_buf()).or_insert_with(|| {
+            let text = fs::read_to_string(root.join(folder).join(".gitignore")).unwrap_or_default();
+            text.lines().filter_map(Rule::parse).collect()
+        })
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use error::UbiquityError;
+use md::Recovery;
+
+use crate::files::write_private;
+
+const EXTENSION: &str = "journal";
+
+/// Keep the latest unsaved edits to a file in `dir`, replacing any kept before.
+///
+/// A journal holds the file's path on its first line and the edited text after
+/// it, and only the user can read it.
+pub fn record(dir: &Path, path: &Path, contents: &str) -> Result<(), UbiquityError> {
+    fs::create_dir_all(dir)?;
+    let journal = format!("{}\n{}", path.to_string_lossy(), contents);
+    write_private(&journal_path(dir, path), journal.as_bytes())
+}
+
+/// Forget the unsaved edits to a file, once they are saved or thrown away
+pub fn discard(dir: &Path, path: &Path) -> Result<(), UbiquityError> {
+    match fs::remove_file(journal_path(dir, path)) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+/// The journals in `dir` holding edits that never made it to disk.
+///
+/// A journal is only worth offering when it is newer than its file and
+/// differs from it. The rest are left over from edits that were saved
+/// later, so they are removed. Journals that cannot be read are skipped,
+/// so one bad journal does not hide the others.
+pub fn pending(dir: &Path) -> Result<Vec<Recovery>, UbiquityError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut recoveries = Vec::new();
+    for entry in entries.flatten() {
+        let journal = entry.path();
+        if journal.extension() != Some(OsStr::new(EXTENSION)) {
+            continue;
+        }
+
+        match recovery(&journal) {
+            Ok(Some(recovery)) => recoveries.push(recovery),
+            Ok(None) => {
+                let _ = fs::remove_file(&journal);
+            }
+            Err(_) => {}
+        }
+    }
+
+    recoveries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(recoveries)
+}
+
+/// The edits a journal holds, or `None` when they are no longer worth offering
+fn recovery(journal: &Path) -> io::Result<Option<Recovery>> {
+    let text = fs::read_to_string(journal)?;
+    let (path, contents) = text.split_once('\n').ok_or_else(|| io::Error::from(ErrorKind::InvalidData))?;
+
+    let saved = match fs::read_to_string(path) {
+        Ok(saved) => Some(saved),
+        Err(err) if err.kind() == ErrorKind::NotFound => None,
+        Err(err) => return Err(err),
+    };
+
+    let newer = match (fs::metadata(journal), fs::metadata(path)) {
+        (Ok(recorded), Ok(file)) => recorded.modified()? > file.modified()?,
+        _ => true,
+    };
+
+    Ok((newer && saved.as_deref() != Some(contents))
+        .then(|| Recovery { path: path.to_string(), contents: contents.to_string(), saved }))
+}
+
+/// The journal for a file, named after it so the folder can be read by hand.
+///
+/// The hash of the path keeps files with the same name apart, and has to
+/// stay the same between versions of Ubiquity to find journals kept by older ones.
+fn journal_path(dir: &Path, path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    dir.join(format!("{}-{:016x}.{}", name, fnv1a(path.to_string_lossy().as_bytes()), EXTENSION))
+}
+
+/// The 64 bit FNV-1a hash, which unlike the standard library's hashers is fixed
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3))
+}
@@ -0,0 +1,7 @@
+//! The file handling behind Ubiquity's Tauri commands, kept out of `main.rs` so it can be tested.
+
+pub mod files;
+pub mod gitignore;
+pub mod journal;
+pub mod open_files;
+pub mod workspace;
@@ -4,11 +4,13 @@
 )]
 #![feature(let_chains)]
 
-use std::{fs, path::PathBuf};
+use std::{collections::HashSet, path::{Path, PathBuf}, sync::Mutex};
 
 use error::UbiquityError;
-use tauri::{generate_context, Manager};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{generate_context, Manager, State};
 use md::*;
+use ubiquity::{files, journal, open_files::OpenFiles, workspace};
 
 use rfd::FileDialog;
 
@@ -18,7 +20,9 @@ use window_shadows::set_shadow;
 
 fn main() {
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![read_file, save_file, open_file_dialog])
+        .manage(OpenFiles::default())
+        .manage(OpenWorkspace::load())
+        .invoke_handler(tauri::generate_handler![read_file, peek_file, save_file, open_file_dialog, export_file, files_exist, auto_save, record_edit, discard_recovery, pending_recoveries, open_folder_dialog, close_folder, scan_workspace, create_workspace_file, rename_path, move_path, delete_path])
         .setup(|app| {
             let window = app.get_window("main").unwrap();
 
@@ -33,6 +37,19 @@ fn main() {
             #[cfg(any(windows, target_os = "macos"))]
             set_shadow(&window, true).unwrap();
 
+            let handle = app.handle();
+            let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event && (event.kind.is_create() || event.kind.is_modify()) {
+                    let open_files = handle.state::<OpenFiles>();
+                    for path in &event.paths {
+                        if let Some(change) = open_files.changes(path) {
+                            let _ = handle.emit_all(FILE_CHANGED_EVENT, change);
+                        }
+                    }
+                }
+            })?;
+            app.manage(FileWatcher { watcher: Mutex::new(watcher), folders: Mutex::default() });
+
             Ok(())
         })
         .run(generate_context!())
@@ -40,11 +57,11 @@ fn main() {
 }
 
 #[tauri::command]
-fn save_file(path: Option<String>, contents: String) -> Result<String, UbiquityError> {
-    if let Some(path_key) = path && !path_key.eq(&DOCS_KEY) {
+fn save_file(path: Option<String>, contents: String, backups: BackupPolicy, open_files: State<OpenFiles>, watcher: State<FileWatcher>) -> Result<String, UbiquityError> {
+    if let Some(path_key) = path {
       let path = PathBuf::from(path_key.clone());
       
-      match save_to_fs(path, contents) {
+      match save_to_fs(path, contents, backups, &open_files, &watcher) {
         Ok(_) => Ok(path_key),
         Err(err) => Err(err)
       }
@@ -57,7 +74,7 @@ fn save_file(path: Option<String>, contents: String) -> Result<String, UbiquityE
     
         match file_dialog {
           Some(file_handle) => {
-            fs::write(file_handle.clone(), contents)?;
+            save_to_fs(file_handle.clone(), contents, backups, &open_files, &watcher)?;
             Ok(file_handle.to_str().unwrap().to_string())
           },
           None => Err(UbiquityError::no_save_path_selected()),
@@ -66,7 +83,30 @@ fn save_file(path: Option<String>, contents: String) -> Result<String, UbiquityE
 }
 
 #[tauri::command]
-fn open_file_dialog() -> Result<MarkdownFile, UbiquityError> {
+fn export_file(contents: String, extension: String) -> Result<String, UbiquityError> {
+    let mut dir = PathBuf::from("/");
+    if let Some(docs_dir) = dirs::document_dir() {
+      dir = docs_dir;
+    }
+    let file_dialog = FileDialog::new()
+        .set_directory(dir)
+        .add_filter(&extension.to_uppercase(), &[&extension])
+        .save_file();
+
+    match file_dialog {
+      Some(mut file_handle) => {
+        if file_handle.extension().is_none() {
+          file_handle.set_extension(&extension);
+        }
+        files::write_atomic(&file_handle, contents.as_bytes())?;
+        Ok(file_handle.to_str().unwrap().to_string())
+      },
+      None => Err(UbiquityError::no_save_path_selected()),
+    }
+}
+
+#[tauri::command]
+fn open_file_dialog(open_files: State<OpenFiles>, watcher: State<FileWatcher>) -> Result<MarkdownFile, UbiquityError> {
     let mut dir = PathBuf::from("/");
     if let Some(docs_dir) = dirs::document_dir() {
       dir = docs_dir;
@@ -74,7 +114,7 @@ fn open_file_dialog() -> Result<MarkdownFile, UbiquityError> {
     let file_dialog_res = FileDialog::new().set_directory(dir).pick_file();
 
     if let Some(file_handle) = file_dialog_res {
-        let contents = read_from_fs(file_handle.clone())?;
+        let contents = read_from_fs(file_handle.clone(), &open_files, &watcher)?;
         let path = Some(file_handle.to_str().unwrap().to_string());
         let markdown_file = MarkdownFile { path, contents};
         Ok(markdown_file)
@@ -83,20 +123,180 @@ fn open_file_dialog() -> Result<MarkdownFile, UbiquityError> {
     }
 }
 
+/// Reading a file again also takes in changes made by other programs, so it can be saved over them
+#[tauri::command]
+fn read_file(path: String, open_files: State<OpenFiles>, watcher: State<FileWatcher>) -> Result<String, UbiquityError> {
+    read_from_fs(PathBuf::from(path), &open_files, &watcher)
+}
+
+/// How a file with unsaved edits differs on disk, leaving changes made by
+/// other programs for the user to settle rather than taking them in
+#[tauri::command]
+fn peek_file(path: String, open_files: State<OpenFiles>, watcher: State<FileWatcher>) -> Result<Option<FileChange>, UbiquityError> {
+    let path = PathBuf::from(path);
+    let change = open_files.peek(&path)?;
+    watcher.watch(&path);
+    Ok(change)
+}
+
+/// Whether each linked path exists, resolving relative paths against the document's folder
+#[tauri::command]
+fn files_exist(base: String, paths: Vec<String>) -> Vec<bool> {
+    let dir = Path::new(&base).parent().unwrap_or(Path::new(""));
+    paths.iter().map(|path| dir.join(path).exists()).collect()
+}
+
+fn read_from_fs(path: PathBuf, open_files: &OpenFiles, watcher: &FileWatcher) -> Result<String, UbiquityError> {
+    let contents = open_files.open(&path)?;
+    watcher.watch(&path);
+    Ok(contents)
+}
+
+fn save_to_fs(path: PathBuf, contents: String, backups: BackupPolicy, open_files: &OpenFiles, watcher: &FileWatcher) -> Result<(), UbiquityError> {
+    open_files.save(&path, &contents, || files::save(&path, &contents, backups))?;
+    watcher.watch(&path);
+    forget_edits(&path);
+    Ok(())
+}
+
+/// Returns whether the file was written, which it is not when nothing changed
+#[tauri::command]
+fn auto_save(path: PathBuf, contents: String, open_files: State<OpenFiles>, watcher: State<FileWatcher>) -> Result<bool, UbiquityError> {
+    let saved = open_files.save(&path, &contents, || files::auto_save(&path, &contents))?;
+    watcher.watch(&path);
+    forget_edits(&path);
+    Ok(saved)
+}
+
+/// Watches the folders of open files, telling the frontend when another program changes one
+struct FileWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    folders: Mutex<HashSet<PathBuf>>,
+}
+
+impl FileWatcher {
+    /// Folders are watched rather than files, as a file that another editor
+    /// saves by renaming a new one over it would stop being watched.
+    /// Files that cannot be watched can still be saved, so errors are left out.
+    fn watch(&self, path: &Path) {
+        let Some(folder) = path.parent() else {
+            return;
+        };
+        let mut folders = self.folders.lock().unwrap();
+        if !folders.contains(folder) && self.watcher.lock().unwrap().watch(folder, RecursiveMode::NonRecursive).is_ok() {
+            folders.insert(folder.to_path_buf());
+        }
+    }
+}
+
+/// Only the backend can find the data folder, so the frontend never passes it.
+/// The saved config is used when there is one, as users may have moved the folder.
+fn recovery_folder() -> Result<PathBuf, UbiquityError> {
+    config::read_config_file().unwrap_or_default().recovery_folder()
+}
+
+/// Once a file is saved there is nothing left to recover. Failing to
+/// remove the journal is not worth failing the save over.
+fn forget_edits(path: &Path) {
+    if let Ok(folder) = recovery_folder() {
+        let _ = journal::discard(&folder, path);
+    }
+}
+
+/// Keep unsaved edits where they can be recovered if Ubiquity closes before they are saved
 #[tauri::command]
-fn read_file(path: String) -> Result<String, UbiquityError> {
-    Ok(fs::read_to_string(PathBuf::from(path))?)
+fn record_edit(path: PathBuf, contents: String) -> Result<(), UbiquityError> {
+    journal::record(&recovery_folder()?, &path, &contents)
 }
 
-fn read_from_fs(path: PathBuf) -> Result<String, UbiquityError> {
-  Ok(fs::read_to_string(PathBuf::from(path))?)
+#[tauri::command]
+fn discard_recovery(path: PathBuf) -> Result<(), UbiquityError> {
+    journal::discard(&recovery_folder()?, &path)
+}
+
+#[tauri::command]
+fn pending_recoveries() -> Result<Vec<Recovery>, UbiquityError> {
+    journal::pending(&recovery_folder()?)
 }
 
-fn save_to_fs(path: PathBuf, contents: String) -> Result<(), UbiquityError> {
-    Ok(fs::write(path, contents)?)
+/// The folder open as a workspace. Workspace commands only ever reach
+/// inside it, so it is kept here rather than passed in by the frontend.
+struct OpenWorkspace {
+    root: Mutex<Option<PathBuf>>,
 }
 
+impl OpenWorkspace {
+    /// The folder that was open when Ubiquity last closed
+    fn load() -> Self {
+        let root = config::read_config_file().ok().and_then(|config| config.workspace);
+        Self { root: Mutex::new(root) }
+    }
+
+    fn root(&self) -> Result<PathBuf, UbiquityError> {
+        self.root.lock().unwrap().clone().ok_or_else(UbiquityError::no_workspace)
+    }
+
+    /// Failing to remember the folder only means opening it again next
+    /// time, which is not worth failing over.
+    fn set(&self, root: Option<PathBuf>) {
+        let mut config = config::read_config_file().unwrap_or_default();
+        config.workspace = root.clone();
+        let _ = config.save();
+        *self.root.lock().unwrap() = root;
+    }
+}
+
+/// Open a folder as a workspace, listing the markdown files in it
 #[tauri::command]
-fn auto_save(path: PathBuf, contents: String) -> Result<(), UbiquityError> {
-    Ok(fs::write(path, contents)?)
-}
\ No newline at end of file
+fn open_folder_dialog(open_workspace: State<OpenWorkspace>) -> Result<Workspace, UbiquityError> {
+    let mut dir = PathBuf::from("/");
+    if let Some(docs_dir) = dirs::document_dir() {
+      dir = docs_dir;
+    }
+
+    match FileDialog::new().set_directory(dir).pick_folder() {
+      Some(folder) => {
+        let workspace = workspace::scan(&folder)?;
+        open_workspace.set(Some(folder));
+        Ok(workspace)
+      },
+      None => Err(UbiquityError::no_folder_selected()),
+    }
+}
+
+#[tauri::command]
+fn close_folder(open_workspace: State<OpenWorkspace>) {
+    open_workspace.set(None);
+}
+
+#[tauri::command]
+fn scan_workspace(open_workspace: State<OpenWorkspace>) -> Result<Workspace, UbiquityError> {
+    workspace::scan(&open_workspace.root()?)
+}
+
+/// Returns the new file's full path
+#[tauri::command]
+fn create_workspace_file(path: String, open_workspace: State<OpenWorkspace>) -> Result<String, UbiquityError> {
+    workspace::create_file(&open_workspace.root()?, &path)
+}
+
+/// Returns the new path, relative to the root
+#[tauri::command]
+fn rename_path(path: String, name: String, open_workspace: State<OpenWorkspace>) -> Result<String, UbiquityError> {
+    workspace::rename(&open_workspace.root()?, &path, &name)
+}
+
+/// Returns the new path, relative to the root
+#[tauri::command]
+fn move_path(path: String, folder: String, open_workspace: State<OpenWorkspace>) -> Result<String, UbiquityError> {
+    workspace::move_to(&open_workspace.root()?, &path, &folder)
+}
+
+#[tauri::command]
+fn delete_path(path: String, open_workspace: State<OpenWorkspace>) -> Result<(), UbiquityError> {
+    let root = open_workspace.root()?;
+    workspace::delete_file(&root, &path)?;
+    // A deleted file has no edits left to recover
+    forget_edits(&root.join(path));
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use error::UbiquityError;
+use md::FileChange;
+
+/// A file as Ubiquity last read or wrote it
+struct Known {
+    /// As the frontend knows it, which may differ from the path the watcher reports
+    path: String,
+    modified: Option<SystemTime>,
+    hash: u64,
+    contents: String,
+}
+
+impl Known {
+    fn new(path: &Path, contents: String) -> Self {
+        let path_name = path.to_string_lossy().into_owned();
+        Self { path: path_name, modified: modified(path), hash: hash(&contents), contents }
+    }
+}
+
+/// The files Ubiquity has open, as it last saw them on disk.
+///
+/// This tells Ubiquity's own writes apart from other programs', and stops
+/// a save from overwriting changes made since the file was last read.
+#[derive(Default)]
+pub struct OpenFiles {
+    known: Mutex<HashMap<PathBuf, Known>>,
+}
+
+impl OpenFiles {
+    /// Read a file and remember it as it is now, which takes in any changes
+    /// made by other programs.
+    pub fn open(&self, path: &Path) -> Result<String, UbiquityError> {
+        let mut known = self.known.lock().unwrap();
+        let contents = fs::read_to_string(path)?;
+        known.insert(key(path), Known::new(path, contents.clone()));
+        Ok(contents)
+    }
+
+    /// Write a file with `write`, unless another program has changed it
+    /// since Ubiquity last saw it.
+    ///
+    /// Files that were never opened are written without a check, as there
+    /// is nothing to compare them with.
+    pub fn save<T, W>(&self, path: &Path, contents: &str, write: W) -> Result<T, UbiquityError>
+    where
+        W: FnOnce() -> Result<T, UbiquityError>,
+    {
+        // Held throughout, so the watcher never sees this write as someone else's
+        let mut known = self.known.lock().unwrap();
+
+        if let Some(file) = known.get(&key(path)) {
+            if changed(path, file)? {
+                return Err(UbiquityError::file_changed());
+            }
+        }
+
+        let written = write()?;
+        // Looked up again, as a new file only has somewhere to lead once it is written
+        known.insert(key(path), Known::new(path, contents.to_string()));
+        Ok(written)
+    }
+
+    /// How an open file differs from when Ubiquity last saw it, or `None`
+    /// when it does not, is not open or can no longer be read.
+    ///
+    /// The file is not remembered as changed until it is opened again, so
+    /// saves stay blocked until the user decides what to keep.
+    pub fn changes(&self, path: &Path) -> Option<FileChange> {
+        let known = self.known.lock().unwrap();
+        let file = known.get(&key(path))?;
+
+        let contents = fs::read_to_string(path).ok()?;
+        change(file, contents)
+    }
+
+    /// Read a file without taking in changes made by other programs, for
+    /// files with unsaved edits. Returns how it differs, like [`changes`](Self::changes).
+    ///
+    /// Files that were never opened are remembered as they are now, as
+    /// there is no earlier version of them to lose.
+    pub fn peek(&self, path: &Path) -> Result<Option<FileChange>, UbiquityError> {
+        let mut known = self.known.lock().unwrap();
+        let contents = fs::read_to_string(path)?;
+
+        match known.get(&key(path)) {
+            Some(file) => Ok(change(file, contents)),
+            None => {
+                known.insert(key(path), Known::new(path, contents));
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// How `contents` differs from the file as it was last seen, if at all
+fn change(file: &Known, contents: String) -> Option<FileChange> {
+    if hash(&contents) == file.hash {
+        return None;
+    }
+    Some(FileChange { path: file.path.clone(), contents, base: file.contents.clone() })
+}
+
+/// Whether the file on disk differs from how it was last seen. A file that
+/// was only touched has a new modification time but the same contents.
+fn changed(path: &Path, file: &Known) -> Result<bool, UbiquityError> {
+    if file.modified.is_some() && modified(path) == file.modified {
+        return Ok(false);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(hash(&contents) != file.hash),
+        // Saving puts back a file that was deleted
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Paths from the frontend and from the watcher can differ in form, so
+/// both are looked up by where they lead.
+fn key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
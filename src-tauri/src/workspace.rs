@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use error::UbiquityError;
+use md::{Workspace, WorkspaceFile};
+use walkdir::WalkDir;
+
+use crate::gitignore::Ignores;
+
+/// The extensions of the files listed in a workspace
+pub const MARKDOWN_EXTENSIONS: [&str; 4] = ["md", "markdown", "mdown", "mkd"];
+
+/// Every markdown file in a folder and the folders inside it, leaving out
+/// what its `.gitignore` files ignore. Folders that cannot be read are skipped.
+pub fn scan(root: &Path) -> Result<Workspace, UbiquityError> {
+    if !root.is_dir() {
+        return Err(io::Error::from(ErrorKind::NotFound).into());
+    }
+
+    let mut ignores = Ignores::new(root);
+    let entries = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !ignores.is_ignored(entry.path(), entry.file_type().is_dir()))
+        .filter_map(Result::ok);
+
+    let files = entries
+        .filter(|entry| entry.file_type().is_file() && is_markdown(entry.path()))
+        .map(|entry| WorkspaceFile {
+            path: entry.path().to_string_lossy().into_owned(),
+            relative: relative(root, entry.path()),
+        })
+        .collect();
+
+    Ok(Workspace { root: root.to_string_lossy().into_owned(), files })
+}
+
+/// Create an empty markdown file, and any folders it needs. Names without a
+/// markdown extension are given `.md`.
+///
+/// Returns the new file's full path.
+pub fn create_file(root: &Path, path: &str) -> Result<String, UbiquityError> {
+    let mut file = inside(root, path)?;
+    if !is_markdown(&file) {
+        let mut name = file.file_name().unwrap_or_default().to_os_string();
+        name.push(".md");
+        file.set_file_name(name);
+    }
+
+    if let Some(folder) = file.parent() {
+        fs::create_dir_all(folder)?;
+    }
+    fs::OpenOptions::new().write(true).create_new(true).open(&file)?;
+    Ok(file.to_string_lossy().into_owned())
+}
+
+/// Give a file or folder a new name in the same folder.
+///
+/// Returns its new path, relative to the root.
+pub fn rename(root: &Path, path: &str, name: &str) -> Result<String, UbiquityError> {
+    let from = inside(root, path)?;
+    let mut components = Path::new(name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        return Err(UbiquityError::invalid_workspace_path(name.to_string()));
+    }
+
+    let to = from.with_file_name(name);
+    move_path(&from, &to)?;
+    Ok(relative(root, &to))
+}
+
+/// Move a file or folder into another folder of the workspace, where an
+/// empty `folder` is the root.
+///
+/// Returns its new path, relative to the root.
+pub fn move_to(root: &Path, path: &str, folder: &str) -> Result<String, UbiquityError> {
+    let from = inside(root, path)?;
+    let folder = match folder.is_empty() {
+        true => root.to_path_buf(),
+        false => inside(root, folder)?,
+    };
+    // A folder cannot go inside itself
+    if folder.starts_with(&from) {
+        return Err(UbiquityError::invalid_workspace_path(path.to_string()));
+    }
+
+    let to = folder.join(from.file_name().unwrap_or_default());
+    move_path(&from, &to)?;
+    Ok(relative(root, &to))
+}
+
+/// Delete a file. Folders are left alone, as they can hold more than markdown.
+pub fn delete_file(root: &Path, path: &str) -> Result<(), UbiquityError> {
+    let file = inside(root, path)?;
+    if !file.is_file() {
+        return Err(UbiquityError::invalid_workspace_path(path.to_string()));
+    }
+    Ok(fs::remove_file(file)?)
+}
+
+/// Renaming replaces whatever is at `to` on some platforms, so that is checked first
+fn move_path(from: &Path, to: &Path) -> Result<(), UbiquityError> {
+    if to.exists() {
+        return Err(io::Error::from(ErrorKind::AlreadyExists).into());
+    }
+    Ok(fs::rename(from, to)?)
+}
+
+/// A path relative to the root, refused if it could lead anywhere outside it
+fn inside(root: &Path, path: &str) -> Result<PathBuf, UbiquityError> {
+    let relative = Path::new(path);
+    let plain = relative.components().all(|component| matches!(component, Component::Normal(_)));
+    if path.is_empty() || !plain {
+        return Err(UbiquityError::invalid_workspace_path(path.to_string()));
+    }
+    Ok(root.join(relative))
+}
+
+/// A path inside the root, relative to it and with `/` between folders on every platform
+fn relative(root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative.iter().map(|name| name.to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| MARKDOWN_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use std::{fs, io::{self, Write}, path::Path};
+
+use common::TempDir;
+use md::BackupPolicy;
+use ubiquity::files::{backup_path, save, write_atomic, write_atomic_with};
+
+/// The names of everything in a folder, sorted
+fn entries(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_write_atomic_replaces_contents() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Old\n\nA much longer first version\n").unwrap();
+
+    assert_eq!(write_atomic(&path, b"# New\n"), Ok(()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# New\n");
+    assert_eq!(entries(dir.path()), vec!["notes.md"]);
+}
+
+#[test]
+fn test_failed_write_keeps_original() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+
+    // Half the file makes it to disk before it fills up
+    let result = write_atomic_with(&path, b"# Rewritten notes\n", |file, contents| {
+        file.write_all(&contents[..contents.len() / 2])?;
+        Err(io::Error::new(io::ErrorKind::WriteZero, "No space left on device"))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Notes\n");
+    assert_eq!(entries(dir.path()), vec!["notes.md"]);
+}
+
+#[test]
+fn test_failed_write_of_new_file_leaves_nothing() {
+    let dir = TempDir::new();
+    let path = dir.join("new.md");
+
+    let result = write_atomic_with(&path, b"# New\n", |file, _| {
+        file.write_all(b"# N")?;
+        Err(io::Error::new(io::ErrorKind::Interrupted, "Killed"))
+    });
+
+    assert!(result.is_err());
+    assert!(entries(dir.path()).is_empty());
+}
+
+#[test]
+fn test_failed_rename_keeps_original() {
+    let dir = TempDir::new();
+    // A file cannot be renamed over a folder that has something in it
+    let path = dir.join("notes.md");
+    fs::create_dir(&path).unwrap();
+    fs::write(path.join("inside.md"), "# Inside\n").unwrap();
+
+    assert!(write_atomic(&path, b"# Notes\n").is_err());
+    assert_eq!(entries(dir.path()), vec!["notes.md"]);
+    assert_eq!(fs::read_to_string(path.join("inside.md")).unwrap(), "# Inside\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    assert_eq!(save(&path, "# Changed\n", BackupPolicy::Single), Ok(()));
+
+    let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&path), 0o640);
+    assert_eq!(mode(&backup_path(&path, BackupPolicy::Single)), 0o640);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_private_contents_are_never_readable_by_others() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new();
+    let path = dir.join("secret.md");
+    fs::write(&path, "# Secret\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+
+    // The temporary file has the file's mode before anything is written to it
+    let result = write_atomic_with(&path, b"# Still secret\n", |file, contents| {
+        assert_eq!(file.metadata()?.permissions().mode() & 0o777, 0o600);
+        file.write_all(contents)
+    });
+    assert_eq!(result, Ok(()));
+
+    assert_eq!(save(&path, "# Changed\n", BackupPolicy::Single), Ok(()));
+    assert_eq!(save(&path, "# Changed again\n", BackupPolicy::Single), Ok(()));
+    let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&path), 0o600);
+    assert_eq!(mode(&backup_path(&path, BackupPolicy::Single)), 0o600);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_save_writes_through_symlinks() {
+    let dir = TempDir::new();
+    let target = dir.join("target.md");
+    let link = dir.join("link.md");
+    fs::write(&target, "# Target\n").unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    assert_eq!(save(&link, "# Changed\n", BackupPolicy::Off), Ok(()));
+    assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_to_string(&target).unwrap(), "# Changed\n");
+}
+
+#[test]
+fn test_no_backups() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+
+    assert_eq!(save(&path, "1", BackupPolicy::Off), Ok(()));
+    assert_eq!(save(&path, "2", BackupPolicy::Off), Ok(()));
+    assert_eq!(entries(dir.path()), vec!["notes.md"]);
+}
+
+#[test]
+fn test_single_backup() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+
+    // A new file has nothing to back up
+    assert_eq!(save(&path, "1", BackupPolicy::Single), Ok(()));
+    assert_eq!(entries(dir.path()), vec!["notes.md"]);
+
+    assert_eq!(save(&path, "2", BackupPolicy::Single), Ok(()));
+    assert_eq!(save(&path, "3", BackupPolicy::Single), Ok(()));
+    assert_eq!(entries(dir.path()), vec!["notes.md", "notes.md.bak"]);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "3");
+    assert_eq!(fs::read_to_string(dir.join("notes.md.bak")).unwrap(), "2");
+}
+
+#[test]
+fn test_numbered_backups_roll() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+
+    for version in 1..=5 {
+        assert_eq!(save(&path, &version.to_string(), BackupPolicy::Numbered(3)), Ok(()));
+    }
+
+    assert_eq!(entries(dir.path()), vec!["notes.md", "notes.md.1.bak", "notes.md.2.bak", "notes.md.3.bak"]);
+    let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+    assert_eq!(read("notes.md"), "5");
+    assert_eq!(read("notes.md.1.bak"), "4");
+    assert_eq!(read("notes.md.2.bak"), "3");
+    assert_eq!(read("notes.md.3.bak"), "2");
+}
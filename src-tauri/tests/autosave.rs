@@ -0,0 +1,41 @@
+mod common;
+
+use std::{fs, thread, time::Duration};
+
+use common::TempDir;
+use ubiquity::files::auto_save;
+
+#[test]
+fn test_auto_save_creates_file() {
+    let dir = TempDir::new();
+    let path = dir.join("new.md");
+
+    assert_eq!(auto_save(&path, "# New\n"), Ok(true));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# New\n");
+}
+
+#[test]
+fn test_auto_save_skips_unchanged_contents() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+    // Long enough for a write to show up in the modification time
+    thread::sleep(Duration::from_millis(50));
+
+    assert_eq!(auto_save(&path, "# Notes\n"), Ok(false));
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+
+    assert_eq!(auto_save(&path, "# Notes\n\nMore\n"), Ok(true));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Notes\n\nMore\n");
+}
+
+#[test]
+fn test_auto_save_reports_errors() {
+    let dir = TempDir::new();
+
+    assert!(auto_save(&dir.join("missing/notes.md"), "text").is_err());
+    // A directory cannot be read or written as a file
+    assert!(auto_save(dir.path(), "text").is_err());
+}
@@ -0,0 +1,34 @@
+// Each test uses only some of these helpers
+#![allow(dead_code)]
+
+use std::{fs, path::{Path, PathBuf}, process, sync::atomic::{AtomicUsize, Ordering}};
+
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory under the system temp directory, removed again when dropped
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new() -> Self {
+        let name = format!("ubiquity-test-{}-{}", process::id(), COUNT.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use std::{fs, path::Path, thread, time::Duration};
+
+use common::TempDir;
+use md::Recovery;
+use ubiquity::journal::{discard, pending, record};
+
+/// Long enough for a write to show up in the modification time
+fn pause() {
+    thread::sleep(Duration::from_millis(50));
+}
+
+#[test]
+fn test_record_and_recover() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    pause();
+
+    assert_eq!(record(&journals, &path, "# Notes\n\nFirst\n"), Ok(()));
+    assert_eq!(record(&journals, &path, "# Notes\n\nSecond\n"), Ok(()));
+
+    let recovery = Recovery {
+        path: path.to_string_lossy().into_owned(),
+        contents: String::from("# Notes\n\nSecond\n"),
+        saved: Some(String::from("# Notes\n")),
+    };
+    assert_eq!(pending(&journals), Ok(vec![recovery]));
+
+    assert_eq!(discard(&journals, &path), Ok(()));
+    assert_eq!(pending(&journals), Ok(Vec::new()));
+    // Discarding twice is fine
+    assert_eq!(discard(&journals, &path), Ok(()));
+}
+
+#[test]
+fn test_saved_edits_are_not_recovered() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    let (saved_later, unchanged) = (dir.join("later.md"), dir.join("unchanged.md"));
+    fs::write(&unchanged, "# Unchanged\n").unwrap();
+    pause();
+
+    assert_eq!(record(&journals, &saved_later, "# Journaled\n"), Ok(()));
+    assert_eq!(record(&journals, &unchanged, "# Unchanged\n"), Ok(()));
+    pause();
+    fs::write(&saved_later, "# Saved after the journal\n").unwrap();
+
+    assert_eq!(pending(&journals), Ok(Vec::new()));
+    // Both are cleared away
+    assert_eq!(fs::read_dir(&journals).unwrap().count(), 0);
+}
+
+#[test]
+fn test_deleted_file_is_recovered() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    let path = dir.join("gone.md");
+
+    assert_eq!(record(&journals, &path, "# Gone\n"), Ok(()));
+
+    let recoveries = pending(&journals).unwrap();
+    assert_eq!(recoveries.len(), 1);
+    assert_eq!(recoveries[0].contents, "# Gone\n");
+    assert_eq!(recoveries[0].saved, None);
+}
+
+#[test]
+fn test_files_are_journaled_separately() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    // The same name in different folders
+    let (first, second) = (dir.join("a/notes.md"), dir.join("b/notes.md"));
+
+    assert_eq!(record(&journals, &first, "first"), Ok(()));
+    assert_eq!(record(&journals, &second, "second"), Ok(()));
+
+    let contents: Vec<String> = pending(&journals).unwrap().into_iter().map(|recovery| recovery.contents).collect();
+    assert_eq!(contents, vec!["first", "second"]);
+}
+
+#[test]
+fn test_nothing_pending_without_journals() {
+    let dir = TempDir::new();
+    assert_eq!(pending(&dir.join("recovery")), Ok(Vec::new()));
+    assert_eq!(pending(dir.path()), Ok(Vec::new()));
+}
+
+#[test]
+fn test_journal_names_are_stable() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+
+    assert_eq!(record(&journals, Path::new("/notes/todo.md"), "# Todo\n"), Ok(()));
+    assert!(journals.join("todo.md-5e3d922c90a929a7.journal").is_file());
+}
+
+#[test]
+fn test_bad_journals_are_skipped() {
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    let path = dir.join("notes.md");
+
+    assert_eq!(record(&journals, &path, "# Notes\n"), Ok(()));
+    fs::write(journals.join("invalid.journal"), [0xff, 0xfe, b'\n']).unwrap();
+    fs::write(journals.join("no-path.journal"), "").unwrap();
+    fs::create_dir(journals.join("folder.journal")).unwrap();
+
+    let paths: Vec<String> = pending(&journals).unwrap().into_iter().map(|recovery| recovery.path).collect();
+    assert_eq!(paths, vec![path.to_string_lossy().into_owned()]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_journals_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new();
+    let journals = dir.join("recovery");
+    assert_eq!(record(&journals, &dir.join("notes.md"), "# Notes\n"), Ok(()));
+
+    for entry in fs::read_dir(&journals).unwrap() {
+        assert_eq!(entry.unwrap().metadata().unwrap().permissions().mode() & 0o777, 0o600);
+    }
+}
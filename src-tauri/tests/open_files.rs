@@ -0,0 +1,131 @@
+mod common;
+
+use std::{fs, thread, time::Duration};
+
+use common::TempDir;
+use error::UbiquityError;
+use md::FileChange;
+use ubiquity::open_files::OpenFiles;
+
+/// Long enough for a write to show up in the modification time
+fn pause() {
+    thread::sleep(Duration::from_millis(50));
+}
+
+fn write(path: &std::path::Path, contents: &str) -> impl FnOnce() -> Result<(), UbiquityError> {
+    let (path, contents) = (path.to_path_buf(), contents.to_string());
+    move || Ok(fs::write(path, contents)?)
+}
+
+#[test]
+fn test_own_saves_are_not_changes() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    let open_files = OpenFiles::default();
+
+    assert_eq!(open_files.open(&path), Ok(String::from("# Notes\n")));
+    assert_eq!(open_files.changes(&path), None);
+
+    pause();
+    assert_eq!(open_files.save(&path, "# Mine\n", write(&path, "# Mine\n")), Ok(()));
+    assert_eq!(open_files.changes(&path), None);
+
+    pause();
+    assert_eq!(open_files.save(&path, "# Mine again\n", write(&path, "# Mine again\n")), Ok(()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Mine again\n");
+}
+
+#[test]
+fn test_changes_by_others_block_saves() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    let open_files = OpenFiles::default();
+    open_files.open(&path).unwrap();
+
+    pause();
+    fs::write(&path, "# Theirs\n").unwrap();
+
+    let change = FileChange {
+        path: path.to_string_lossy().into_owned(),
+        contents: String::from("# Theirs\n"),
+        base: String::from("# Notes\n"),
+    };
+    assert_eq!(open_files.changes(&path), Some(change));
+
+    assert_eq!(open_files.save(&path, "# Mine\n", write(&path, "# Mine\n")), Err(UbiquityError::file_changed()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Theirs\n");
+
+    // Opening the file again takes in their changes, so keeping mine can overwrite them
+    assert_eq!(open_files.open(&path), Ok(String::from("# Theirs\n")));
+    assert_eq!(open_files.changes(&path), None);
+    assert_eq!(open_files.save(&path, "# Mine\n", write(&path, "# Mine\n")), Ok(()));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Mine\n");
+}
+
+#[test]
+fn test_touched_files_are_not_changes() {
+    let dir = TempDir::new();
+    let path = dir.join("notes.md");
+    fs::write(&path, "# Notes\n").unwrap();
+    let open_files = OpenFiles::default();
+    open_files.open(&path).unwrap();
+
+    pause();
+    fs::write(&path, "# Notes\n").unwrap();
+
+    assert_eq!(open_files.changes(&path), None);
+    assert_eq!(open_files.save(&path, "# Mine\n", write(&path, "# Mine\n")), Ok(()));
+}
+
+#[test]
+fn test_unopened_and_deleted_files() {
+    let dir = TempDir::new();
+    let (unopened, deleted) = (dir.join("unopened.md"), dir.join("deleted.md"));
+    fs::write(&unopened, "# Unopened\n").unwrap();
+    fs::write(&deleted, "# Deleted\n").unwrap();
+    let open_files = OpenFiles::default();
+
+    assert_eq!(open_files.changes(&unopened), None);
+    assert_eq!(open_files.save(&unopened, "# Mine\n", write(&unopened, "# Mine\n")), Ok(()));
+
+    open_files.open(&deleted).unwrap();
+    fs::remove_file(&deleted).unwrap();
+    assert_eq!(open_files.changes(&deleted), None);
+    // Saving puts it back
+    assert_eq!(open_files.save(&deleted, "# Back\n", write(&deleted, "# Back\n")), Ok(()));
+    assert_eq!(fs::read_to_string(&deleted).unwrap(), "# Back\n");
+}
+
+#[test]
+fn test_peeking_keeps_changes_by_others() {
+    let dir = TempDir::new();
+    let (path, unopened) = (dir.join("notes.md"), dir.join("unopened.md"));
+    fs::write(&path, "# Notes\n").unwrap();
+    fs::write(&unopened, "# Unopened\n").unwrap();
+    let open_files = OpenFiles::default();
+    open_files.open(&path).unwrap();
+    assert_eq!(open_files.peek(&path), Ok(None));
+
+    pause();
+    fs::write(&path, "# Theirs\n").unwrap();
+
+    let change = FileChange {
+        path: path.to_string_lossy().into_owned(),
+        contents: String::from("# Theirs\n"),
+        base: String::from("# Notes\n"),
+    };
+    assert_eq!(open_files.peek(&path), Ok(Some(change.clone())));
+    // Still blocked, unlike after opening it again
+    assert_eq!(open_files.peek(&path), Ok(Some(change)));
+    assert_eq!(open_files.save(&path, "# Mine\n", write(&path, "# Mine\n")), Err(UbiquityError::file_changed()));
+
+    // Files seen for the first time have nothing to differ from
+    assert_eq!(open_files.peek(&unopened), Ok(None));
+    pause();
+    fs::write(&unopened, "# Theirs\n").unwrap();
+    assert!(open_files.peek(&unopened).unwrap().is_some());
+
+    assert!(open_files.peek(&dir.join("missing.md")).is_err());
+}
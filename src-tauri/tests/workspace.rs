@@ -0,0 +1,145 @@
+mod common;
+
+use std::{fs, path::Path};
+
+use common::TempDir;
+use error::UbiquityError;
+use ubiquity::workspace::{create_file, delete_file, move_to, rename, scan};
+
+fn write(root: &Path, relative: &str, contents: &str) {
+    let path = root.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn scanned(root: &Path) -> Vec<String> {
+    scan(root).unwrap().files.into_iter().map(|file| file.relative).collect()
+}
+
+#[test]
+fn test_scan_finds_markdown() {
+    let dir = TempDir::new();
+    for file in ["b.md", "a.markdown", "notes/c.md", "notes/deeper/d.MD", "image.png", "notes/todo.txt"] {
+        write(dir.path(), file, "");
+    }
+
+    assert_eq!(scanned(dir.path()), vec!["a.markdown", "b.md", "notes/c.md", "notes/deeper/d.MD"]);
+
+    let workspace = scan(dir.path()).unwrap();
+    assert_eq!(workspace.root, dir.path().to_string_lossy());
+    assert_eq!(Path::new(&workspace.files[2].path), dir.join("notes").join("c.md"));
+
+    assert!(scan(&dir.join("missing")).is_err());
+}
+
+#[test]
+fn test_scan_respects_gitignore() {
+    let dir = TempDir::new();
+    write(dir.path(), ".gitignore", "# Comment\n*.draft.md\nbuild/\n/top.md\n!keep.draft.md\ndocs/**/private.md\n[0-9]*.md\n");
+    write(dir.path(), "notes/.gitignore", "local.md\n!/top.md\n");
+    for file in [
+        "readme.md",
+        "ideas.draft.md",
+        "keep.draft.md",
+        "build/output.md",
+        "top.md",
+        "notes/top.md",
+        "notes/local.md",
+        "notes/build/nested.md",
+        "docs/private.md",
+        "docs/a/b/private.md",
+        "docs/public.md",
+        "2023.md",
+        ".git/info.md",
+    ] {
+        write(dir.path(), file, "");
+    }
+    // Only folders are ignored by a rule ending in a slash
+    write(dir.path(), "notes/build.md", "");
+
+    assert_eq!(
+        scanned(dir.path()),
+        vec!["docs/public.md", "keep.draft.md", "notes/build.md", "notes/top.md", "readme.md"],
+    );
+}
+
+#[test]
+fn test_create_file() {
+    let dir = TempDir::new();
+
+    let path = create_file(dir.path(), "notes/ideas").unwrap();
+    assert_eq!(Path::new(&path), dir.join("notes").join("ideas.md"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+    let path = create_file(dir.path(), "plan.markdown").unwrap();
+    assert_eq!(Path::new(&path), dir.join("plan.markdown"));
+
+    // Existing files are never emptied
+    write(dir.path(), "kept.md", "# Kept\n");
+    assert!(create_file(dir.path(), "kept.md").is_err());
+    assert_eq!(fs::read_to_string(dir.join("kept.md")).unwrap(), "# Kept\n");
+}
+
+#[test]
+fn test_rename_and_move() {
+    let dir = TempDir::new();
+    write(dir.path(), "notes/ideas.md", "# Ideas\n");
+    write(dir.path(), "archive/old.md", "");
+
+    assert_eq!(rename(dir.path(), "notes/ideas.md", "plans.md"), Ok(String::from("notes/plans.md")));
+    assert_eq!(move_to(dir.path(), "notes/plans.md", "archive"), Ok(String::from("archive/plans.md")));
+    assert_eq!(fs::read_to_string(dir.join("archive/plans.md")).unwrap(), "# Ideas\n");
+
+    // Folders move with everything in them
+    assert_eq!(rename(dir.path(), "archive", "old"), Ok(String::from("old")));
+    assert_eq!(move_to(dir.path(), "old", "notes"), Ok(String::from("notes/old")));
+    assert_eq!(move_to(dir.path(), "notes/old/plans.md", ""), Ok(String::from("plans.md")));
+    assert_eq!(scanned(dir.path()), vec!["notes/old/old.md", "plans.md"]);
+
+    // Nothing is replaced
+    write(dir.path(), "notes/plans.md", "");
+    assert!(move_to(dir.path(), "plans.md", "notes").is_err());
+    assert!(rename(dir.path(), "plans.md", "old.md").is_ok());
+    assert!(rename(dir.path(), "old.md", "plans.md").is_ok());
+    write(dir.path(), "other.md", "");
+    assert!(rename(dir.path(), "other.md", "plans.md").is_err());
+    assert_eq!(fs::read_to_string(dir.join("plans.md")).unwrap(), "# Ideas\n");
+}
+
+#[test]
+fn test_delete_file() {
+    let dir = TempDir::new();
+    write(dir.path(), "notes/ideas.md", "");
+
+    assert_eq!(delete_file(dir.path(), "notes/ideas.md"), Ok(()));
+    assert!(!dir.join("notes/ideas.md").exists());
+    assert!(delete_file(dir.path(), "notes/ideas.md").is_err());
+    // Folders are never deleted
+    assert_eq!(
+        delete_file(dir.path(), "notes"),
+        Err(UbiquityError::invalid_workspace_path(String::from("notes"))),
+    );
+    assert!(dir.join("notes").is_dir());
+}
+
+#[test]
+fn test_paths_stay_inside_the_workspace() {
+    let dir = TempDir::new();
+    let root = dir.join("workspace");
+    write(&root, "notes/ideas.md", "");
+    write(dir.path(), "outside.md", "");
+
+    let invalid = |path: &str| Some(UbiquityError::invalid_workspace_path(String::from(path)));
+    assert_eq!(delete_file(&root, "../outside.md").err(), invalid("../outside.md"));
+    assert_eq!(create_file(&root, "/tmp/absolute.md").err(), invalid("/tmp/absolute.md"));
+    assert_eq!(create_file(&root, "").err(), invalid(""));
+    assert_eq!(rename(&root, "notes/ideas.md", "../ideas.md").err(), invalid("../ideas.md"));
+    assert_eq!(rename(&root, "notes/ideas.md", "a/b.md").err(), invalid("a/b.md"));
+    assert_eq!(move_to(&root, "notes/ideas.md", "..").err(), invalid(".."));
+    // A folder cannot go inside itself
+    write(&root, "notes/inner/deep.md", "");
+    assert_eq!(move_to(&root, "notes", "notes/inner").err(), invalid("notes"));
+
+    assert!(dir.join("outside.md").exists());
+    assert_eq!(scanned(&root), vec!["notes/ideas.md", "notes/inner/deep.md"]);
+}
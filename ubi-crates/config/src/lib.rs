@@ -1,5 +1,7 @@
 use dirs::{config_dir, data_dir};
 use ::error::UbiquityError;
+use lint::LintOptions;
+use md::{BackupPolicy, RenderOptions};
 use ron::ser::PrettyConfig;
 
 use std::fs;
@@ -15,7 +17,40 @@ pub struct Config {
     pub md_preview_font_size: String,
     pub mobile_ui: bool,
     pub data_path: Option<PathBuf>,
-    pub view: View
+    pub view: View,
+    #[serde(default)]
+    pub render: RenderOptions,
+    #[serde(default)]
+    pub outline: bool,
+    #[serde(default = "scroll_sync_default")]
+    pub scroll_sync: bool,
+    #[serde(default)]
+    pub lint: LintOptions,
+    #[serde(default)]
+    pub format_on_save: bool,
+    #[serde(default = "autosave_default")]
+    pub autosave: bool,
+    /// How long typing has to pause for before an autosave
+    #[serde(default = "autosave_delay_default")]
+    pub autosave_delay_millis: u32,
+    #[serde(default)]
+    pub backups: BackupPolicy,
+    /// The folder open as a workspace, shown as a file tree in the drawer
+    #[serde(default)]
+    pub workspace: Option<PathBuf>
+}
+
+fn scroll_sync_default() -> bool {
+    true
+}
+
+/// Autosave is opt in, so files are only written when the user saves them until it is turned on
+fn autosave_default() -> bool {
+    false
+}
+
+fn autosave_delay_default() -> u32 {
+    1000
 }
 
 impl Default for Config {
@@ -27,6 +62,15 @@ impl Default for Config {
             md_preview_font_size: String::from("prose-base"),
             mobile_ui: false,
             view: View::Dual,
+            render: RenderOptions::default(),
+            outline: false,
+            scroll_sync: true,
+            lint: LintOptions::default(),
+            format_on_save: false,
+            autosave: autosave_default(),
+            autosave_delay_millis: autosave_delay_default(),
+            backups: BackupPolicy::Off,
+            workspace: None,
         }
     }
 }
@@ -49,6 +93,15 @@ impl Config {
             md_preview_font_size: String::from("prose-base"),
             mobile_ui: true,
             view: View::Input,
+            render: RenderOptions::default(),
+            outline: false,
+            scroll_sync: true,
+            lint: LintOptions::default(),
+            format_on_save: false,
+            autosave: autosave_default(),
+            autosave_delay_millis: autosave_delay_default(),
+            backups: BackupPolicy::Off,
+            workspace: None,
         }
     }
 
@@ -84,6 +137,14 @@ impl Config {
     pub fn current(&self) -> &Self {
         &self
     }
+
+    /// Where journals of unsaved edits are kept, so they can be recovered after a crash
+    pub fn recovery_folder(&self) -> Result<PathBuf, UbiquityError> {
+        match &self.data_path {
+            Some(path) => Ok(path.join("ubiquity").join("recovery")),
+            None => Err(UbiquityError::no_data_folder()),
+        }
+    }
 }
 
 pub fn read_config_file() -> Result<Config, UbiquityError> {
@@ -115,7 +176,7 @@ pub fn get_config_folder() -> Result<PathBuf, UbiquityError> {
     match config_dir() {
         Some(mut path) => {
             path.push("ubiquity/");
-            fs::create_dir(&path)?;
+            fs::create_dir_all(&path)?;
             Ok(path)
         },
         None => Err(UbiquityError::no_config_folder()),
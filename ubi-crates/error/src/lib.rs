@@ -101,6 +101,13 @@ impl UbiquityError {
         Self { title, human_description, verbose_description }
     }
 
+    pub fn no_data_folder() -> Self {
+        let title = String::from("Data Folder Error");
+        let human_description = String::from("Ubiquity could not find your operating system's default data folder.");
+        let verbose_description = None;
+        Self { title, human_description, verbose_description }
+    }
+
     pub fn no_save_path_selected() -> Self {
         let title = String::from("Save Error");
         let human_description = String::from("There was no save path selected.");
@@ -108,6 +115,13 @@ impl UbiquityError {
         Self { title, human_description, verbose_description }
     }
 
+    pub fn file_changed() -> Self {
+        let title = String::from("Save Error");
+        let human_description = String::from("The file was changed by another program since Ubiquity opened it. Reload it or keep your version before saving.");
+        let verbose_description = None;
+        Self { title, human_description, verbose_description }
+    }
+
     pub fn no_file_selected() -> Self {
         let title = String::from("Open Error");
         let human_description = String::from("There was no file selected for opening.");
@@ -115,10 +129,45 @@ impl UbiquityError {
         Self { title, human_description, verbose_description }
     }
 
+    pub fn no_folder_selected() -> Self {
+        let title = String::from("Open Error");
+        let human_description = String::from("There was no folder selected for opening.");
+        let verbose_description = None;
+        Self { title, human_description, verbose_description }
+    }
+
+    pub fn invalid_workspace_path(path: String) -> Self {
+        let title = String::from("Workspace Error");
+        let human_description = String::from("That name or location is not inside the open folder, or cannot be used there.");
+        let verbose_description = Some(path);
+        Self { title, human_description, verbose_description }
+    }
+
+    pub fn no_workspace() -> Self {
+        let title = String::from("Workspace Error");
+        let human_description = String::from("There is no folder open as a workspace.");
+        let verbose_description = None;
+        Self { title, human_description, verbose_description }
+    }
+
     pub fn mdtg(err: String) -> Self {
         let title = String::from("Markdown Table Error");
         let human_description = String::from("There was an error generating your markdown table.");
         let verbose_description = Some(err);
         Self { title, human_description, verbose_description }
     }
-}
\ No newline at end of file
+
+    pub fn markdown(err: String) -> Self {
+        let title = String::from("Markdown Error");
+        let human_description = String::from("There was an error processing your markdown.");
+        let verbose_description = Some(err);
+        Self { title, human_description, verbose_description }
+    }
+
+    pub fn front_matter(err: String) -> Self {
+        let title = String::from("Front Matter Error");
+        let human_description = String::from("The metadata block at the top of your markdown could not be read.");
+        let verbose_description = Some(err);
+        Self { title, human_description, verbose_description }
+    }
+}
@@ -0,0 +1,199 @@
+use std::ops::{Range, RangeInclusive};
+
+use markdown::mdast::Node;
+use md::RenderOptions;
+
+mod lines;
+mod rule;
+mod tree;
+
+pub use rule::{LintOptions, Rule};
+
+/// Fixes can uncover more problems, such as a heading that is only out of step
+/// once the one before it has been fixed, so fixing repeats up to this many times.
+const MAX_FIX_PASSES: usize = 10;
+
+/// A replacement for part of the text that resolves a lint
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fix {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// A problem found in the markdown
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub message: String,
+    /// The byte range of the problem
+    pub range: Range<usize>,
+    /// The line the problem starts on, counting from 1
+    pub line: usize,
+    /// The column the problem starts at in characters, counting from 1
+    pub column: usize,
+    pub fix: Option<Fix>,
+}
+
+impl Lint {
+    pub(crate) fn new(text: &str, rule: Rule, range: Range<usize>, message: String, fix: Option<Fix>) -> Self {
+        let before = &text[..range.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        let column = before[line_start..].chars().count() + 1;
+
+        Self { rule, message, range, line, column, fix }
+    }
+}
+
+/// Check markdown against every enabled rule, returning the problems in the order they appear.
+///
+/// The markdown is parsed with the render options, so lints agree with the preview.
+pub fn lint(text: &str, options: &LintOptions, render_options: &RenderOptions) -> Result<Vec<Lint>, String> {
+    let tree = markdown::to_mdast(text, &render_options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+
+    let mut lints = Vec::new();
+    tree::check(&tree, text, &mut lints);
+    lines::check(text, &verbatim_lines(&tree), &code_spans(&tree), &mut lints);
+
+    lints.retain(|lint| options.is_enabled(lint.rule));
+    lints.sort_by_key(|lint| (lint.range.start, lint.rule));
+
+    Ok(lints)
+}
+
+/// Apply the fixes of the lints to the text. Fixes that overlap an earlier
+/// fix are left out, as the text they were made for has changed.
+pub fn apply_fixes(text: &str, lints: &[Lint]) -> String {
+    let mut fixes: Vec<&Fix> = lints.iter().filter_map(|lint| lint.fix.as_ref()).collect();
+    fixes.sort_by_key(|fix| (fix.range.start, fix.range.end));
+
+    let mut output = String::with_capacity(text.len());
+    let mut position = 0;
+
+    for fix in fixes {
+        if fix.range.start < position {
+            continue;
+        }
+        output.push_str(&text[position..fix.range.start]);
+        output.push_str(&fix.replacement);
+        position = fix.range.end;
+    }
+
+    output.push_str(&text[position..]);
+    output
+}
+
+/// Fix every problem that has a fix, returning the fixed text
+pub fn fix_all(text: &str, options: &LintOptions, render_options: &RenderOptions) -> Result<String, String> {
+    let mut text = text.to_string();
+
+    for _ in 0..MAX_FIX_PASSES {
+        let lints = lint(&text, options, render_options)?;
+        if lints.iter().all(|lint| lint.fix.is_none()) {
+            break;
+        }
+        text = apply_fixes(&text, &lints);
+    }
+
+    Ok(text)
+}
+
+/// The lines of code blocks, math blocks and front matter, where whitespace is content
+fn verbatim_lines(tree: &Node) -> Vec<RangeInclusive<usize>> {
+    let mut lines = Vec::new();
+    collect_verbatim_lines(tree, &mut lines);
+    lines
+}
+
+fn collect_verbatim_lines(node: &Node, lines: &mut Vec<RangeInclusive<usize>>) {
+    match node {
+        Node::Code(_) | Node::Math(_) | Node::Yaml(_) | Node::Toml(_) => {
+            if let Some(position) = node.position() {
+                lines.push(position.start.line..=position.end.line);
+            }
+        }
+        _ => {
+            for child in node.children().into_iter().flatten() {
+                collect_verbatim_lines(child, lines);
+            }
+        }
+    }
+}
+
+/// The byte ranges of inline code, where tabs are content
+fn code_spans(tree: &Node) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    collect_code_spans(tree, &mut spans);
+    spans
+}
+
+fn collect_code_spans(node: &Node, spans: &mut Vec<Range<usize>>) {
+    match node {
+        Node::InlineCode(_) => {
+            if let Some(position) = node.position() {
+                spans.push(position.start.offset..position.end.offset);
+            }
+        }
+        _ => {
+            for child in node.children().into_iter().flatten() {
+                collect_code_spans(child, spans);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_positions() {
+        let lints = lint("# A\n\nsome text \n", &LintOptions::default(), &RenderOptions::default()).unwrap();
+
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].rule, Rule::TrailingSpaces);
+        assert_eq!((lints[0].line, lints[0].column), (3, 10));
+        assert_eq!(lints[0].range, 14..15);
+    }
+
+    #[test]
+    fn test_disabled_rules() {
+        let text = "# A\n\n### B \n";
+        let mut options = LintOptions::default();
+
+        let rules = |options: &LintOptions| lint(text, options, &RenderOptions::default()).unwrap().into_iter().map(|lint| lint.rule).collect::<Vec<_>>();
+        assert_eq!(rules(&options), vec![Rule::HeadingIncrement, Rule::TrailingSpaces]);
+
+        options.toggle(Rule::HeadingIncrement);
+        assert_eq!(rules(&options), vec![Rule::TrailingSpaces]);
+        assert!(!options.is_enabled(Rule::HeadingIncrement));
+
+        options.toggle(Rule::HeadingIncrement);
+        assert_eq!(options, LintOptions::default());
+    }
+
+    #[test]
+    fn test_fix_all() {
+        let text = "# A\n\n#### B  \n\n\n\n* one\n- two\n\nSee https://example.com\n\n##### C";
+        let fixed = fix_all(text, &LintOptions::default(), &RenderOptions::default()).unwrap();
+
+        assert_eq!(fixed, "# A\n\n## B\n\n* one\n* two\n\nSee <https://example.com>\n\n### C\n");
+        assert_eq!(lint(&fixed, &LintOptions::default(), &RenderOptions::default()).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_overlapping_fixes() {
+        let text = "abc";
+        let fix = |range: Range<usize>, replacement: &str| Lint {
+            rule: Rule::TrailingSpaces,
+            message: String::new(),
+            range: range.clone(),
+            line: 1,
+            column: 1,
+            fix: Some(Fix { range, replacement: replacement.to_string() }),
+        };
+
+        assert_eq!(apply_fixes(text, &[fix(2..3, "C"), fix(0..2, "AB"), fix(1..2, "x")]), "ABC");
+        assert_eq!(apply_fixes(text, &[fix(3..3, "\n")]), "abc\n");
+    }
+}
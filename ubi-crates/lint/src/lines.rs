@@ -0,0 +1,133 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{Fix, Lint, Rule};
+
+/// The spaces a tab is replaced with
+const TAB: &str = "    ";
+
+/// Run the rules that look at lines of text rather than the syntax tree.
+///
+/// `verbatim` holds the lines of code blocks and the like, where whitespace is
+/// part of the content and is left alone, and `code_spans` the byte ranges of
+/// inline code, whose tabs are left alone too.
+pub(crate) fn check(text: &str, verbatim: &[RangeInclusive<usize>], code_spans: &[Range<usize>], lints: &mut Vec<Lint>) {
+    let lines: Vec<(usize, &str)> = text
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .collect();
+
+    let is_blank = |line: &str| line.trim().is_empty();
+    let mut previous_blank = false;
+
+    for (i, (start, line)) in lines.iter().enumerate() {
+        let (start, line) = (*start, *line);
+        let content = line.trim_end_matches('\n').trim_end_matches('\r');
+        let number = i + 1;
+
+        if verbatim.iter().any(|lines| lines.contains(&number)) {
+            previous_blank = false;
+            continue;
+        }
+
+        let blank = is_blank(content);
+
+        if blank && previous_blank {
+            let fix = Fix { range: start..start + line.len(), replacement: String::new() };
+            let message = String::from("Multiple consecutive blank lines");
+            lints.push(Lint::new(text, Rule::MultipleBlankLines, start..start + line.len(), message, Some(fix)));
+        }
+        previous_blank = blank;
+
+        let trimmed = content.trim_end_matches([' ', '\t']);
+        let trailing = &content[trimmed.len()..];
+        // Two spaces before another line of the paragraph is a line break
+        let line_break = trailing == "  "
+            && !blank
+            && lines.get(i + 1).is_some_and(|(_, next)| !is_blank(next));
+
+        if !trailing.is_empty() && !blank && !line_break {
+            let range = start + trimmed.len()..start + content.len();
+            let fix = Fix { range: range.clone(), replacement: String::new() };
+            let message = format!("Trailing whitespace, {} character{}", trailing.len(), if trailing.len() == 1 { "" } else { "s" });
+            lints.push(Lint::new(text, Rule::TrailingSpaces, range, message, Some(fix)));
+        }
+
+        let in_code = |i: usize| code_spans.iter().any(|span| span.contains(&(start + i)));
+        let mut tabs = trimmed.match_indices('\t').map(|(i, _)| i).filter(|i| !in_code(*i));
+        if let Some(first) = tabs.next() {
+            let last = tabs.next_back().unwrap_or(first);
+            let replacement = trimmed[first..=last]
+                .char_indices()
+                .map(|(i, c)| match c == '\t' && !in_code(first + i) {
+                    true => TAB,
+                    false => &trimmed[first + i..first + i + c.len_utf8()],
+                })
+                .collect();
+
+            let range = start + first..start + last + 1;
+            let fix = Fix { range: range.clone(), replacement };
+            lints.push(Lint::new(text, Rule::HardTabs, range, String::from("Hard tab"), Some(fix)));
+        }
+    }
+
+    if !text.is_empty() && !text.ends_with('\n') {
+        let fix = Fix { range: text.len()..text.len(), replacement: String::from("\n") };
+        let message = String::from("File should end with a newline");
+        lints.push(Lint::new(text, Rule::TrailingNewline, text.len()..text.len(), message, Some(fix)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use md::RenderOptions;
+
+    use crate::{apply_fixes, lint, LintOptions, Rule};
+
+    fn lints(text: &str) -> Vec<(Rule, usize)> {
+        lint(text, &LintOptions::default(), &RenderOptions::default()).unwrap().into_iter().map(|lint| (lint.rule, lint.line)).collect()
+    }
+
+    fn fixed(text: &str) -> String {
+        apply_fixes(text, &lint(text, &LintOptions::default(), &RenderOptions::default()).unwrap())
+    }
+
+    #[test]
+    fn test_trailing_spaces() {
+        assert_eq!(lints("a \nb\t\n"), vec![(Rule::TrailingSpaces, 1), (Rule::TrailingSpaces, 2)]);
+        assert_eq!(fixed("a \r\nb   \n"), "a\r\nb\n");
+
+        // A line break, but not at the end of a paragraph
+        assert_eq!(lints("a  \nb  \n\nc\n"), vec![(Rule::TrailingSpaces, 2)]);
+        assert_eq!(lints("```text\ncode \n```\n"), Vec::new());
+    }
+
+    #[test]
+    fn test_hard_tabs() {
+        assert_eq!(lints("a\tb\tc\n"), vec![(Rule::HardTabs, 1)]);
+        assert_eq!(fixed("a\tb\tc\n"), "a    b    c\n");
+        assert_eq!(lints("```make\nall:\n\tcc main.c\n```\n"), Vec::new());
+
+        // Tabs in inline code are content
+        assert_eq!(lints("Split on `a\tb`\n"), Vec::new());
+        assert_eq!(fixed("a\t`b\tc`\td\n"), "a    `b\tc`    d\n");
+        assert_eq!(fixed("`b\tc`\td\n"), "`b\tc`    d\n");
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        assert_eq!(lints("a\n\n\n\nb\n"), vec![(Rule::MultipleBlankLines, 3), (Rule::MultipleBlankLines, 4)]);
+        assert_eq!(fixed("a\n\n\n\nb\n"), "a\n\nb\n");
+        assert_eq!(lints("```text\na\n\n\nb\n```\n"), Vec::new());
+    }
+
+    #[test]
+    fn test_trailing_newline() {
+        assert_eq!(lints("a"), vec![(Rule::TrailingNewline, 1)]);
+        assert_eq!(fixed("a"), "a\n");
+        assert_eq!(lints(""), Vec::new());
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+/// A check the linter can run, each matching a markdownlint rule whose code and name it shares
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    HeadingIncrement,
+    ListMarker,
+    TrailingSpaces,
+    HardTabs,
+    MultipleBlankLines,
+    DuplicateHeading,
+    BareUrl,
+    FencedCodeLanguage,
+    ImageAltText,
+    TrailingNewline,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 10] = [
+        Rule::HeadingIncrement,
+        Rule::ListMarker,
+        Rule::TrailingSpaces,
+        Rule::HardTabs,
+        Rule::MultipleBlankLines,
+        Rule::DuplicateHeading,
+        Rule::BareUrl,
+        Rule::FencedCodeLanguage,
+        Rule::ImageAltText,
+        Rule::TrailingNewline,
+    ];
+
+    /// The markdownlint code of the rule
+    pub fn code(&self) -> &'static str {
+        match self {
+            Rule::HeadingIncrement => "MD001",
+            Rule::ListMarker => "MD004",
+            Rule::TrailingSpaces => "MD009",
+            Rule::HardTabs => "MD010",
+            Rule::MultipleBlankLines => "MD012",
+            Rule::DuplicateHeading => "MD024",
+            Rule::BareUrl => "MD034",
+            Rule::FencedCodeLanguage => "MD040",
+            Rule::ImageAltText => "MD045",
+            Rule::TrailingNewline => "MD047",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::HeadingIncrement => "heading-increment",
+            Rule::ListMarker => "ul-style",
+            Rule::TrailingSpaces => "no-trailing-spaces",
+            Rule::HardTabs => "no-hard-tabs",
+            Rule::MultipleBlankLines => "no-multiple-blanks",
+            Rule::DuplicateHeading => "no-duplicate-heading",
+            Rule::BareUrl => "no-bare-urls",
+            Rule::FencedCodeLanguage => "fenced-code-language",
+            Rule::ImageAltText => "no-alt-text",
+            Rule::TrailingNewline => "single-trailing-newline",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::HeadingIncrement => "Heading levels should only increase by one at a time",
+            Rule::ListMarker => "Bulleted lists should use the same marker throughout",
+            Rule::TrailingSpaces => "Lines should not end in spaces, other than a two space line break",
+            Rule::HardTabs => "Use spaces rather than tabs outside of code blocks",
+            Rule::MultipleBlankLines => "Blocks should be separated by a single blank line",
+            Rule::DuplicateHeading => "Headings should not repeat the text of an earlier heading",
+            Rule::BareUrl => "URLs should be wrapped in angle brackets or written as links",
+            Rule::FencedCodeLanguage => "Fenced code blocks should name their language",
+            Rule::ImageAltText => "Images should have alternate text",
+            Rule::TrailingNewline => "Files should end with a single newline",
+        }
+    }
+}
+
+/// Which rules the linter runs
+#[derive(Clone, Deserialize, Serialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct LintOptions {
+    /// Rules that are turned off. Every other rule runs, so rules added later are on by default.
+    pub disabled: Vec<Rule>,
+}
+
+impl LintOptions {
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+
+    /// Turn a rule off if it is on, or on if it is off
+    pub fn toggle(&mut self, rule: Rule) {
+        match self.disabled.iter().position(|disabled| *disabled == rule) {
+            Some(i) => {
+                self.disabled.remove(i);
+            }
+            None => {
+                self.disabled.push(rule);
+                self.disabled.sort();
+            }
+        }
+    }
+}
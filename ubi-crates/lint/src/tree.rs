@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use markdown::mdast::Node;
+use md::plain_text;
+
+use crate::{Fix, Lint, Rule};
+
+/// What earlier parts of the document decide about later ones
+#[derive(Default)]
+struct State {
+    previous_level: Option<u8>,
+    headings: HashSet<String>,
+    /// The bullet of the first bulleted list, which every other list should use
+    bullet: Option<char>,
+}
+
+/// Run the rules that look at the syntax tree
+pub(crate) fn check(tree: &Node, text: &str, lints: &mut Vec<Lint>) {
+    visit(tree, text, &mut State::default(), lints);
+}
+
+fn visit(node: &Node, text: &str, state: &mut State, lints: &mut Vec<Lint>) {
+    let range = match node.position() {
+        Some(position) => position.start.offset..position.end.offset,
+        None => 0..0,
+    };
+
+    match node {
+        Node::Heading(heading) => {
+            let skipped = state.previous_level.filter(|previous| heading.depth > previous + 1);
+            if let Some(previous) = skipped {
+                let hashes = text[range.start..].bytes().take_while(|byte| *byte == b'#').count();
+                // Only ATX headings can be fixed by changing the number of `#`s
+                let fix = (hashes == heading.depth as usize).then(|| Fix {
+                    range: range.start..range.start + hashes,
+                    replacement: "#".repeat(previous as usize + 1),
+                });
+                let message = format!("Expected a level {} heading, found level {}", previous + 1, heading.depth);
+                lints.push(Lint::new(text, Rule::HeadingIncrement, range.clone(), message, fix));
+            }
+            state.previous_level = Some(heading.depth);
+
+            let heading_text = plain_text(node);
+            if !state.headings.insert(heading_text.clone()) {
+                let message = format!("Duplicate heading \"{}\"", heading_text);
+                lints.push(Lint::new(text, Rule::DuplicateHeading, range.clone(), message, None));
+            }
+        }
+        Node::List(list) if !list.ordered => {
+            for item in &list.children {
+                if let Some(lint) = check_bullet(item, text, state) {
+                    lints.push(lint);
+                }
+            }
+        }
+        Node::Link(link) => {
+            let source = &text[range.clone()];
+            if !source.starts_with('[') && !source.starts_with('<') {
+                // Only URLs with a scheme, and email addresses, work in angle brackets
+                let fix = (source.contains("://") || link.url == format!("mailto:{}", source)).then(|| Fix {
+                    range: range.clone(),
+                    replacement: format!("<{}>", source),
+                });
+                let message = format!("Bare URL \"{}\"", source);
+                lints.push(Lint::new(text, Rule::BareUrl, range.clone(), message, fix));
+            }
+        }
+        Node::Code(code) if code.lang.is_none() => {
+            let fence = text[range.clone()].trim_start();
+            if fence.starts_with("```") || fence.starts_with("~~~") {
+                let message = String::from("Fenced code block has no language");
+                lints.push(Lint::new(text, Rule::FencedCodeLanguage, range.clone(), message, None));
+            }
+        }
+        Node::Image(image) if image.alt.trim().is_empty() => {
+            lints.push(Lint::new(text, Rule::ImageAltText, range.clone(), String::from("Image has no alternate text"), None));
+        }
+        Node::ImageReference(image) if image.alt.trim().is_empty() => {
+            lints.push(Lint::new(text, Rule::ImageAltText, range.clone(), String::from("Image has no alternate text"), None));
+        }
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        visit(child, text, state, lints);
+    }
+}
+
+/// Check that a list item's bullet matches the first bullet of the document
+fn check_bullet(item: &Node, text: &str, state: &mut State) -> Option<Lint> {
+    let start = item.position()?.start.offset;
+    let offset = start + text[start..].len() - text[start..].trim_start().len();
+    let bullet = text[offset..].chars().next().filter(|c| matches!(c, '-' | '*' | '+'))?;
+
+    let expected = *state.bullet.get_or_insert(bullet);
+    if bullet == expected {
+        return None;
+    }
+
+    let range: Range<usize> = offset..offset + 1;
+    let fix = Fix { range: range.clone(), replacement: expected.to_string() };
+    let message = format!("Expected the bullet \"{}\", found \"{}\"", expected, bullet);
+    Some(Lint::new(text, Rule::ListMarker, range, message, Some(fix)))
+}
+
+#[cfg(test)]
+mod tests {
+    use md::RenderOptions;
+
+    use crate::{apply_fixes, lint, LintOptions, Rule};
+
+    fn lints(text: &str) -> Vec<(Rule, usize, bool)> {
+        lint(text, &LintOptions::default(), &RenderOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|lint| (lint.rule, lint.line, lint.fix.is_some()))
+            .collect()
+    }
+
+    fn fixed(text: &str) -> String {
+        apply_fixes(text, &lint(text, &LintOptions::default(), &RenderOptions::default()).unwrap())
+    }
+
+    #[test]
+    fn test_heading_increment() {
+        assert_eq!(lints("# A\n\n### B\n\n#### C\n\n# D\n\n## E\n"), vec![(Rule::HeadingIncrement, 3, true)]);
+        assert_eq!(fixed("# A\n\n### B\n"), "# A\n\n## B\n");
+        assert_eq!(lints("# A\n\nB\n---\n\nC\n===\n"), Vec::new());
+        assert_eq!(lints("# A\n\nB\n-\n\n- #### C\n"), vec![(Rule::HeadingIncrement, 6, true)]);
+        assert_eq!(fixed("# A\n\n- #### C\n"), "# A\n\n- ## C\n");
+    }
+
+    #[test]
+    fn test_duplicate_heading() {
+        assert_eq!(lints("# A *b*\n\n## A b\n\n## C\n"), vec![(Rule::DuplicateHeading, 3, false)]);
+    }
+
+    #[test]
+    fn test_list_marker() {
+        assert_eq!(lints("- a\n- b\n\n* c\n\n  + d\n"), vec![(Rule::ListMarker, 4, true), (Rule::ListMarker, 6, true)]);
+        assert_eq!(fixed("+ a\n\n- b\n  * c\n"), "+ a\n\n+ b\n  + c\n");
+        assert_eq!(lints("1. a\n2. b\n"), Vec::new());
+    }
+
+    #[test]
+    fn test_bare_url() {
+        assert_eq!(
+            lints("<https://a.com> [b](https://b.com) https://c.com www.d.com e@f.com\n"),
+            vec![(Rule::BareUrl, 1, true), (Rule::BareUrl, 1, false), (Rule::BareUrl, 1, true)]
+        );
+        assert_eq!(fixed("See https://c.com or e@f.com\n"), "See <https://c.com> or <e@f.com>\n");
+    }
+
+    #[test]
+    fn test_fenced_code_language() {
+        assert_eq!(lints("```\ncode\n```\n\n```rust\ncode\n```\n\n    indented\n"), vec![(Rule::FencedCodeLanguage, 1, false)]);
+    }
+
+    #[test]
+    fn test_image_alt_text() {
+        assert_eq!(
+            lints("![](a.png) ![alt](b.png) ![ ][c]\n\n[c]: c.png\n"),
+            vec![(Rule::ImageAltText, 1, false), (Rule::ImageAltText, 1, false)]
+        );
+    }
+}
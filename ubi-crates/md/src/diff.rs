@@ -0,0 +1,194 @@
+/// How a line changed from one text to another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Removed,
+    Added,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffKind,
+    pub text: String,
+}
+
+/// One step from the lines of one text to those of another, by line index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Edit {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// The lines of `new` compared with those of `old`, as few removals and
+/// additions as turn one into the other. Removals come before the
+/// additions that replace them.
+pub fn diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let (old, new): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+
+    edits(&old, &new)
+        .into_iter()
+        .map(|edit| {
+            let (kind, text) = match edit {
+                Edit::Same(i, _) => (DiffKind::Same, old[i]),
+                Edit::Removed(i) => (DiffKind::Removed, old[i]),
+                Edit::Added(j) => (DiffKind::Added, new[j]),
+            };
+            DiffLine { kind, text: text.to_string() }
+        })
+        .collect()
+}
+
+/// The shortest edit script from `a` to `b`, found with Myers' algorithm.
+///
+/// Lines shared at the start and end are matched up first, so the work
+/// grows with the size of the changes rather than of the texts.
+pub(crate) fn edits(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Same(i, i)).collect();
+    let middle = myers(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    edits.extend(middle.into_iter().map(|edit| match edit {
+        Edit::Same(i, j) => Edit::Same(i + prefix, j + prefix),
+        Edit::Removed(i) => Edit::Removed(i + prefix),
+        Edit::Added(j) => Edit::Added(j + prefix),
+    }));
+    edits.extend((0..suffix).map(|i| Edit::Same(a.len() - suffix + i, b.len() - suffix + i)));
+    edits
+}
+
+fn myers(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    // The furthest x reached on each diagonal k = x - y, offset so k can be negative
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // For each round d, diagonals -d - 1..=d + 1 as they were before it
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| v[(offset + k) as usize];
+            let mut x = match k == -d || (k != d && at(k - 1) < at(k + 1)) {
+                true => at(k + 1),
+                false => at(k - 1) + 1,
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(offset + k) as usize] = x;
+
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = match k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Same(x as usize, y as usize));
+        }
+        if d > 0 {
+            edits.push(match x == prev_x {
+                true => Edit::Added(prev_y as usize),
+                false => Edit::Removed(prev_x as usize),
+            });
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(old: &str, new: &str) -> String {
+        diff(old, new)
+            .into_iter()
+            .map(|line| {
+                let sign = match line.kind {
+                    DiffKind::Same => ' ',
+                    DiffKind::Removed => '-',
+                    DiffKind::Added => '+',
+                };
+                format!("{}{}\n", sign, line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(summary("a\nb\nc\n", "a\nb\nc\n"), " a\n b\n c\n");
+        assert_eq!(summary("a\nb\nc\n", "a\nc\nd\n"), " a\n-b\n c\n+d\n");
+        assert_eq!(summary("a\nb\n", "a\nx\n"), " a\n-b\n+x\n");
+        assert_eq!(summary("", "a\nb\n"), "+a\n+b\n");
+        assert_eq!(summary("a\nb\n", ""), "-a\n-b\n");
+        assert_eq!(summary("", ""), "");
+    }
+
+    #[test]
+    fn test_diff_is_shortest() {
+        // Compared against the longest common subsequence, for a spread of small texts
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as usize
+        };
+
+        for _ in 0..300 {
+            let a: Vec<String> = (0..next() % 12).map(|_| (next() % 4).to_string()).collect();
+            let b: Vec<String> = (0..next() % 12).map(|_| (next() % 4).to_string()).collect();
+            let (a, b): (Vec<&str>, Vec<&str>) = (a.iter().map(String::as_str).collect(), b.iter().map(String::as_str).collect());
+
+            let edits = edits(&a, &b);
+            let same = edits.iter().filter(|edit| matches!(edit, Edit::Same(..))).count();
+            assert_eq!(same, lcs(&a, &b), "{:?} {:?}", a, b);
+
+            // Replaying the edits gives back both texts
+            let old: Vec<&str> = edits.iter().filter_map(|edit| match edit {
+                Edit::Same(i, _) | Edit::Removed(i) => Some(a[*i]),
+                Edit::Added(_) => None,
+            }).collect();
+            let new: Vec<&str> = edits.iter().filter_map(|edit| match edit {
+                Edit::Same(_, j) | Edit::Added(j) => Some(b[*j]),
+                Edit::Removed(_) => None,
+            }).collect();
+            assert_eq!((old, new), (a.clone(), b.clone()));
+        }
+    }
+
+    fn lcs(a: &[&str], b: &[&str]) -> usize {
+        let mut lengths = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                lengths[i + 1][j + 1] = match a[i] == b[j] {
+                    true => lengths[i][j] + 1,
+                    false => lengths[i][j + 1].max(lengths[i + 1][j]),
+                };
+            }
+        }
+        lengths[a.len()][b.len()]
+    }
+}
@@ -0,0 +1,539 @@
+use std::collections::HashSet;
+use std::ops::{Range, RangeInclusive};
+
+use markdown::mdast::{Code, List, Node};
+
+use crate::render::RenderOptions;
+
+/// A replacement for part of the source
+struct Edit {
+    range: Range<usize>,
+    replacement: String,
+}
+
+/// Pretty-print markdown into one consistent style without changing what it renders to.
+///
+/// Headings become ATX headings, emphasis uses `*` and strong `**`, bullets
+/// are `-`, ordered lists are numbered in order (or all with the same number
+/// when the source did that), `~~~` fences become backticks, top level tables
+/// are aligned, and blocks are separated by exactly one blank line.
+///
+/// Rather than printing the syntax tree back out, only the markers being
+/// normalised are rewritten, so everything else keeps its source text exactly.
+pub fn format(text: &str, options: &RenderOptions) -> Result<String, String> {
+    let parse = options.to_markdown_options().parse;
+
+    let tree = markdown::to_mdast(text, &parse).map_err(|err| err.to_string())?;
+    let newline = line_ending(text);
+    let mut edits = Vec::new();
+    collect_edits(&tree, text, &mut edits);
+    let text = apply_edits(text, edits);
+
+    let tree = markdown::to_mdast(&text, &parse).map_err(|err| err.to_string())?;
+    Ok(normalise_lines(&text, &tree, newline))
+}
+
+/// The line ending of the first line, which the formatted document uses throughout
+fn line_ending(text: &str) -> &'static str {
+    match text.find('\n') {
+        Some(end) if text[..end].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+fn collect_edits(node: &Node, text: &str, edits: &mut Vec<Edit>) {
+    let range = match node.position() {
+        Some(position) => position.start.offset..position.end.offset,
+        None => 0..text.len(),
+    };
+
+    match node {
+        Node::Heading(heading) => heading_edits(&heading.children, heading.depth, range, text, edits),
+        Node::Emphasis(_) => marker_edits(range, text, "_", "*", edits),
+        Node::Strong(_) => marker_edits(range, text, "__", "**", edits),
+        Node::Code(code) => fence_edits(code, range, text, edits),
+        Node::Table(_) if node.position().is_some_and(|position| position.start.column == 1) => {
+            // Tables in block quotes and lists have prefixes on each row, so only top level tables are aligned
+            if let Ok(table) = mdtg::format_table(&text[range.clone()]) {
+                let table = table.trim_end_matches('\n');
+                if table != &text[range.clone()] {
+                    edits.push(Edit { range, replacement: table.to_string() });
+                }
+                return;
+            }
+        }
+        _ => {}
+    }
+
+    // Lists next to each other are only kept apart by using different bullets
+    let mut previous_bullet = None;
+    for child in node.children().into_iter().flatten() {
+        previous_bullet = match child {
+            Node::List(list) if !list.ordered => {
+                let bullet = if previous_bullet == Some('-') { '*' } else { '-' };
+                list_edits(list, text, bullet, edits);
+                Some(bullet)
+            }
+            Node::List(list) => {
+                list_edits(list, text, '-', edits);
+                None
+            }
+            _ => None,
+        };
+        collect_edits(child, text, edits);
+    }
+}
+
+/// Turn a heading into a single line ATX heading, without closing `#`s
+fn heading_edits(children: &[Node], depth: u8, range: Range<usize>, text: &str, edits: &mut Vec<Edit>) {
+    let marker = "#".repeat(depth as usize);
+    let content = children
+        .first()
+        .and_then(Node::position)
+        .zip(children.last().and_then(Node::position))
+        .map(|(first, last)| first.start.offset..last.end.offset);
+
+    match content {
+        // An ATX heading has to fit on one line, and `#`s at its end would close it
+        Some(content) if text[content.clone()].contains('\n') || text[..content.end].ends_with('#') => {}
+        Some(content) => {
+            edits.push(Edit { range: range.start..content.start, replacement: format!("{} ", marker) });
+            edits.push(Edit { range: content.end..range.end, replacement: String::new() });
+        }
+        None => edits.push(Edit { range, replacement: marker }),
+    }
+}
+
+/// Swap the markers at either end of emphasis or strong
+fn marker_edits(range: Range<usize>, text: &str, from: &str, to: &str, edits: &mut Vec<Edit>) {
+    let source = &text[range.clone()];
+    if source.len() < from.len() * 2 || !source.starts_with(from) || !source.ends_with(from) {
+        return;
+    }
+
+    // A `*` next to the new marker would merge with it, changing how the markers pair up
+    let inner = &source[from.len()..source.len() - from.len()];
+    let touches_star = text[..range.start].ends_with('*')
+        || text[range.end..].starts_with('*')
+        || inner.starts_with('*')
+        || inner.ends_with('*');
+    if touches_star {
+        return;
+    }
+
+    edits.push(Edit { range: range.start..range.start + from.len(), replacement: to.to_string() });
+    edits.push(Edit { range: range.end - from.len()..range.end, replacement: to.to_string() });
+}
+
+/// Use backticks for a `~~~` fenced code block
+fn fence_edits(code: &Code, range: Range<usize>, text: &str, edits: &mut Vec<Edit>) {
+    let source = &text[range.clone()];
+    let opening = source.bytes().take_while(|byte| *byte == b'~').count();
+
+    // Backtick fences cannot have backticks in their info string
+    let info_has_backtick = [&code.lang, &code.meta].into_iter().flatten().any(|info| info.contains('`'));
+    if opening < 3 || info_has_backtick {
+        return;
+    }
+
+    // The fence has to be longer than any run of backticks that could close it early
+    let longest_run = code
+        .value
+        .lines()
+        .map(|line| line.trim_start().bytes().take_while(|byte| *byte == b'`').count())
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+
+    edits.push(Edit { range: range.start..range.start + opening, replacement: fence.clone() });
+
+    // An unclosed block runs to the end of its container, which works the same with backticks
+    if let Some(line_start) = source.rfind('\n').map(|i| range.start + i + 1) {
+        let line = &text[line_start..range.end];
+        let tildes = line.find('~').map(|i| (i, line[i..].bytes().take_while(|byte| *byte == b'~').count()));
+
+        if let Some((i, count)) = tildes {
+            let is_fence = count >= opening
+                && line[..i].chars().all(|c| c == ' ' || c == '>')
+                && line[i + count..].trim().is_empty();
+            if is_fence {
+                let start = line_start + i;
+                edits.push(Edit { range: start..start + count, replacement: fence });
+            }
+        }
+    }
+}
+
+/// Use one bullet for every item of a bulleted list, or number an ordered list in order
+fn list_edits(list: &List, text: &str, bullet: char, edits: &mut Vec<Edit>) {
+    let markers: Vec<(usize, &str)> = list
+        .children
+        .iter()
+        .filter_map(|item| {
+            let start = item.position()?.start.offset;
+            let offset = start + text[start..].bytes().take_while(|byte| *byte == b' ').count();
+            Some((offset, &text[offset..]))
+        })
+        .collect();
+
+    if !list.ordered {
+        for (offset, source) in markers {
+            if source.starts_with(['-', '*', '+']) && !source.starts_with(bullet) {
+                edits.push(Edit { range: offset..offset + 1, replacement: bullet.to_string() });
+            }
+        }
+        return;
+    }
+
+    let number = |source: &str| source.bytes().take_while(u8::is_ascii_digit).count();
+    let first = list.start.unwrap_or(1) as usize;
+    // Lists written as `1.`, `1.`, `1.` stay that way, so items can be moved without renumbering
+    let same_number = markers
+        .get(1)
+        .zip(markers.first())
+        .is_some_and(|((_, second), (_, first))| second[..number(second)] == first[..number(first)]);
+
+    for (i, (offset, source)) in markers.into_iter().enumerate() {
+        let digits = number(source);
+        let delimiter = match source[digits..].chars().next() {
+            Some(delimiter @ ('.' | ')')) if digits > 0 => delimiter,
+            _ => continue,
+        };
+
+        let marker = format!("{}{}", if same_number { first } else { first + i }, delimiter);
+        let rest = &source[digits + 1..];
+        let spaces = rest.bytes().take_while(|byte| *byte == b' ').count();
+        let blank_line = rest[spaces..].starts_with(['\n', '\r']) || rest[spaces..].is_empty();
+
+        // The item's content has to stay in the same column, or its continuation lines would change meaning
+        let replacement = if blank_line && marker.len() == digits + 1 {
+            marker
+        } else if !blank_line && (1..=4).contains(&spaces) && marker.len() < digits + 1 + spaces {
+            format!("{:width$}", marker, width = digits + 1 + spaces)
+        } else {
+            continue;
+        };
+
+        if replacement != source[..replacement.len()] {
+            let end = offset + if blank_line { digits + 1 } else { digits + 1 + spaces };
+            edits.push(Edit { range: offset..end, replacement });
+        }
+    }
+}
+
+/// Apply edits in order, leaving out any that overlap an earlier one
+fn apply_edits(text: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut output = String::with_capacity(text.len());
+    let mut position = 0;
+
+    for edit in edits {
+        if edit.range.start < position {
+            continue;
+        }
+        output.push_str(&text[position..edit.range.start]);
+        output.push_str(&edit.replacement);
+        position = edit.range.end;
+    }
+
+    output.push_str(&text[position..]);
+    output
+}
+
+/// Lines that need special treatment when normalising whitespace
+#[derive(Default)]
+struct Lines {
+    /// The lines of code, math and HTML, and of code spans that run over more
+    /// than one line, whose text has to be kept as it is
+    verbatim: Vec<RangeInclusive<usize>>,
+    /// Lines ending in a line break
+    breaks: HashSet<usize>,
+    /// The last line of each list. markdown-rs counts two or more blank lines
+    /// after a list as part of it, which makes the list loose.
+    list_ends: Vec<usize>,
+}
+
+impl Lines {
+    fn collect(&mut self, node: &Node) {
+        match (node, node.position()) {
+            (Node::Code(_) | Node::Math(_) | Node::Html(_) | Node::Yaml(_) | Node::Toml(_), Some(position)) => {
+                self.verbatim.push(position.start.line..=position.end.line);
+            }
+            (Node::InlineCode(_), Some(position)) if position.start.line < position.end.line => {
+                self.verbatim.push(position.start.line..=position.end.line);
+            }
+            (Node::Break(_), Some(position)) => {
+                self.breaks.insert(position.start.line);
+            }
+            _ => {
+                if let (Node::List(_), Some(position)) = (node, node.position()) {
+                    self.list_ends.push(position.end.line);
+                }
+                for child in node.children().into_iter().flatten() {
+                    self.collect(child);
+                }
+            }
+        }
+    }
+}
+
+/// Separate top level blocks by exactly one blank line, and strip trailing
+/// whitespace that is not a line break, leaving code and HTML untouched.
+///
+/// Every line ends with `newline`, so CRLF documents stay CRLF.
+fn normalise_lines(text: &str, tree: &Node, newline: &str) -> String {
+    let mut lines = Lines::default();
+    lines.collect(tree);
+
+    let blocks: Vec<RangeInclusive<usize>> = tree
+        .children()
+        .into_iter()
+        .flatten()
+        .filter_map(Node::position)
+        .map(|position| position.start.line..=position.end.line)
+        .collect();
+    let needs_gap: HashSet<usize> = blocks
+        .windows(2)
+        .filter(|pair| *pair[1].start() == pair[0].end() + 1)
+        .map(|pair| *pair[1].start())
+        .collect();
+
+    let text_lines: Vec<&str> = text.lines().collect();
+    let is_blank = |number: usize| text_lines.get(number - 1).map_or(true, |line| line.trim().is_empty());
+
+    // The blank lines a list ends with have to stay to keep it loose
+    let mut kept_blanks = HashSet::new();
+    for end in lines.list_ends.iter().copied() {
+        let mut number = end;
+        while number > 0 && is_blank(number) && kept_blanks.insert(number) {
+            number -= 1;
+        }
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+
+    for (i, line) in text_lines.iter().copied().enumerate() {
+        let number = i + 1;
+        let blank = line.trim().is_empty();
+
+        if needs_gap.contains(&number) && blank_lines == 0 && !blank {
+            output.push_str(newline);
+        }
+
+        if lines.verbatim.iter().any(|lines| lines.contains(&number)) {
+            output.push_str(line);
+            output.push_str(newline);
+            blank_lines = 0;
+            continue;
+        }
+
+        if blank {
+            if (blank_lines == 0 || kept_blanks.contains(&number)) && !output.is_empty() {
+                output.push_str(newline);
+            }
+            blank_lines += 1;
+            continue;
+        }
+
+        blank_lines = 0;
+
+        output.push_str(if lines.breaks.contains(&number) { line } else { line.trim_end() });
+        output.push_str(newline);
+    }
+
+    if !kept_blanks.contains(&text_lines.len()) {
+        let end = output.trim_end_matches(newline).len();
+        output.truncate(end);
+        if !output.is_empty() {
+            output.push_str(newline);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render;
+
+    fn format_default(text: &str) -> String {
+        format(text, &RenderOptions::default()).unwrap()
+    }
+
+    static DOC: &str = "\
+Title
+=====
+Intro with _emphasis_, __strong__ and **_both_**.
+More text.
+
+
+
+Sub *heading*
+-------------
+
+* one
+* two
+    + nested
++ another list
+
+1. a
+1. b
+1. c
+
+3) x
+5) y
+9) z
+
+~~~rust title=\"a\"
+let s = \"```\";
+```
+~~~
+
+| a | b |
+|:--|--:|
+| long cell | 1 |
+
+> quote with `code` and __strong__
+> ~~~
+> code
+> ~~~
+
+<div>
+  <p>html   </p>
+</div>
+
+- [ ] task
+- [x] done
+
+$$
+x^2
+$$
+
+Footnote[^1].
+
+[^1]: The note.
+";
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            format_default(DOC),
+            "\
+# Title
+
+Intro with *emphasis*, **strong** and **_both_**.
+More text.
+
+## Sub *heading*
+
+- one
+- two
+    - nested
+
+* another list
+
+1. a
+1. b
+1. c
+
+3) x
+4) y
+5) z
+
+````rust title=\"a\"
+let s = \"```\";
+```
+````
+
+| a         |   b |
+|:--------- | ---:|
+| long cell |   1 |
+
+> quote with `code` and **strong**
+> ```
+> code
+> ```
+
+<div>
+  <p>html   </p>
+</div>
+
+- [ ] task
+- [x] done
+
+$$
+x^2
+$$
+
+Footnote[^1].
+
+[^1]: The note.
+"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let options = RenderOptions::default();
+        let formatted = format_default(DOC);
+
+        assert_eq!(render(DOC, &options).unwrap().html, render(&formatted, &options).unwrap().html);
+        assert_eq!(format_default(&formatted), formatted);
+    }
+
+    #[test]
+    fn test_lists_keep_their_meaning() {
+        // Renumbering cannot move an item's content to another column
+        let text = "8. a\n9. b\n99. c\n    continued\n";
+        assert_eq!(format_default(text), "8. a\n9. b\n10. c\n    continued\n");
+
+        let text = "1. a\n\n2.\n   b\n";
+        assert_eq!(format_default(text), text);
+
+        // More than one blank line after a list makes it loose
+        let text = "- a\n  - b\n\n\nc\n";
+        assert_eq!(format_default(text), text);
+
+        let text = "- a\n+ b\n* c\n";
+        assert_eq!(format_default(text), "- a\n\n* b\n\n- c\n");
+
+        for text in [text, "9. a\n1. b\n2. c\n   - d\n   * e\n"] {
+            let options = RenderOptions::default();
+            assert_eq!(render(text, &options).unwrap().html, render(&format_default(text), &options).unwrap().html);
+        }
+    }
+
+    #[test]
+    fn test_headings() {
+        assert_eq!(format_default("A\n=\n\n##   B   ###\n\n###\n"), "# A\n\n## B\n\n###\n");
+        assert_eq!(format_default("A\nB\n---\n\nC #\n---\n"), "A\nB\n---\n\nC #\n---\n");
+    }
+
+    #[test]
+    fn test_whitespace() {
+        assert_eq!(format_default("\n\na  \nb \t\n\n\n\nc\n- d\n```text\ne  \n\n\n```"), "a  \nb\n\nc\n\n- d\n\n```text\ne  \n\n\n```\n");
+    }
+
+    #[test]
+    fn test_code_spans_keep_their_whitespace() {
+        let text = "`a  \nb`  \nc\n";
+        let options = RenderOptions::default();
+
+        assert_eq!(format_default(text), text);
+        assert_eq!(render(text, &options).unwrap().html, render(&format_default(text), &options).unwrap().html);
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_eq!(format_default("A\r\n=\r\n* a \r\n* b\r\n\r\nc"), "# A\r\n\r\n- a\r\n- b\r\n\r\nc\r\n");
+        assert_eq!(format_default("| a |\r\n|-|\r\n| bb |\r\n"), "| a   |\r\n| --- |\r\n| bb  |\r\n");
+    }
+
+    #[test]
+    fn test_format_empty() {
+        assert_eq!(format_default(""), "");
+        assert_eq!(format_default("\n\n  \n"), "");
+    }
+}
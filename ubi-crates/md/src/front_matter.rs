@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+
+use crate::source_lines::line_at;
+
+/// The language a front matter block is written in
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq)]
+pub enum FrontMatterFormat {
+    /// Fenced by `---` lines
+    Yaml,
+    /// Fenced by `+++` lines
+    Toml,
+}
+
+impl FrontMatterFormat {
+    fn fence(&self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "---",
+            FrontMatterFormat::Toml => "+++",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FrontMatterFormat::Yaml => "YAML",
+            FrontMatterFormat::Toml => "TOML",
+        }
+    }
+}
+
+/// The metadata block at the top of a document, as used by static site generators
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrontMatter {
+    pub format: FrontMatterFormat,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub date: Option<String>,
+    pub tags: Vec<String>,
+    /// Every top level field in the order it was written, with its value as text
+    pub fields: Vec<(String, String)>,
+    /// The byte offset the body of the document starts at
+    pub end: usize,
+}
+
+/// A front matter block's language and the source between its fences
+struct Block<'a> {
+    format: FrontMatterFormat,
+    source: &'a str,
+    end: usize,
+}
+
+/// Find the front matter block at the very start of the text.
+///
+/// This follows the markdown parser's rules, a fence of exactly three `-` or
+/// `+` closed by the same fence, so what is parsed here is what the preview hides.
+fn find_block(text: &str) -> Option<Block<'_>> {
+    let first_line = text.split('\n').next()?;
+    let format = match first_line.trim_end() {
+        "---" => FrontMatterFormat::Yaml,
+        "+++" => FrontMatterFormat::Toml,
+        _ => return None,
+    };
+
+    let start = (first_line.len() + 1).min(text.len());
+    let mut offset = start;
+
+    for line in text[start..].split_inclusive('\n') {
+        if line.trim_end() == format.fence() {
+            let source = &text[start..offset];
+            return Some(Block { format, source, end: offset + line.len() });
+        }
+        offset += line.len();
+    }
+
+    None
+}
+
+/// Parse the front matter at the start of a document.
+///
+/// Returns `None` when the document has no front matter, and an error naming
+/// the line of the document when the block is not a valid YAML mapping or TOML table.
+pub fn front_matter(text: &str) -> Result<Option<FrontMatter>, String> {
+    let block = match find_block(text) {
+        Some(block) => block,
+        None => return Ok(None),
+    };
+
+    let fields = match block.format {
+        FrontMatterFormat::Yaml => yaml_fields(block.source),
+        FrontMatterFormat::Toml => toml_fields(block.source),
+    };
+
+    let fields = fields.map_err(|(line, message)| match line {
+        // The opening fence is the first line of the document
+        Some(line) => format!("Invalid {} front matter on line {}: {}", block.format.name(), line + 1, message),
+        None => format!("Invalid {} front matter: {}", block.format.name(), message),
+    })?;
+
+    let mut front_matter = FrontMatter {
+        format: block.format,
+        title: None,
+        author: None,
+        date: None,
+        tags: Vec::new(),
+        fields: Vec::new(),
+        end: block.end,
+    };
+
+    for (key, value) in fields {
+        match key.to_ascii_lowercase().as_str() {
+            "title" => front_matter.title = value.text(),
+            "author" | "authors" => front_matter.author = value.text(),
+            "date" => front_matter.date = value.text(),
+            "tags" => front_matter.tags = value.list(),
+            _ => {}
+        }
+        front_matter.fields.push((key, value.to_string()));
+    }
+
+    Ok(Some(front_matter))
+}
+
+/// A field value, reduced to what the editor needs to show it
+enum Value {
+    Text(String),
+    List(Vec<String>),
+    Other(String),
+}
+
+impl Value {
+    fn text(&self) -> Option<String> {
+        match self {
+            Value::Text(text) => Some(text.clone()),
+            Value::List(items) if !items.is_empty() => Some(items.join(", ")),
+            _ => None,
+        }
+    }
+
+    /// A list of items, also accepting a comma separated string
+    fn list(&self) -> Vec<String> {
+        match self {
+            Value::Text(text) => text
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(String::from)
+                .collect(),
+            Value::List(items) => items.clone(),
+            Value::Other(_) => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Text(text) | Value::Other(text) => write!(f, "{}", text),
+            Value::List(items) => write!(f, "{}", items.join(", ")),
+        }
+    }
+}
+
+/// The fields of a block, or the line the error is on and its message
+type Fields = Result<Vec<(String, Value)>, (Option<usize>, String)>;
+
+/// The top level fields of a YAML mapping
+fn yaml_fields(source: &str) -> Fields {
+    let mapping = match serde_yaml::from_str::<serde_yaml::Value>(source) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => mapping,
+        Ok(serde_yaml::Value::Null) => return Ok(Vec::new()),
+        Ok(_) => return Err((None, String::from("expected a list of `key: value` fields"))),
+        Err(err) => {
+            let message = err.to_string();
+            return Err(match err.location() {
+                Some(location) => {
+                    let suffix = format!(" at line {} column {}", location.line(), location.column());
+                    (Some(location.line()), message.trim_end_matches(&suffix).to_string())
+                }
+                None => (None, message),
+            });
+        }
+    };
+
+    Ok(mapping.into_iter().map(|(key, value)| (yaml_text(&key), yaml_value(&value))).collect())
+}
+
+fn yaml_value(value: &serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Sequence(items) => Value::List(items.iter().map(yaml_text).collect()),
+        serde_yaml::Value::Mapping(_) | serde_yaml::Value::Tagged(_) => Value::Other(yaml_text(value)),
+        _ => Value::Text(yaml_text(value)),
+    }
+}
+
+fn yaml_text(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::Null => String::new(),
+        serde_yaml::Value::Bool(bool) => bool.to_string(),
+        serde_yaml::Value::Number(number) => number.to_string(),
+        serde_yaml::Value::String(string) => string.clone(),
+        _ => serde_yaml::to_string(value).unwrap_or_default().trim_end().to_string(),
+    }
+}
+
+/// The top level fields of a TOML table
+fn toml_fields(source: &str) -> Fields {
+    let table = source.parse::<toml::Table>().map_err(|err| {
+        let line = err.span().map(|span| line_at(source, span.start));
+        (line, err.message().to_string())
+    })?;
+
+    Ok(table.into_iter().map(|(key, value)| (key, toml_value(&value))).collect())
+}
+
+fn toml_value(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::Array(items) => Value::List(items.iter().map(toml_text).collect()),
+        toml::Value::Table(_) => Value::Other(toml_text(value)),
+        _ => Value::Text(toml_text(value)),
+    }
+}
+
+fn toml_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(string) => string.clone(),
+        toml::Value::Datetime(datetime) => datetime.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{render, RenderOptions};
+
+    #[test]
+    fn test_yaml() {
+        let text = "\
+---
+title: \"Hello, world\"
+author: Ada
+date: 2023-07-01
+tags: [rust, markdown]
+draft: false
+---
+# Body
+";
+        let front_matter = front_matter(text).unwrap().unwrap();
+
+        assert_eq!(front_matter.format, FrontMatterFormat::Yaml);
+        assert_eq!(front_matter.title.as_deref(), Some("Hello, world"));
+        assert_eq!(front_matter.author.as_deref(), Some("Ada"));
+        assert_eq!(front_matter.date.as_deref(), Some("2023-07-01"));
+        assert_eq!(front_matter.tags, vec!["rust", "markdown"]);
+        assert_eq!(front_matter.fields[4], ("draft".to_string(), "false".to_string()));
+        assert_eq!(&text[front_matter.end..], "# Body\n");
+    }
+
+    #[test]
+    fn test_toml() {
+        let text = "\
++++
+title = 'Notes'
+date = 2023-07-01T10:00:00Z
+tags = \"a, b\"
+
+[extra]
+toc = true
++++
+Body";
+        let front_matter = front_matter(text).unwrap().unwrap();
+
+        assert_eq!(front_matter.format, FrontMatterFormat::Toml);
+        assert_eq!(front_matter.title.as_deref(), Some("Notes"));
+        assert_eq!(front_matter.author, None);
+        assert_eq!(front_matter.date.as_deref(), Some("2023-07-01T10:00:00Z"));
+        assert_eq!(front_matter.tags, vec!["a", "b"]);
+        assert_eq!(front_matter.fields.last().unwrap().0, "extra");
+        assert_eq!(&text[front_matter.end..], "Body");
+    }
+
+    #[test]
+    fn test_no_front_matter() {
+        assert_eq!(front_matter("# Title\n\n---\n"), Ok(None));
+        assert_eq!(front_matter("----\ntitle: a\n----\n"), Ok(None));
+        // Without a closing fence the opening one is just a horizontal rule
+        assert_eq!(front_matter("---\ntitle: a\n"), Ok(None));
+        assert_eq!(front_matter("---\n---\nBody").unwrap().unwrap().fields, Vec::new());
+    }
+
+    #[test]
+    fn test_malformed_front_matter() {
+        let yaml = front_matter("---\ntitle: a\ntags: [a, b\n---\n").unwrap_err();
+        assert!(yaml.starts_with("Invalid YAML front matter on line "), "{}", yaml);
+
+        let toml = front_matter("+++\ntitle = 'a'\ntags = \n+++\n").unwrap_err();
+        assert!(toml.starts_with("Invalid TOML front matter on line 3: "), "{}", toml);
+
+        assert!(front_matter("---\ntitle: Hello: world\n---\n").is_err());
+
+        let list = front_matter("---\n- a\n- b\n---\n").unwrap_err();
+        assert_eq!(list, "Invalid YAML front matter: expected a list of `key: value` fields");
+    }
+
+    #[test]
+    fn test_preview_hides_front_matter() {
+        let text = "---\ntitle: Post\n---\n\n# Heading\n";
+        let options = RenderOptions { source_lines: true, ..RenderOptions::default() };
+        let html = render(text, &options).unwrap().html;
+
+        assert_eq!(html, "<h1 data-line=\"5\" id=\"user-content-heading\">Heading</h1>\n");
+
+        let options = RenderOptions { front_matter: false, ..RenderOptions::default() };
+        assert!(render(text, &options).unwrap().html.starts_with("<hr />"));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+use crate::render::decode_entities;
+
+/// The prefix of every class given to a highlighted token.
+///
+/// The colours live in the frontend's `highlight.css`, which maps each scope
+/// to a colour of the active DaisyUI theme.
+pub const CLASS_PREFIX: &str = "hl-";
+
+static CODE_OPEN: &str = "<pre><code";
+static CODE_CLOSE: &str = "</code></pre>";
+
+/// The class of code with no language, as syntect's plain text grammar would give it
+static PLAIN_TEXT_CLASS: &str = "hl-text hl-plain";
+
+/// The highlighted blocks of the last render, keyed by language and code
+type Highlighted = HashMap<(Option<String>, String), String>;
+
+/// The bundled grammars are slow to load, so it is left until a block names a language, and only done once
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Blocks highlighted by the last render, so that typing elsewhere does not highlight them again
+fn last_highlighted() -> &'static Mutex<Highlighted> {
+    static LAST_HIGHLIGHTED: OnceLock<Mutex<Highlighted>> = OnceLock::new();
+    LAST_HIGHLIGHTED.get_or_init(Mutex::default)
+}
+
+/// Highlight code, returning HTML with a classed `<span>` around every token.
+///
+/// The language is matched against grammar names and file extensions, so both
+/// `rust` and `rs` work. Unknown languages are treated as plain text.
+pub fn highlight(code: &str, language: Option<&str>) -> String {
+    let Some(language) = language else {
+        return format!("<span class=\"{}\">{}</span>", PLAIN_TEXT_CLASS, escape(code));
+    };
+
+    let syntaxes = syntax_set();
+    let syntax = syntaxes
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    let style = ClassStyle::SpacedPrefixed { prefix: CLASS_PREFIX };
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, style);
+
+    for line in LinesWithEndings::from(code) {
+        if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+            return escape(code);
+        }
+    }
+
+    generator.finalize()
+}
+
+/// Highlight the content of every `<pre><code>` block in rendered HTML.
+///
+/// Blocks that are unchanged since the last call are reused rather than highlighted again.
+pub(crate) fn highlight_code_blocks(html: &str) -> String {
+    let mut last = last_highlighted().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut highlighted = Highlighted::new();
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find(CODE_OPEN) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let open_end = rest[CODE_OPEN.len()..].find('>').map(|end| CODE_OPEN.len() + end + 1);
+        let (open_end, close_start) = match (open_end, rest.find(CODE_CLOSE)) {
+            (Some(open_end), Some(close_start)) if open_end <= close_start => (open_end, close_start),
+            _ => break,
+        };
+
+        let open = &rest[..open_end];
+        let key = (language(open).map(String::from), decode_entities(&rest[open_end..close_start]));
+        let code = match last.remove(&key) {
+            Some(code) => code,
+            None => highlight(&key.1, key.0.as_deref()),
+        };
+
+        output.push_str(open);
+        output.push_str(&code);
+        output.push_str(CODE_CLOSE);
+        highlighted.insert(key, code);
+
+        rest = &rest[close_start + CODE_CLOSE.len()..];
+    }
+
+    *last = highlighted;
+    output.push_str(rest);
+    output
+}
+
+/// The language of an opening `<pre><code class="language-...">` tag
+fn language(open: &str) -> Option<&str> {
+    let start = open.find("language-")? + "language-".len();
+    let end = open[start..].find(['"', ' '])?;
+    Some(&open[start..start + end])
+}
+
+/// Escape code the way syntect does, so plain text looks the same either way
+fn escape(code: &str) -> String {
+    code.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_rust() {
+        let html = highlight("fn main() {}\n", Some("rust"));
+
+        assert!(html.contains("<span class=\"hl-storage hl-type hl-function hl-rust\">fn</span>"));
+        assert!(html.contains("hl-entity hl-name hl-function"));
+        assert_eq!(html, highlight("fn main() {}\n", Some("rs")));
+    }
+
+    #[test]
+    fn test_unknown_language() {
+        let html = highlight("a < b\n", Some("not-a-language"));
+
+        assert_eq!(html, "<span class=\"hl-text hl-plain\">a &lt; b\n</span>");
+        assert_eq!(html, highlight("a < b\n", None));
+        assert_eq!(highlight("\"x\" & 'y'\n", Some("not-a-language")), highlight("\"x\" & 'y'\n", None));
+    }
+
+    #[test]
+    fn test_highlight_code_blocks() {
+        let html = "<p>x</p>\n<pre><code class=\"language-json\">{&quot;a&quot;: 1}\n</code></pre>\n<pre><code>a &amp;&amp; b\n</code></pre>";
+        let highlighted = highlight_code_blocks(html);
+
+        assert!(highlighted.starts_with("<p>x</p>\n<pre><code class=\"language-json\"><span class=\"hl-source hl-json\">"));
+        assert!(highlighted.contains("hl-string hl-quoted hl-double hl-json"));
+        assert!(highlighted.ends_with("<pre><code><span class=\"hl-text hl-plain\">a &amp;&amp; b\n</span></code></pre>"));
+        // Unchanged blocks come out the same when reused
+        assert_eq!(highlight_code_blocks(html), highlighted);
+    }
+
+    #[test]
+    fn test_language() {
+        assert_eq!(language("<pre><code class=\"language-rust\">"), Some("rust"));
+        assert_eq!(language("<pre><code>"), None);
+    }
+}
@@ -1,16 +1,130 @@
 use serde::{Deserialize, Serialize};
 
+mod diff;
+mod format;
+mod front_matter;
+mod highlight;
+mod links;
+mod math;
+mod merge;
+mod outline;
+mod render;
+mod sanitise;
+mod source_lines;
+mod stats;
+mod toc;
+
+pub use diff::{diff, DiffKind, DiffLine};
+pub use format::format;
+pub use front_matter::{front_matter, FrontMatter, FrontMatterFormat};
+pub use highlight::{highlight, CLASS_PREFIX};
+pub use links::{links, DocLink, LinkStatus, LinkTarget};
+pub use math::tex_to_mathml;
+pub use merge::{merge, MergeChunk};
+pub use outline::{current_heading, outline, plain_text, OutlineHeading};
+pub use render::{element_id, render, slugify, Heading, HtmlPolicy, RenderOptions, Rendered, Slugger};
+pub use sanitise::{sanitise, ID_PREFIX};
+pub use source_lines::{column_at, line_at, line_offset};
+pub use stats::{stats, Stats, WORDS_PER_MINUTE};
+pub use toc::{insert_or_update_toc, toc, TOC_END, TOC_START};
+
 #[derive(Deserialize, Serialize)]
 pub struct MarkdownFile {
     pub path: Option<String>,
     pub contents: String
 }
 
+/// A file to save, asking for a dialog when there is no path yet
+#[derive(Deserialize, Serialize)]
+pub struct SaveFile {
+    pub path: Option<String>,
+    pub contents: String,
+    pub backups: BackupPolicy
+}
+
+/// What is kept of a file's previous contents when it is saved over
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BackupPolicy {
+    #[default]
+    Off,
+    /// The last version, as `notes.md.bak`
+    Single,
+    /// Up to this many versions, newest first, as `notes.md.1.bak`, `notes.md.2.bak` and so on
+    Numbered(u8)
+}
+
+/// Edits to a file that were never saved, kept from before Ubiquity last closed
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Recovery {
+    pub path: String,
+    pub contents: String,
+    /// What is on disk now, or `None` when the file is gone
+    pub saved: Option<String>
+}
+
+/// A file that another program changed while it was open
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    pub contents: String,
+    /// The file as Ubiquity last read or saved it, for merging
+    pub base: String
+}
+
+/// The event the backend sends with a [`FileChange`]
+pub const FILE_CHANGED_EVENT: &str = "file-changed";
+
 #[derive(Deserialize, Serialize)]
 pub struct MarkdownPath {
     pub path: String
 }
 
+/// Files to look for, relative to the document at `base`
+#[derive(Deserialize, Serialize)]
+pub struct LinkedFiles {
+    pub base: String,
+    pub paths: Vec<String>
+}
+
+/// The markdown files in a folder opened as a workspace
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Workspace {
+    pub root: String,
+    pub files: Vec<WorkspaceFile>
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct WorkspaceFile {
+    pub path: String,
+    /// Relative to the workspace root, with `/` between folders
+    pub relative: String
+}
+
+/// A file or folder in the open workspace, relative to its root
+#[derive(Deserialize, Serialize)]
+pub struct WorkspacePath {
+    pub path: String
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RenamePath {
+    pub path: String,
+    pub name: String
+}
+
+/// Where `folder` is empty for the workspace root
+#[derive(Deserialize, Serialize)]
+pub struct MovePath {
+    pub path: String,
+    pub folder: String
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct ExportFile {
+    pub contents: String,
+    pub extension: String
+}
+
 pub const DOCS_KEY: &'static str = "ubiquity_about.md";
 pub const DOCS_STR: &'static str = r#"# Ubiquity
 
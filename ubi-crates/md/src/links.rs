@@ -0,0 +1,281 @@
+use std::collections::HashSet;
+use std::ops::Range;
+
+use markdown::mdast::Node;
+
+use crate::outline::outline;
+use crate::render::{element_id, HtmlPolicy, RenderOptions};
+use crate::sanitise::ID_PREFIX;
+
+/// What a link points at
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// An element of this document, from a `#anchor` link
+    Anchor(String),
+    /// A file, relative to the document unless the path is absolute.
+    /// Any anchor or query after the path is left out.
+    File(String),
+    /// A URL with a scheme, such as `https:` or `mailto:`
+    Remote,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkStatus {
+    Ok,
+    Broken,
+    /// Remote links are never fetched, and files can only be looked for on desktop
+    Unchecked,
+}
+
+/// A link or image in a document
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocLink {
+    pub url: String,
+    pub image: bool,
+    pub target: LinkTarget,
+    pub status: LinkStatus,
+    /// The line the link starts on, counting from 1
+    pub line: usize,
+    pub range: Range<usize>,
+}
+
+/// Every link and image in a document, with `#anchor` links checked against
+/// the ids of its headings and HTML elements.
+///
+/// Reference links are listed once, at their definition, as that is where
+/// the URL is written. File links are left unchecked for the caller to look
+/// for on disk.
+pub fn links(text: &str, options: &RenderOptions) -> Result<Vec<DocLink>, String> {
+    let tree = markdown::to_mdast(text, &options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+
+    let mut ids = HashSet::new();
+    // The preview follows plain anchors to prefixed ids, so both forms reach a heading
+    for heading in outline(text, options)? {
+        ids.insert(element_id(&heading.id, options.html));
+        ids.insert(heading.id);
+    }
+    if options.html != HtmlPolicy::Escape {
+        collect_html_ids(&tree, &mut ids);
+    }
+
+    let mut links = Vec::new();
+    collect_links(&tree, &mut links);
+
+    for link in &mut links {
+        link.status = match &link.target {
+            // An empty anchor goes to the top of the page
+            LinkTarget::Anchor(id) if id.is_empty() || ids.contains(id) => LinkStatus::Ok,
+            LinkTarget::Anchor(_) => LinkStatus::Broken,
+            LinkTarget::File(_) | LinkTarget::Remote => LinkStatus::Unchecked,
+        };
+    }
+
+    Ok(links)
+}
+
+fn collect_links(node: &Node, links: &mut Vec<DocLink>) {
+    let url = match node {
+        Node::Link(link) => Some((&link.url, false)),
+        Node::Definition(definition) => Some((&definition.url, false)),
+        Node::Image(image) => Some((&image.url, true)),
+        _ => None,
+    };
+
+    if let (Some((url, image)), Some(position)) = (url, node.position()) {
+        if let Some(target) = target(url) {
+            links.push(DocLink {
+                url: url.clone(),
+                image,
+                target,
+                status: LinkStatus::Unchecked,
+                line: position.start.line,
+                range: position.start.offset..position.end.offset,
+            });
+        }
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_links(child, links);
+    }
+}
+
+/// Work out what a URL points at, or `None` when it is empty
+fn target(url: &str) -> Option<LinkTarget> {
+    if url.is_empty() {
+        return None;
+    }
+
+    if let Some(anchor) = url.strip_prefix('#') {
+        return Some(LinkTarget::Anchor(percent_decode(anchor)));
+    }
+
+    // Single letter schemes are left as paths, as they are more likely a Windows drive
+    let scheme = url.split(':').next().filter(|scheme| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+            && url.len() > scheme.len()
+    });
+    if scheme.is_some() || url.starts_with("//") {
+        return Some(LinkTarget::Remote);
+    }
+
+    let path = url.split(['#', '?']).next().unwrap_or_default();
+    Some(LinkTarget::File(percent_decode(path)))
+}
+
+/// The `id` and `name` attributes in raw HTML, which links can point at too.
+/// Sanitising prefixes ids, and the preview follows links to either form.
+fn collect_html_ids(node: &Node, ids: &mut HashSet<String>) {
+    if let Node::Html(html) = node {
+        for attribute in ["id=", "name="] {
+            let values = html.value.match_indices(attribute).filter_map(|(i, _)| {
+                if !html.value[..i].ends_with(char::is_whitespace) {
+                    return None;
+                }
+                let value = &html.value[i + attribute.len()..];
+                let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+                let value = &value[1..];
+                Some(value[..value.find(quote)?].to_string())
+            });
+            for value in values {
+                if attribute == "id=" {
+                    ids.insert(format!("{}{}", ID_PREFIX, value));
+                }
+                ids.insert(value);
+            }
+        }
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_html_ids(child, ids);
+    }
+}
+
+/// Decode `%XX` escapes, leaving malformed ones as they are
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(text: &str) -> Vec<(LinkTarget, LinkStatus, usize)> {
+        links(text, &RenderOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|link| (link.target, link.status, link.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_anchors() {
+        let text = "\
+# Intro
+
+## Setup *steps*
+
+## Intro
+
+<a name=\"custom\"></a>
+
+[a](#intro) [b](#setup-steps) [c](#intro-1) [d](#custom) [e](#missing) [f](#) [g](#user-content-raw)
+
+<span id=\"raw\"></span>
+";
+        let anchor = |id: &str| LinkTarget::Anchor(id.to_string());
+        assert_eq!(
+            summary(text),
+            vec![
+                (anchor("intro"), LinkStatus::Ok, 9),
+                (anchor("setup-steps"), LinkStatus::Ok, 9),
+                (anchor("intro-1"), LinkStatus::Ok, 9),
+                (anchor("custom"), LinkStatus::Ok, 9),
+                (anchor("missing"), LinkStatus::Broken, 9),
+                (anchor(""), LinkStatus::Ok, 9),
+                (anchor("user-content-raw"), LinkStatus::Ok, 9),
+            ]
+        );
+
+        // Escaped HTML has no ids in the preview
+        let options = RenderOptions { html: HtmlPolicy::Escape, ..RenderOptions::default() };
+        let statuses: Vec<LinkStatus> = links(text, &options).unwrap().into_iter().map(|link| link.status).collect();
+        assert_eq!(statuses[3], LinkStatus::Broken);
+    }
+
+    #[test]
+    fn test_anchors_match_preview_ids() {
+        let text = "# Heading[^1]\n\n# A <b>x</b>\n\n[a](#heading1) [b](#a-x) [c](#a-bxb)\n\n[^1]: Note\n";
+        let statuses = |html: HtmlPolicy| -> Vec<LinkStatus> {
+            let options = RenderOptions { html, ..RenderOptions::default() };
+            links(text, &options).unwrap().into_iter().map(|link| link.status).collect()
+        };
+
+        assert_eq!(statuses(HtmlPolicy::Sanitise), vec![LinkStatus::Ok, LinkStatus::Ok, LinkStatus::Broken]);
+        assert_eq!(statuses(HtmlPolicy::Escape), vec![LinkStatus::Ok, LinkStatus::Broken, LinkStatus::Ok]);
+
+        // Heading elements have prefixed ids unless HTML is trusted
+        let text = "# Intro\n\n[a](#user-content-intro)\n";
+        let status = |html: HtmlPolicy| links(text, &RenderOptions { html, ..RenderOptions::default() }).unwrap()[0].status;
+        assert_eq!(status(HtmlPolicy::Sanitise), LinkStatus::Ok);
+        assert_eq!(status(HtmlPolicy::Trust), LinkStatus::Broken);
+    }
+
+    #[test]
+    fn test_targets() {
+        let text = "\
+[a](docs/My%20File.md#usage) ![b](./images/b.png?raw=true) [c](https://example.com)
+<mailto:me@example.com> www.example.com [d][ref] [d again][ref]
+
+```md
+[not a link](missing.md)
+```
+
+[ref]: /abs/path.md
+";
+        let file = |path: &str| LinkTarget::File(path.to_string());
+        assert_eq!(
+            summary(text),
+            vec![
+                (file("docs/My File.md"), LinkStatus::Unchecked, 1),
+                (file("./images/b.png"), LinkStatus::Unchecked, 1),
+                (LinkTarget::Remote, LinkStatus::Unchecked, 1),
+                (LinkTarget::Remote, LinkStatus::Unchecked, 2),
+                (LinkTarget::Remote, LinkStatus::Unchecked, 2),
+                (file("/abs/path.md"), LinkStatus::Unchecked, 8),
+            ]
+        );
+
+        let images: Vec<bool> = links(text, &RenderOptions::default()).unwrap().iter().map(|link| link.image).collect();
+        assert_eq!(images, vec![false, true, false, false, false, false]);
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b%2"), "a b%2");
+        assert_eq!(percent_decode("%E2%9C%93%zz"), "✓%zz");
+    }
+}
@@ -0,0 +1,736 @@
+use crate::render::decode_entities;
+
+static INLINE_OPEN: &str = "<code class=\"language-math math-inline\">";
+static INLINE_CLOSE: &str = "</code>";
+static DISPLAY_OPEN: &str = "<pre><code class=\"language-math math-display\">";
+static DISPLAY_CLOSE: &str = "</code></pre>";
+
+/// How deeply groups, `\left…\right` pairs and environments can nest, as each level recurses
+const MAX_NESTING: usize = 64;
+
+static GREEK: &[(&str, char)] = &[
+    ("alpha", 'α'), ("beta", 'β'), ("gamma", 'γ'), ("delta", 'δ'), ("epsilon", 'ϵ'), ("varepsilon", 'ε'),
+    ("zeta", 'ζ'), ("eta", 'η'), ("theta", 'θ'), ("vartheta", 'ϑ'), ("iota", 'ι'), ("kappa", 'κ'),
+    ("lambda", 'λ'), ("mu", 'μ'), ("nu", 'ν'), ("xi", 'ξ'), ("omicron", 'ο'), ("pi", 'π'), ("varpi", 'ϖ'),
+    ("rho", 'ρ'), ("varrho", 'ϱ'), ("sigma", 'σ'), ("varsigma", 'ς'), ("tau", 'τ'), ("upsilon", 'υ'),
+    ("phi", 'ϕ'), ("varphi", 'φ'), ("chi", 'χ'), ("psi", 'ψ'), ("omega", 'ω'),
+    ("Gamma", 'Γ'), ("Delta", 'Δ'), ("Theta", 'Θ'), ("Lambda", 'Λ'), ("Xi", 'Ξ'), ("Pi", 'Π'),
+    ("Sigma", 'Σ'), ("Upsilon", 'Υ'), ("Phi", 'Φ'), ("Psi", 'Ψ'), ("Omega", 'Ω'),
+];
+
+/// Symbols that are identifiers rather than operators
+static IDENTIFIERS: &[(&str, char)] = &[
+    ("infty", '∞'), ("partial", '∂'), ("nabla", '∇'), ("hbar", 'ℏ'), ("ell", 'ℓ'), ("emptyset", '∅'),
+    ("varnothing", '∅'), ("aleph", 'ℵ'), ("Re", 'ℜ'), ("Im", 'ℑ'),
+];
+
+static OPERATORS: &[(&str, char)] = &[
+    ("cdot", '⋅'), ("times", '×'), ("div", '÷'), ("pm", '±'), ("mp", '∓'), ("ast", '∗'), ("star", '⋆'),
+    ("circ", '∘'), ("bullet", '∙'), ("oplus", '⊕'), ("otimes", '⊗'), ("leq", '≤'), ("le", '≤'),
+    ("geq", '≥'), ("ge", '≥'), ("neq", '≠'), ("ne", '≠'), ("ll", '≪'), ("gg", '≫'), ("approx", '≈'),
+    ("equiv", '≡'), ("sim", '∼'), ("simeq", '≃'), ("cong", '≅'), ("propto", '∝'), ("to", '→'),
+    ("rightarrow", '→'), ("leftarrow", '←'), ("gets", '←'), ("leftrightarrow", '↔'), ("Rightarrow", '⇒'),
+    ("Leftarrow", '⇐'), ("Leftrightarrow", '⇔'), ("implies", '⟹'), ("iff", '⟺'), ("mapsto", '↦'),
+    ("in", '∈'), ("notin", '∉'), ("ni", '∋'), ("subset", '⊂'), ("subseteq", '⊆'), ("supset", '⊃'),
+    ("supseteq", '⊇'), ("cup", '∪'), ("cap", '∩'), ("setminus", '∖'), ("forall", '∀'), ("exists", '∃'),
+    ("neg", '¬'), ("lnot", '¬'), ("land", '∧'), ("wedge", '∧'), ("lor", '∨'), ("vee", '∨'), ("perp", '⊥'),
+    ("parallel", '∥'), ("mid", '∣'), ("ldots", '…'), ("dots", '…'), ("cdots", '⋯'), ("vdots", '⋮'),
+    ("ddots", '⋱'), ("langle", '⟨'), ("rangle", '⟩'), ("lfloor", '⌊'), ("rfloor", '⌋'), ("lceil", '⌈'),
+    ("rceil", '⌉'), ("prime", '′'), ("colon", ':'), ("{", '{'), ("}", '}'), ("|", '‖'), ("#", '#'),
+    ("$", '$'), ("%", '%'), ("&", '&'), ("_", '_'),
+];
+
+/// Large operators, and whether their scripts become limits above and below in display math
+static LARGE_OPERATORS: &[(&str, char, bool)] = &[
+    ("sum", '∑', true), ("prod", '∏', true), ("coprod", '∐', true), ("bigcup", '⋃', true),
+    ("bigcap", '⋂', true), ("bigoplus", '⨁', true), ("bigotimes", '⨂', true), ("int", '∫', false),
+    ("iint", '∬', false), ("iiint", '∭', false), ("oint", '∮', false),
+];
+
+/// Function names, and whether their scripts become limits above and below in display math
+static FUNCTIONS: &[(&str, bool)] = &[
+    ("sin", false), ("cos", false), ("tan", false), ("sec", false), ("csc", false), ("cot", false),
+    ("arcsin", false), ("arccos", false), ("arctan", false), ("sinh", false), ("cosh", false),
+    ("tanh", false), ("log", false), ("ln", false), ("lg", false), ("exp", false), ("deg", false),
+    ("dim", false), ("ker", false), ("arg", false), ("hom", false), ("lim", true), ("liminf", true),
+    ("limsup", true), ("max", true), ("min", true), ("sup", true), ("inf", true), ("det", true),
+    ("gcd", true), ("Pr", true),
+];
+
+/// Accents drawn over or under their argument
+static ACCENTS: &[(&str, char, bool)] = &[
+    ("hat", '^', true), ("widehat", '^', true), ("bar", '¯', true), ("overline", '¯', true),
+    ("vec", '→', true), ("overrightarrow", '→', true), ("dot", '˙', true), ("ddot", '¨', true),
+    ("tilde", '~', true), ("widetilde", '~', true), ("underline", '_', false),
+];
+
+static SPACES: &[(&str, &str)] = &[
+    (",", "0.1667em"), (":", "0.2222em"), (">", "0.2222em"), (";", "0.2778em"), ("!", "-0.1667em"),
+    (" ", "0.25em"), ("quad", "1em"), ("qquad", "2em"),
+];
+
+static VARIANTS: &[(&str, &str)] = &[
+    ("mathrm", "normal"), ("mathbf", "bold"), ("boldsymbol", "bold-italic"), ("mathit", "italic"),
+    ("mathbb", "double-struck"), ("mathcal", "script"), ("mathfrak", "fraktur"), ("mathsf", "sans-serif"),
+    ("mathtt", "monospace"),
+];
+
+/// Replace the math that markdown leaves in `<code>` elements with MathML.
+///
+/// Math that fails to parse is left as red source text, with the reason as a tooltip.
+pub(crate) fn render_math(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    loop {
+        let next = [(INLINE_OPEN, INLINE_CLOSE, false), (DISPLAY_OPEN, DISPLAY_CLOSE, true)]
+            .into_iter()
+            .filter_map(|(open, close, display)| rest.find(open).map(|start| (start, open, close, display)))
+            .min_by_key(|(start, ..)| *start);
+
+        let (start, open, close, display) = match next {
+            Some(next) => next,
+            None => break,
+        };
+
+        let content_start = start + open.len();
+        let content_end = match rest[content_start..].find(close) {
+            Some(end) => content_start + end,
+            None => break,
+        };
+
+        let tex = decode_entities(&rest[content_start..content_end]);
+
+        output.push_str(&rest[..start]);
+        output.push_str(&match tex_to_mathml(&tex, display) {
+            Ok(mathml) => mathml,
+            Err(err) if display => format!(
+                "<pre class=\"math-error text-error\" title=\"{}\">{}</pre>",
+                escape(&err),
+                &rest[content_start..content_end]
+            ),
+            Err(err) => format!(
+                "<code class=\"math-error text-error\" title=\"{}\">{}</code>",
+                escape(&err),
+                &rest[content_start..content_end]
+            ),
+        });
+
+        rest = &rest[content_end + close.len()..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Convert TeX math into a MathML `<math>` element.
+///
+/// The source is kept as an annotation, so copying the math gives back the TeX.
+pub fn tex_to_mathml(tex: &str, display: bool) -> Result<String, String> {
+    let mut parser = Parser { tokens: tokenize(tex), position: 0, display, variant: None, nesting: 0 };
+    let row = parser.parse_row()?;
+
+    match parser.peek() {
+        None => {}
+        Some(Token::Close) => return Err("Unexpected }".to_string()),
+        Some(Token::Align) => return Err("& is only allowed inside an environment".to_string()),
+        Some(Token::NewRow) => return Err("\\\\ is only allowed inside an environment".to_string()),
+        Some(Token::Command(name)) => return Err(format!("Unexpected \\{}", name)),
+        Some(_) => unreachable!(),
+    }
+
+    let display = if display { " display=\"block\"" } else { "" };
+
+    Ok(format!(
+        "<math{}><semantics><mrow>{}</mrow><annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+        display,
+        row,
+        escape(tex.trim())
+    ))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Char(char),
+    Command(String),
+    Space,
+    Open,
+    Close,
+    Sub,
+    Sup,
+    Align,
+    NewRow,
+}
+
+fn tokenize(tex: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = tex.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => match chars.next() {
+                Some('\\') => Token::NewRow,
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut name = c.to_string();
+                    while let Some(c) = chars.next_if(char::is_ascii_alphabetic) {
+                        name.push(c);
+                    }
+                    Token::Command(name)
+                }
+                Some(c) => Token::Command(c.to_string()),
+                None => Token::Char('\\'),
+            },
+            '%' => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '_' => Token::Sub,
+            '^' => Token::Sup,
+            '&' => Token::Align,
+            c if c.is_whitespace() => Token::Space,
+            c => Token::Char(c),
+        };
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    display: bool,
+    /// The `mathvariant` set by a command such as `\mathbf` for the argument being parsed
+    variant: Option<&'static str>,
+    /// How many rows are being parsed inside each other
+    nesting: usize,
+}
+
+impl Parser {
+    /// The next token, skipping spaces as TeX does in math
+    fn peek(&mut self) -> Option<Token> {
+        while self.tokens.get(self.position) == Some(&Token::Space) {
+            self.position += 1;
+        }
+        self.tokens.get(self.position).cloned()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    /// Parse until the end of a group, cell or row, leaving the token that ended it
+    fn parse_row(&mut self) -> Result<String, String> {
+        if self.nesting == MAX_NESTING {
+            return Err("Math is nested too deeply".to_string());
+        }
+        self.nesting += 1;
+
+        let mut row = String::new();
+
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Close | Token::Align | Token::NewRow => break,
+                Token::Command(name) if name == "end" || name == "right" => break,
+                _ => row.push_str(&self.parse_scripted()?),
+            }
+        }
+
+        self.nesting -= 1;
+        Ok(row)
+    }
+
+    /// Parse an atom along with any subscript and superscript
+    fn parse_scripted(&mut self) -> Result<String, String> {
+        let (base, limits) = self.parse_atom()?;
+        let mut sub = None;
+        let mut sup = None;
+
+        loop {
+            match self.peek() {
+                Some(Token::Sub) if sub.is_none() => {
+                    self.next();
+                    sub = Some(self.parse_argument()?);
+                }
+                Some(Token::Sup) if sup.is_none() => {
+                    self.next();
+                    sup = Some(self.parse_argument()?);
+                }
+                Some(Token::Sub) => return Err("Double subscript".to_string()),
+                Some(Token::Sup) => return Err("Double superscript".to_string()),
+                _ => break,
+            }
+        }
+
+        let (under, over, under_over) = match limits && self.display {
+            true => ("munder", "mover", "munderover"),
+            false => ("msub", "msup", "msubsup"),
+        };
+
+        Ok(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base, sup),
+            (Some(sub), Some(sup)) => format!("<{0}>{1}{2}{3}</{0}>", under_over, base, sub, sup),
+        })
+    }
+
+    /// Parse a single element, returning it along with whether its scripts are limits
+    fn parse_atom(&mut self) -> Result<(String, bool), String> {
+        let token = match self.peek() {
+            Some(Token::Sub) | Some(Token::Sup) => return Ok(("<mrow></mrow>".to_string(), false)),
+            _ => self.next(),
+        };
+
+        match token {
+            Some(Token::Open) => Ok((self.parse_group()?, false)),
+            Some(Token::Char(c)) if c.is_ascii_digit() || c == '.' => {
+                let mut number = c.to_string();
+                while let Some(Token::Char(c)) = self.tokens.get(self.position) {
+                    if !c.is_ascii_digit() && *c != '.' {
+                        break;
+                    }
+                    number.push(*c);
+                    self.position += 1;
+                }
+                Ok((format!("<mn>{}</mn>", number), false))
+            }
+            Some(Token::Char(c)) => Ok((self.char_element(c), false)),
+            Some(Token::Command(name)) => self.parse_command(&name),
+            Some(Token::Close) => Err("Unexpected }".to_string()),
+            _ => Err("Unexpected end of math".to_string()),
+        }
+    }
+
+    /// Parse the argument of a command or script: a group, or a single character or command
+    fn parse_argument(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Open) => {
+                self.next();
+                self.parse_group()
+            }
+            Some(Token::Char(c)) => {
+                self.next();
+                Ok(match c.is_ascii_digit() {
+                    true => format!("<mn>{}</mn>", c),
+                    false => self.char_element(c),
+                })
+            }
+            Some(Token::Command(name)) if name != "end" && name != "right" => {
+                self.next();
+                Ok(self.parse_command(&name)?.0)
+            }
+            _ => Err("Missing argument".to_string()),
+        }
+    }
+
+    /// Parse the rest of a group whose `{` has been consumed
+    fn parse_group(&mut self) -> Result<String, String> {
+        let row = self.parse_row()?;
+
+        match self.next() {
+            Some(Token::Close) => Ok(format!("<mrow>{}</mrow>", row)),
+            _ => Err("Missing }".to_string()),
+        }
+    }
+
+    /// Read a group as raw text, for `\text` and environment names
+    fn parse_raw_group(&mut self) -> Result<String, String> {
+        if self.next() != Some(Token::Open) {
+            return Err("Missing {".to_string());
+        }
+
+        let mut text = String::new();
+        let mut depth = 0;
+
+        loop {
+            let token = self.tokens.get(self.position).cloned();
+            self.position += 1;
+
+            match token {
+                Some(Token::Close) if depth == 0 => return Ok(text),
+                Some(Token::Close) => {
+                    depth -= 1;
+                    text.push('}');
+                }
+                Some(Token::Open) => {
+                    depth += 1;
+                    text.push('{');
+                }
+                Some(Token::Char(c)) => text.push(c),
+                Some(Token::Space) => text.push(' '),
+                Some(Token::Sub) => text.push('_'),
+                Some(Token::Sup) => text.push('^'),
+                Some(Token::Align) => text.push('&'),
+                Some(Token::NewRow) => text.push_str("\\\\"),
+                Some(Token::Command(name)) if name.len() == 1 && !name.chars().all(char::is_alphabetic) => {
+                    text.push_str(&name)
+                }
+                Some(Token::Command(name)) => {
+                    text.push('\\');
+                    text.push_str(&name);
+                }
+                None => return Err("Missing }".to_string()),
+            }
+        }
+    }
+
+    fn parse_command(&mut self, name: &str) -> Result<(String, bool), String> {
+        if let Some((_, c)) = GREEK.iter().find(|(greek, _)| *greek == name) {
+            return Ok(match c.is_uppercase() {
+                true => (format!("<mi mathvariant=\"normal\">{}</mi>", c), false),
+                false => (format!("<mi>{}</mi>", c), false),
+            });
+        }
+
+        if let Some((_, c)) = IDENTIFIERS.iter().find(|(identifier, _)| *identifier == name) {
+            return Ok((format!("<mi>{}</mi>", c), false));
+        }
+
+        if let Some((_, c)) = OPERATORS.iter().find(|(operator, _)| *operator == name) {
+            return Ok((format!("<mo>{}</mo>", escape(&c.to_string())), false));
+        }
+
+        if let Some((_, c, limits)) = LARGE_OPERATORS.iter().find(|(operator, ..)| *operator == name) {
+            return Ok((format!("<mo largeop=\"true\">{}</mo>", c), *limits));
+        }
+
+        if let Some((_, limits)) = FUNCTIONS.iter().find(|(function, _)| *function == name) {
+            return Ok((format!("<mi>{}</mi>", name), *limits));
+        }
+
+        if let Some((_, c, over)) = ACCENTS.iter().find(|(accent, ..)| *accent == name) {
+            let argument = self.parse_argument()?;
+            return Ok(match over {
+                true => (format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", argument, c), false),
+                false => (format!("<munder accentunder=\"true\">{}<mo>{}</mo></munder>", argument, c), false),
+            });
+        }
+
+        if let Some((_, width)) = SPACES.iter().find(|(space, _)| *space == name) {
+            return Ok((format!("<mspace width=\"{}\"></mspace>", width), false));
+        }
+
+        if let Some((_, variant)) = VARIANTS.iter().find(|(command, _)| *command == name) {
+            let outer = self.variant.replace(variant);
+            let argument = self.parse_argument();
+            self.variant = outer;
+            return Ok((argument?, false));
+        }
+
+        let element = match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let n = self.parse_argument()?;
+                let k = self.parse_argument()?;
+                format!("<mrow><mo>(</mo><mfrac linethickness=\"0\">{}{}</mfrac><mo>)</mo></mrow>", n, k)
+            }
+            "sqrt" => match self.peek() {
+                Some(Token::Char('[')) => {
+                    self.next();
+                    let mut index = String::new();
+                    while self.peek() != Some(Token::Char(']')) {
+                        if self.peek().is_none() {
+                            return Err("Missing ]".to_string());
+                        }
+                        index.push_str(&self.parse_scripted()?);
+                    }
+                    self.next();
+                    format!("<mroot>{}<mrow>{}</mrow></mroot>", self.parse_argument()?, index)
+                }
+                _ => format!("<msqrt>{}</msqrt>", self.parse_argument()?),
+            },
+            "text" | "textrm" | "textit" | "textbf" | "mbox" => {
+                format!("<mtext>{}</mtext>", escape(&self.parse_raw_group()?))
+            }
+            "operatorname" => format!("<mi>{}</mi>", escape(&self.parse_raw_group()?)),
+            "left" => {
+                let open = self.parse_delimiter()?;
+                let row = self.parse_row()?;
+                if self.next() != Some(Token::Command("right".to_string())) {
+                    return Err("\\left without \\right".to_string());
+                }
+                let close = self.parse_delimiter()?;
+                format!(
+                    "<mrow><mo fence=\"true\" form=\"prefix\">{}</mo>{}<mo fence=\"true\" form=\"postfix\">{}</mo></mrow>",
+                    open, row, close
+                )
+            }
+            "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl" | "Bigr" => {
+                format!("<mo>{}</mo>", self.parse_delimiter()?)
+            }
+            "begin" => self.parse_environment()?,
+            "displaystyle" | "textstyle" => String::new(),
+            "right" => return Err("\\right without \\left".to_string()),
+            "end" => return Err("\\end without \\begin".to_string()),
+            _ => return Err(format!("Unknown command \\{}", name)),
+        };
+
+        Ok((element, false))
+    }
+
+    /// Parse the delimiter after `\left`, `\right` or `\big`, where `.` means none
+    fn parse_delimiter(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Char('.')) => Ok(String::new()),
+            Some(Token::Char(c)) => Ok(escape(&c.to_string())),
+            Some(Token::Command(name)) => match OPERATORS.iter().find(|(operator, _)| *operator == name) {
+                Some((_, c)) => Ok(escape(&c.to_string())),
+                None => Err(format!("\\{} is not a delimiter", name)),
+            },
+            _ => Err("Missing delimiter".to_string()),
+        }
+    }
+
+    /// Parse the rest of an environment whose `\begin` has been consumed
+    fn parse_environment(&mut self) -> Result<String, String> {
+        let name = self.parse_raw_group()?;
+
+        let (open, close) = match name.as_str() {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            "matrix" | "smallmatrix" | "array" | "aligned" | "align" | "align*" | "split" | "gathered"
+            | "gather" | "gather*" => ("", ""),
+            _ => return Err(format!("Unknown environment {}", name)),
+        };
+
+        let alignment: Vec<&str> = match name.as_str() {
+            "array" => self
+                .parse_raw_group()?
+                .chars()
+                .filter_map(|c| match c {
+                    'l' => Some("left"),
+                    'c' => Some("center"),
+                    'r' => Some("right"),
+                    _ => None,
+                })
+                .collect(),
+            "cases" => vec!["left"],
+            "aligned" | "align" | "align*" | "split" => vec!["right", "left"],
+            _ => vec!["center"],
+        };
+
+        let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+
+        loop {
+            let cell = self.parse_row()?;
+            rows.last_mut().unwrap().push(cell);
+
+            match self.next() {
+                Some(Token::Align) => {}
+                Some(Token::NewRow) => rows.push(Vec::new()),
+                Some(Token::Command(command)) if command == "end" => break,
+                Some(Token::Close) => return Err("Unexpected }".to_string()),
+                Some(Token::Command(command)) => return Err(format!("Unexpected \\{}", command)),
+                _ => return Err(format!("Missing \\end{{{}}}", name)),
+            }
+        }
+
+        let end = self.parse_raw_group()?;
+        if end != name {
+            return Err(format!("\\begin{{{}}} ended by \\end{{{}}}", name, end));
+        }
+
+        // A trailing `\\` leaves an empty last row
+        if rows.len() > 1 && rows.last().is_some_and(|row| row.len() == 1 && row[0].is_empty()) {
+            rows.pop();
+        }
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let columnalign: Vec<&str> = match name.as_str() {
+            "aligned" | "align" | "align*" | "split" => (0..columns).map(|i| alignment[i % 2]).collect(),
+            _ => alignment,
+        };
+
+        let mut table = String::from("<mtable");
+        if !columnalign.is_empty() {
+            table.push_str(&format!(" columnalign=\"{}\"", columnalign.join(" ")));
+        }
+        if let "aligned" | "align" | "align*" | "split" | "gathered" | "gather" | "gather*" = name.as_str() {
+            table.push_str(" displaystyle=\"true\" columnspacing=\"0em\"");
+        }
+        table.push('>');
+
+        for row in rows {
+            table.push_str("<mtr>");
+            for cell in row {
+                table.push_str(&format!("<mtd><mrow>{}</mrow></mtd>", cell));
+            }
+            table.push_str("</mtr>");
+        }
+        table.push_str("</mtable>");
+
+        Ok(match (open, close) {
+            ("", "") => table,
+            (open, close) => format!(
+                "<mrow><mo fence=\"true\">{}</mo>{}<mo fence=\"true\">{}</mo></mrow>",
+                open, table, close
+            ),
+        })
+    }
+
+    fn char_element(&self, c: char) -> String {
+        if c.is_alphabetic() {
+            return match self.variant {
+                Some(variant) => format!("<mi mathvariant=\"{}\">{}</mi>", variant, c),
+                None => format!("<mi>{}</mi>", c),
+            };
+        }
+
+        let c = match c {
+            '-' => '−',
+            '*' => '∗',
+            '\'' => '′',
+            c => c,
+        };
+
+        format!("<mo>{}</mo>", escape(&c.to_string()))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Convert inline math, keeping only the MathML inside the outer `<mrow>`
+    fn inner(tex: &str) -> String {
+        let mathml = tex_to_mathml(tex, false).unwrap();
+        let start = mathml.find("<semantics><mrow>").unwrap() + "<semantics><mrow>".len();
+        let end = mathml.rfind("</mrow><annotation").unwrap();
+        mathml[start..end].to_string()
+    }
+
+    #[test]
+    fn test_math_element() {
+        assert_eq!(
+            tex_to_mathml("x < 1", true).unwrap(),
+            "<math display=\"block\"><semantics><mrow><mi>x</mi><mo>&lt;</mo><mn>1</mn></mrow>\
+             <annotation encoding=\"application/x-tex\">x &lt; 1</annotation></semantics></math>"
+        );
+        assert!(tex_to_mathml("x", false).unwrap().starts_with("<math><semantics>"));
+    }
+
+    #[test]
+    fn test_fractions() {
+        assert_eq!(inner("\\frac{a}{b}"), "<mfrac><mrow><mi>a</mi></mrow><mrow><mi>b</mi></mrow></mfrac>");
+        assert_eq!(inner("\\frac12"), "<mfrac><mn>1</mn><mn>2</mn></mfrac>");
+        assert_eq!(inner("\\sqrt[3]{x}"), "<mroot><mrow><mi>x</mi></mrow><mrow><mn>3</mn></mrow></mroot>");
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(inner("x^2"), "<msup><mi>x</mi><mn>2</mn></msup>");
+        assert_eq!(inner("x_{i+1}"), "<msub><mi>x</mi><mrow><mi>i</mi><mo>+</mo><mn>1</mn></mrow></msub>");
+        assert_eq!(inner("a_1^{10}"), "<msubsup><mi>a</mi><mn>1</mn><mrow><mn>10</mn></mrow></msubsup>");
+        assert_eq!(inner("\\sum_{i=0}^n"), "<msubsup><mo largeop=\"true\">∑</mo><mrow><mi>i</mi><mo>=</mo><mn>0</mn></mrow><mi>n</mi></msubsup>");
+        assert!(tex_to_mathml("\\sum_{i=0}^n", true).unwrap().contains("<munderover>"));
+        assert_eq!(tex_to_mathml("x^2^3", false), Err("Double superscript".to_string()));
+    }
+
+    #[test]
+    fn test_greek() {
+        assert_eq!(inner("\\alpha + \\Omega"), "<mi>α</mi><mo>+</mo><mi mathvariant=\"normal\">Ω</mi>");
+        assert_eq!(inner("2\\pi r"), "<mn>2</mn><mi>π</mi><mi>r</mi>");
+    }
+
+    #[test]
+    fn test_matrices() {
+        assert_eq!(
+            inner("\\begin{pmatrix} a & b \\\\ c & d \\end{pmatrix}"),
+            "<mrow><mo fence=\"true\">(</mo><mtable columnalign=\"center\">\
+             <mtr><mtd><mrow><mi>a</mi></mrow></mtd><mtd><mrow><mi>b</mi></mrow></mtd></mtr>\
+             <mtr><mtd><mrow><mi>c</mi></mrow></mtd><mtd><mrow><mi>d</mi></mrow></mtd></mtr>\
+             </mtable><mo fence=\"true\">)</mo></mrow>"
+        );
+        assert!(inner("\\begin{bmatrix} 1 \\\\ 2 \\\\ \\end{bmatrix}").matches("<mtr>").count() == 2);
+        assert_eq!(
+            tex_to_mathml("\\begin{pmatrix} a \\end{bmatrix}", false),
+            Err("\\begin{pmatrix} ended by \\end{bmatrix}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_aligned() {
+        assert_eq!(
+            inner("\\begin{aligned} x &= 1 \\\\ y &= 2 \\end{aligned}"),
+            "<mtable columnalign=\"right left\" displaystyle=\"true\" columnspacing=\"0em\">\
+             <mtr><mtd><mrow><mi>x</mi></mrow></mtd><mtd><mrow><mo>=</mo><mn>1</mn></mrow></mtd></mtr>\
+             <mtr><mtd><mrow><mi>y</mi></mrow></mtd><mtd><mrow><mo>=</mo><mn>2</mn></mrow></mtd></mtr>\
+             </mtable>"
+        );
+    }
+
+    #[test]
+    fn test_text_and_fonts() {
+        assert_eq!(inner("\\text{if } x"), "<mtext>if </mtext><mi>x</mi>");
+        assert_eq!(inner("\\mathbb{R}"), "<mrow><mi mathvariant=\"double-struck\">R</mi></mrow>");
+        assert_eq!(inner("\\sin x"), "<mi>sin</mi><mi>x</mi>");
+    }
+
+    #[test]
+    fn test_delimiters() {
+        assert_eq!(
+            inner("\\left( x \\right."),
+            "<mrow><mo fence=\"true\" form=\"prefix\">(</mo><mi>x</mi><mo fence=\"true\" form=\"postfix\"></mo></mrow>"
+        );
+        assert_eq!(inner("\\{ \\langle"), "<mo>{</mo><mo>⟨</mo>");
+        assert_eq!(
+            inner("\\left\\& x \\right<"),
+            "<mrow><mo fence=\"true\" form=\"prefix\">&amp;</mo><mi>x</mi><mo fence=\"true\" form=\"postfix\">&lt;</mo></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(tex_to_mathml("\\frac{a}{b", false), Err("Missing }".to_string()));
+        assert_eq!(tex_to_mathml("a}", false), Err("Unexpected }".to_string()));
+        assert_eq!(tex_to_mathml("\\foo", false), Err("Unknown command \\foo".to_string()));
+        assert_eq!(tex_to_mathml("\\frac{a}", false), Err("Missing argument".to_string()));
+        assert_eq!(tex_to_mathml("\\left( x", false), Err("\\left without \\right".to_string()));
+
+        let nested = |depth: usize| format!("{}x{}", "\\left(".repeat(depth), "\\right)".repeat(depth));
+        assert!(tex_to_mathml(&nested(MAX_NESTING - 1), false).is_ok());
+        assert_eq!(tex_to_mathml(&nested(100_000), false), Err("Math is nested too deeply".to_string()));
+        assert_eq!(tex_to_mathml(&"{".repeat(100_000), false), Err("Math is nested too deeply".to_string()));
+    }
+
+    #[test]
+    fn test_render_math() {
+        let html = "<p>a <code class=\"language-math math-inline\">x &lt; y</code> b</p>\n\
+                    <pre><code class=\"language-math math-display\">\\frac{1}{2}\n</code></pre>";
+        let rendered = render_math(html);
+
+        assert!(rendered.starts_with("<p>a <math><semantics><mrow><mi>x</mi><mo>&lt;</mo><mi>y</mi>"));
+        assert!(rendered.contains("</math> b</p>\n<math display=\"block\"><semantics><mrow><mfrac>"));
+        assert!(!rendered.contains("<code"));
+    }
+
+    #[test]
+    fn test_render_math_errors() {
+        let html = "<code class=\"language-math math-inline\">\\oops &lt;</code>";
+
+        assert_eq!(
+            render_math(html),
+            "<code class=\"math-error text-error\" title=\"Unknown command \\oops\">\\oops &lt;</code>"
+        );
+    }
+}
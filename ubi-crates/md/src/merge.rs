@@ -0,0 +1,129 @@
+use crate::diff::{edits, Edit};
+
+/// A run of lines in a three-way merge
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MergeChunk {
+    /// Lines both sides agree on, or that only one side changed
+    Resolved(Vec<String>),
+    /// Lines both sides changed, in different ways
+    Conflict { base: Vec<String>, ours: Vec<String>, theirs: Vec<String> },
+}
+
+/// Combine two edited versions of `base` line by line.
+///
+/// Changes made by only one side are taken as they are. Where both sides
+/// changed the same lines differently, both versions are kept in a
+/// [`MergeChunk::Conflict`] for the user to choose between.
+pub fn merge(base: &str, ours: &str, theirs: &str) -> Vec<MergeChunk> {
+    let (base, ours, theirs): (Vec<&str>, Vec<&str>, Vec<&str>) = (base.lines().collect(), ours.lines().collect(), theirs.lines().collect());
+    let to_ours = matches(&edits(&base, &ours), base.len());
+    let to_theirs = matches(&edits(&base, &theirs), base.len());
+
+    let mut chunks = Vec::new();
+    let mut resolved: Vec<String> = Vec::new();
+    let (mut i, mut i_ours, mut i_theirs) = (0, 0, 0);
+
+    loop {
+        // The next line of the base that both sides kept
+        let sync = (i..base.len()).find_map(|k| Some((k, to_ours[k]?, to_theirs[k]?)));
+        let (end, end_ours, end_theirs) = sync.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        let (changed_base, changed_ours, changed_theirs) = (&base[i..end], &ours[i_ours..end_ours], &theirs[i_theirs..end_theirs]);
+        if changed_ours == changed_base {
+            resolved.extend(changed_theirs.iter().map(|line| line.to_string()));
+        } else if changed_theirs == changed_base || changed_ours == changed_theirs {
+            resolved.extend(changed_ours.iter().map(|line| line.to_string()));
+        } else {
+            if !resolved.is_empty() {
+                chunks.push(MergeChunk::Resolved(std::mem::take(&mut resolved)));
+            }
+            let owned = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+            chunks.push(MergeChunk::Conflict { base: owned(changed_base), ours: owned(changed_ours), theirs: owned(changed_theirs) });
+        }
+
+        match sync {
+            Some((k, k_ours, k_theirs)) => {
+                resolved.push(base[k].to_string());
+                (i, i_ours, i_theirs) = (k + 1, k_ours + 1, k_theirs + 1);
+            }
+            None => break,
+        }
+    }
+
+    if !resolved.is_empty() {
+        chunks.push(MergeChunk::Resolved(resolved));
+    }
+    chunks
+}
+
+/// Where each line of the base ended up on one side, if it was kept
+fn matches(edits: &[Edit], len: usize) -> Vec<Option<usize>> {
+    let mut matches = vec![None; len];
+    for edit in edits {
+        if let Edit::Same(i, j) = edit {
+            matches[*i] = Some(*j);
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_merge_separate_changes() {
+        let base = "# Title\n\nOne\n\nTwo\n\nThree\n";
+        let ours = "# New title\n\nOne\n\nTwo\n\nThree\n";
+        let theirs = "# Title\n\nOne\n\nTwo\n\nThree\n\nFour\n";
+
+        assert_eq!(merge(base, ours, theirs), vec![MergeChunk::Resolved(lines("# New title\n\nOne\n\nTwo\n\nThree\n\nFour\n"))]);
+        // The same change on both sides is not a conflict
+        assert_eq!(merge(base, ours, ours), vec![MergeChunk::Resolved(lines(ours))]);
+        assert_eq!(merge(base, base, base), vec![MergeChunk::Resolved(lines(base))]);
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let base = "# Title\n\nOne\n\nTwo\n";
+        let ours = "# Title\n\nOne, mine\n\nTwo\n";
+        let theirs = "# Title\n\nOne, theirs\n\nTwo\n\nThree\n";
+
+        assert_eq!(
+            merge(base, ours, theirs),
+            vec![
+                MergeChunk::Resolved(lines("# Title\n\n")),
+                MergeChunk::Conflict { base: lines("One"), ours: lines("One, mine"), theirs: lines("One, theirs") },
+                MergeChunk::Resolved(lines("\nTwo\n\nThree\n")),
+            ]
+        );
+
+        // Lines added in the same place, and a line one side removed and the other changed
+        let base = "a\nb\n";
+        assert_eq!(
+            merge(base, "a\nmine\nb\n", "a\ntheirs\nb\n"),
+            vec![
+                MergeChunk::Resolved(lines("a")),
+                MergeChunk::Conflict { base: vec![], ours: lines("mine"), theirs: lines("theirs") },
+                MergeChunk::Resolved(lines("b")),
+            ]
+        );
+        assert_eq!(
+            merge(base, "a\n", "a\nB\n"),
+            vec![MergeChunk::Resolved(lines("a")), MergeChunk::Conflict { base: lines("b"), ours: vec![], theirs: lines("B") }]
+        );
+    }
+
+    #[test]
+    fn test_merge_without_base() {
+        assert_eq!(merge("", "", "new\n"), vec![MergeChunk::Resolved(lines("new"))]);
+        assert_eq!(
+            merge("", "mine\n", "theirs\n"),
+            vec![MergeChunk::Conflict { base: vec![], ours: lines("mine"), theirs: lines("theirs") }]
+        );
+    }
+}
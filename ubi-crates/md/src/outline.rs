@@ -0,0 +1,169 @@
+use markdown::mdast::Node;
+
+use crate::render::{render, HtmlPolicy, RenderOptions};
+
+/// A heading, along with where it starts in the markdown
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineHeading {
+    pub level: u8,
+    pub text: String,
+    /// The anchor the preview gives the heading, whose element has the id
+    /// [`element_id`](crate::element_id) makes of it
+    pub id: String,
+    /// The line the heading starts on, counting from 1
+    pub line: usize,
+    /// The byte offset the heading starts at
+    pub offset: usize,
+}
+
+/// The headings of a document in order.
+///
+/// Headings come from the syntax tree rather than from scanning lines, so
+/// setext headings and headings nested in lists or block quotes are found,
+/// and `#` lines in code blocks are not.
+///
+/// Ids are taken from the rendered document, so they always match the
+/// preview's, and are given even when heading anchors are off.
+pub fn outline(text: &str, options: &RenderOptions) -> Result<Vec<OutlineHeading>, String> {
+    let tree = markdown::to_mdast(text, &options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+
+    // Math and highlighting come after headings are collected, so they are skipped
+    let options = RenderOptions {
+        heading_anchors: true,
+        math: false,
+        syntax_highlighting: false,
+        source_lines: false,
+        ..options.clone()
+    };
+    let mut ids = render(text, &options)?.headings.into_iter().map(|heading| heading.id);
+
+    let mut headings = Vec::new();
+    collect_headings(&tree, options.html != HtmlPolicy::Escape, &mut ids, &mut headings);
+
+    Ok(headings)
+}
+
+/// Pair the headings of the syntax tree with the rendered ids, skipping the
+/// ids of headings written in raw HTML when it is rendered
+fn collect_headings(node: &Node, raw_html: bool, ids: &mut impl Iterator<Item = String>, headings: &mut Vec<OutlineHeading>) {
+    match node {
+        Node::Heading(heading) => {
+            let text = plain_text(node);
+            let (line, offset) = heading
+                .position
+                .as_ref()
+                .map_or((0, 0), |position| (position.start.line, position.start.offset));
+
+            let id = ids.next().unwrap_or_default();
+            headings.push(OutlineHeading { level: heading.depth, id, text, line, offset });
+            return;
+        }
+        Node::Html(html) if raw_html => ids.take(raw_headings(&html.value)).for_each(drop),
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_headings(child, raw_html, ids, headings);
+    }
+}
+
+/// How many headings raw HTML adds to the rendered document, which only counts `<h1>` to `<h6>` tags without attributes
+fn raw_headings(html: &str) -> usize {
+    (1..=6).map(|level| html.matches(&format!("<h{}>", level)).count()).sum()
+}
+
+/// The text of a node as the preview shows it, leaving out raw HTML and images
+pub fn plain_text(node: &Node) -> String {
+    match node {
+        Node::Text(text) => text.value.clone(),
+        Node::InlineCode(code) => code.value.clone(),
+        Node::InlineMath(math) => math.value.clone(),
+        Node::Html(_) | Node::Image(_) | Node::ImageReference(_) | Node::FootnoteReference(_) => String::new(),
+        _ => node.children().into_iter().flatten().map(plain_text).collect(),
+    }
+}
+
+/// The index of the heading whose section contains the byte offset
+pub fn current_heading(headings: &[OutlineHeading], offset: usize) -> Option<usize> {
+    headings.iter().rposition(|heading| heading.offset <= offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline() {
+        let text = "\
+Title
+=====
+
+Intro
+
+Setext *two*
+------------
+
+- ## In a list
+
+> ### In a quote
+
+```md
+# Not a heading
+```
+
+## Title <span>!</span>
+";
+        let headings = outline(text, &RenderOptions::default()).unwrap();
+        let summary: Vec<(u8, &str, &str, usize)> = headings
+            .iter()
+            .map(|heading| (heading.level, heading.text.as_str(), heading.id.as_str(), heading.line))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (1, "Title", "title", 1),
+                (2, "Setext two", "setext-two", 6),
+                (2, "In a list", "in-a-list", 9),
+                (3, "In a quote", "in-a-quote", 11),
+                (2, "Title !", "title-", 17),
+            ]
+        );
+        assert_eq!(&text[headings[2].offset..headings[2].offset + 5], "## In");
+    }
+
+    #[test]
+    fn test_ids_match_preview() {
+        let text = "# A *b* `c`\n\n## A b c\n\n## Sum $x^2$\n";
+        let options = RenderOptions::default();
+
+        let ids: Vec<String> = outline(text, &options).unwrap().into_iter().map(|heading| heading.id).collect();
+        let rendered: Vec<String> = crate::render(text, &options).unwrap().headings.into_iter().map(|heading| heading.id).collect();
+
+        assert_eq!(ids, rendered);
+    }
+
+    #[test]
+    fn test_ids_match_preview_with_footnotes_and_html() {
+        let text = "# Heading[^1]\n\n# A <b>x</b>\n\n<h2>Raw</h2>\n\n## Raw\n\n[^1]: Note\n";
+        let ids = |html: HtmlPolicy| -> Vec<String> {
+            let options = RenderOptions { html, ..RenderOptions::default() };
+            outline(text, &options).unwrap().into_iter().map(|heading| heading.id).collect()
+        };
+
+        assert_eq!(ids(HtmlPolicy::Escape), vec!["heading1", "a-bxb", "raw"]);
+        // Raw HTML headings are in the preview, so the markdown one after it is numbered
+        assert_eq!(ids(HtmlPolicy::Sanitise), vec!["heading1", "a-x", "raw-1"]);
+        assert_eq!(ids(HtmlPolicy::Trust), vec!["heading1", "a-x", "raw-1"]);
+    }
+
+    #[test]
+    fn test_current_heading() {
+        let headings = outline("intro\n\n# A\n\ntext\n\n# B\n", &RenderOptions::default()).unwrap();
+
+        assert_eq!(current_heading(&headings, 0), None);
+        assert_eq!(current_heading(&headings, 7), Some(0));
+        assert_eq!(current_heading(&headings, 12), Some(0));
+        assert_eq!(current_heading(&headings, 18), Some(1));
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use markdown::{CompileOptions, Constructs, Options, ParseOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::highlight::highlight_code_blocks;
+use crate::math::render_math;
+use crate::sanitise::{sanitise, ID_PREFIX};
+use crate::source_lines::annotate_source_lines;
+
+/// How raw HTML and dangerous link protocols in the markdown are treated
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq)]
+pub enum HtmlPolicy {
+    /// Escape raw HTML so that it shows up as text, and drop `javascript:` style links
+    Escape,
+    /// Keep raw HTML, but strip anything outside an allow-list of tags and attributes
+    Sanitise,
+    /// Pass raw HTML and every link protocol through untouched
+    Trust,
+}
+
+/// Everything that changes how markdown is turned into HTML
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub tables: bool,
+    pub strikethrough: bool,
+    pub task_lists: bool,
+    pub autolinks: bool,
+    pub footnotes: bool,
+    pub math: bool,
+    pub heading_anchors: bool,
+    pub syntax_highlighting: bool,
+    /// Leave YAML and TOML front matter out of the HTML rather than rendering it as markdown
+    pub front_matter: bool,
+    pub html: HtmlPolicy,
+    /// Mark top level elements with the line of markdown they came from. This
+    /// is for the preview to map between the editor and itself, so it is not saved.
+    #[serde(skip)]
+    pub source_lines: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            strikethrough: true,
+            task_lists: true,
+            autolinks: true,
+            footnotes: true,
+            math: true,
+            heading_anchors: true,
+            syntax_highlighting: true,
+            front_matter: true,
+            html: HtmlPolicy::Sanitise,
+            source_lines: false,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// The options of the `markdown` crate these stand for, so other crates parse the same flavour
+    pub fn to_markdown_options(&self) -> Options {
+        let constructs = Constructs {
+            gfm_table: self.tables,
+            gfm_strikethrough: self.strikethrough,
+            gfm_task_list_item: self.task_lists,
+            gfm_autolink_literal: self.autolinks,
+            gfm_footnote_definition: self.footnotes,
+            gfm_label_start_footnote: self.footnotes,
+            math_flow: self.math,
+            math_text: self.math,
+            frontmatter: self.front_matter,
+            ..Constructs::default()
+        };
+        let parse = ParseOptions { constructs, ..ParseOptions::default() };
+
+        let compile = CompileOptions {
+            allow_dangerous_html: self.html != HtmlPolicy::Escape,
+            allow_dangerous_protocol: self.html == HtmlPolicy::Trust,
+            ..CompileOptions::default()
+        };
+
+        Options { parse, compile }
+    }
+}
+
+/// A heading of a rendered document
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    /// The anchor `#` links reach the heading by, empty when heading anchors
+    /// are off. The element's own id is [`element_id`].
+    pub id: String,
+}
+
+/// The HTML of a rendered document, along with what was learned while rendering it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rendered {
+    pub html: String,
+    pub headings: Vec<Heading>,
+}
+
+impl Rendered {
+    /// The text of the first top level heading
+    pub fn title(&self) -> Option<&str> {
+        self.headings
+            .iter()
+            .find(|heading| heading.level == 1)
+            .map(|heading| heading.text.as_str())
+    }
+}
+
+/// Render markdown into HTML.
+///
+/// This is the one place markdown becomes HTML, so the preview, exports and
+/// the backend all agree on the output for the same options.
+pub fn render(text: &str, options: &RenderOptions) -> Result<Rendered, String> {
+    let html = markdown::to_html_with_options(text, &options.to_markdown_options())
+        .map_err(|err| err.to_string())?;
+
+    let html = match options.html {
+        HtmlPolicy::Sanitise => sanitise(&html),
+        HtmlPolicy::Escape | HtmlPolicy::Trust => html,
+    };
+
+    // Headings are collected before math is rendered, so their text keeps the TeX source
+    let mut rendered = annotate_headings(&html, options.heading_anchors, options.html);
+
+    if options.math {
+        rendered.html = render_math(&rendered.html);
+    }
+
+    if options.syntax_highlighting {
+        rendered.html = highlight_code_blocks(&rendered.html);
+    }
+
+    if options.source_lines {
+        rendered.html = annotate_source_lines(&rendered.html, text, options)?;
+    }
+
+    Ok(rendered)
+}
+
+/// The id of the element an anchor leads to. Unless the HTML is trusted, ids
+/// are prefixed like those of raw HTML, so headings such as `# Editor` do not
+/// take the ids of the app's own elements.
+pub fn element_id(anchor: &str, policy: HtmlPolicy) -> String {
+    match policy {
+        HtmlPolicy::Trust => anchor.to_string(),
+        HtmlPolicy::Escape | HtmlPolicy::Sanitise => format!("{}{}", ID_PREFIX, anchor),
+    }
+}
+
+/// Collect the headings of the HTML, giving each an id when anchors are enabled
+fn annotate_headings(html: &str, anchors: bool, policy: HtmlPolicy) -> Rendered {
+    let mut output = String::with_capacity(html.len());
+    let mut headings = Vec::new();
+    let mut slugs = Slugger::default();
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<h") {
+        let level = rest[start + 2..].chars().next().and_then(|c| c.to_digit(10));
+        let open = format!("<h{}>", level.unwrap_or_default());
+
+        let (level, inner_end) = match level {
+            Some(level @ 1..=6) if rest[start..].starts_with(&open) => {
+                match rest[start..].find(&format!("</h{}>", level)) {
+                    Some(end) => (level as u8, start + end),
+                    None => break,
+                }
+            }
+            _ => {
+                output.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                continue;
+            }
+        };
+
+        let inner = &rest[start + open.len()..inner_end];
+        let text = decode_entities(&strip_tags(inner));
+        let id = if anchors { slugs.slug(&text) } else { String::new() };
+
+        output.push_str(&rest[..start]);
+        if anchors {
+            output.push_str(&format!("<h{} id=\"{}\">", level, element_id(&id, policy)));
+        } else {
+            output.push_str(&open);
+        }
+        output.push_str(inner);
+
+        headings.push(Heading { level, text, id });
+        rest = &rest[inner_end..];
+    }
+
+    output.push_str(rest);
+
+    Rendered { html: output, headings }
+}
+
+/// Generates GitHub style heading slugs, numbering repeats so every slug is unique
+#[derive(Default)]
+pub struct Slugger {
+    seen: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn slug(&mut self, text: &str) -> String {
+        let base = slugify(text);
+        let mut slug = base.clone();
+
+        while self.seen.contains_key(&slug) {
+            let count = self.seen.entry(base.clone()).or_default();
+            *count += 1;
+            slug = format!("{}-{}", base, count);
+        }
+
+        self.seen.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// Lowercase the text, drop punctuation and turn spaces into dashes
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+pub(crate) fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_gfm() {
+        let rendered = render("| a |\n| - |\n| ~~b~~ |", &RenderOptions::default()).unwrap();
+
+        assert!(rendered.html.contains("<table>"));
+        assert!(rendered.html.contains("<del>b</del>"));
+    }
+
+    #[test]
+    fn test_disabled_extensions() {
+        let options = RenderOptions { tables: false, strikethrough: false, ..RenderOptions::default() };
+        let rendered = render("| a |\n| - |\n\n~~b~~", &options).unwrap();
+
+        assert!(!rendered.html.contains("<table>"));
+        assert!(!rendered.html.contains("<del>"));
+    }
+
+    #[test]
+    fn test_html_policy() {
+        let text = "<div>hi</div>\n\n[x](javascript:alert(1))";
+
+        let options = RenderOptions { html: HtmlPolicy::Trust, ..RenderOptions::default() };
+        let trusted = render(text, &options).unwrap();
+        assert!(trusted.html.contains("<div>hi</div>"));
+        assert!(trusted.html.contains("javascript:"));
+
+        let options = RenderOptions { html: HtmlPolicy::Escape, ..RenderOptions::default() };
+        let escaped = render(text, &options).unwrap();
+        assert!(escaped.html.contains("&lt;div&gt;"));
+        assert!(!escaped.html.contains("javascript:"));
+
+        let sanitised = render("<div onclick=\"x()\">hi</div>\n\n<iframe src=\"x\"></iframe>", &RenderOptions::default()).unwrap();
+        assert_eq!(sanitised.html, "<div>hi</div>\n");
+    }
+
+    #[test]
+    fn test_heading_anchors() {
+        let rendered = render("# Hello, *World*!\n\n## Hello World\n\n## Hello World", &RenderOptions::default()).unwrap();
+
+        assert!(rendered.html.contains("<h1 id=\"user-content-hello-world\">Hello, <em>World</em>!</h1>"));
+        assert!(rendered.html.contains("<h2 id=\"user-content-hello-world-1\">Hello World</h2>"));
+        assert_eq!(
+            rendered.headings.iter().map(|heading| heading.id.as_str()).collect::<Vec<&str>>(),
+            vec!["hello-world", "hello-world-1", "hello-world-2"]
+        );
+        assert_eq!(rendered.title(), Some("Hello, World!"));
+    }
+
+    #[test]
+    fn test_heading_ids_do_not_clash_with_the_app() {
+        let text = "# Editor\n\n# Drawer input\n";
+        for html in [HtmlPolicy::Escape, HtmlPolicy::Sanitise] {
+            let rendered = render(text, &RenderOptions { html, ..RenderOptions::default() }).unwrap();
+            assert!(!rendered.html.contains("id=\"editor\""));
+            assert!(rendered.html.contains("<h1 id=\"user-content-editor\">Editor</h1>"));
+            assert!(rendered.html.contains("<h1 id=\"user-content-drawer-input\">"));
+            // Links still reach them by the plain anchor
+            assert_eq!(rendered.headings[0].id, "editor");
+        }
+
+        let trusted = render(text, &RenderOptions { html: HtmlPolicy::Trust, ..RenderOptions::default() }).unwrap();
+        assert!(trusted.html.contains("<h1 id=\"editor\">Editor</h1>"));
+        assert_eq!(element_id("editor", HtmlPolicy::Sanitise), "user-content-editor");
+    }
+
+    #[test]
+    fn test_slugger() {
+        let mut slugger = Slugger::default();
+
+        assert_eq!(slugger.slug("Foo 1"), "foo-1");
+        assert_eq!(slugger.slug("Foo"), "foo");
+        assert_eq!(slugger.slug("Foo"), "foo-2");
+        assert_eq!(slugger.slug("Café & Crème_brûlée"), "café--crème_brûlée");
+    }
+
+    #[test]
+    fn test_without_heading_anchors() {
+        let options = RenderOptions { heading_anchors: false, ..RenderOptions::default() };
+        let rendered = render("### A & B\n\n<hr>", &options).unwrap();
+
+        assert_eq!(rendered.html, "<h3>A &amp; B</h3>\n<hr>");
+        assert_eq!(rendered.headings, vec![Heading { level: 3, text: "A & B".to_string(), id: String::new() }]);
+    }
+
+    #[test]
+    fn test_syntax_highlighting() {
+        let text = "```rust\nlet x = 1;\n```";
+
+        assert!(render(text, &RenderOptions::default()).unwrap().html.contains("hl-keyword"));
+
+        let options = RenderOptions { syntax_highlighting: false, ..RenderOptions::default() };
+        assert_eq!(render(text, &options).unwrap().html, "<pre><code class=\"language-rust\">let x = 1;\n</code></pre>");
+    }
+
+    #[test]
+    fn test_math() {
+        let rendered = render("$x$\n\n$$\n\\alpha\n$$", &RenderOptions::default()).unwrap();
+        assert!(rendered.html.starts_with("<p><math><semantics><mrow><mi>x</mi>"));
+        assert!(rendered.html.contains("<math display=\"block\"><semantics><mrow><mi>α</mi>"));
+
+        let rendered = render("# Energy $E = mc^2$", &RenderOptions::default()).unwrap();
+        assert_eq!(rendered.title(), Some("Energy E = mc^2"));
+
+        let options = RenderOptions { math: false, ..RenderOptions::default() };
+        assert_eq!(render("$x$", &options).unwrap().html, "<p>$x$</p>");
+    }
+}
@@ -0,0 +1,432 @@
+/// Tags that are kept, along with the attributes each may keep on top of [`GLOBAL_ATTRIBUTES`]
+static ALLOWED_TAGS: &[(&str, &[&str])] = &[
+    ("a", &["href", "name", "target", "rel"]),
+    ("abbr", &[]),
+    ("b", &[]),
+    ("blockquote", &["cite"]),
+    ("br", &[]),
+    ("code", &[]),
+    ("dd", &[]),
+    ("del", &[]),
+    ("details", &["open"]),
+    ("div", &[]),
+    ("dl", &[]),
+    ("dt", &[]),
+    ("em", &[]),
+    ("h1", &[]),
+    ("h2", &[]),
+    ("h3", &[]),
+    ("h4", &[]),
+    ("h5", &[]),
+    ("h6", &[]),
+    ("hr", &[]),
+    ("i", &[]),
+    ("img", &["src", "alt", "width", "height"]),
+    ("input", &["type", "checked", "disabled"]),
+    ("ins", &[]),
+    ("kbd", &[]),
+    ("li", &["value"]),
+    ("mark", &[]),
+    ("ol", &["start", "type"]),
+    ("p", &[]),
+    ("pre", &[]),
+    ("q", &["cite"]),
+    ("s", &[]),
+    ("section", &[]),
+    ("small", &[]),
+    ("span", &[]),
+    ("strong", &[]),
+    ("sub", &[]),
+    ("summary", &[]),
+    ("sup", &[]),
+    ("table", &[]),
+    ("tbody", &[]),
+    ("td", &["align", "colspan", "rowspan"]),
+    ("tfoot", &[]),
+    ("th", &["align", "colspan", "rowspan"]),
+    ("thead", &[]),
+    ("tr", &[]),
+    ("u", &[]),
+    ("ul", &[]),
+];
+
+/// Attributes that any allowed tag may keep. Ids and classes are handled
+/// apart, as the document would otherwise share them with the app.
+static GLOBAL_ATTRIBUTES: &[&str] = &["title", "lang", "dir", "align"];
+
+/// Given to every id, so a document cannot clobber the app's elements, as on GitHub
+pub static ID_PREFIX: &str = "user-content-";
+
+/// The classes markdown rendering gives its own elements, along with
+/// `language-*`. Any other class could dress a document up as the app's UI.
+static ALLOWED_CLASSES: &[&str] = &["math-inline", "math-display", "footnotes", "sr-only"];
+
+/// Attributes holding a URL, which must use one of [`ALLOWED_PROTOCOLS`] or be relative
+static URL_ATTRIBUTES: &[&str] = &["href", "src", "cite"];
+
+static ALLOWED_PROTOCOLS: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// Tags whose content is dropped along with the tag, rather than kept as text
+static DROPPED_CONTENT_TAGS: &[&str] = &[
+    "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "template", "noscript",
+    "noembed", "textarea", "title", "svg", "math", "select",
+];
+
+/// Sanitise HTML against an allow-list of tags and attributes.
+///
+/// Disallowed tags are removed but their text is kept, except for tags such as
+/// `<script>` and `<iframe>` whose content is removed too. Event handlers,
+/// inline styles and URLs with protocols such as `javascript:` never survive.
+pub fn sanitise(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = match comment.find("-->") {
+                Some(end) => &comment[end + 3..],
+                None => "",
+            };
+            continue;
+        }
+
+        let tag = match parse_tag(rest) {
+            Some(tag) => tag,
+            None => {
+                output.push_str("&lt;");
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        rest = &rest[tag.len..];
+
+        if !tag.closing && DROPPED_CONTENT_TAGS.contains(&tag.name.as_str()) {
+            if !tag.self_closing {
+                rest = skip_past_closing_tag(rest, &tag.name);
+            }
+            continue;
+        }
+
+        let allowed = match ALLOWED_TAGS.iter().find(|(name, _)| *name == tag.name) {
+            Some((_, allowed)) => *allowed,
+            None => continue,
+        };
+
+        if tag.closing {
+            output.push_str(&format!("</{}>", tag.name));
+            continue;
+        }
+
+        output.push('<');
+        output.push_str(&tag.name);
+
+        for (name, value) in &tag.attributes {
+            let value = match name.as_str() {
+                "id" => prefix_id(value),
+                "class" => allowed_classes(value),
+                name if GLOBAL_ATTRIBUTES.contains(&name) || allowed.contains(&name) => Some(value.clone()),
+                _ => None,
+            };
+            let Some(value) = value else {
+                continue;
+            };
+
+            if URL_ATTRIBUTES.contains(&name.as_str()) && !is_safe_url(&value) {
+                continue;
+            }
+
+            output.push_str(&format!(" {}=\"{}\"", name, escape_attribute(&value)));
+        }
+
+        if tag.self_closing {
+            output.push_str(" /");
+        }
+        output.push('>');
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Ids the markdown renderer made for footnotes already have the prefix
+fn prefix_id(id: &str) -> Option<String> {
+    match id.trim() {
+        "" => None,
+        id if id.starts_with(ID_PREFIX) => Some(id.to_string()),
+        id => Some(format!("{}{}", ID_PREFIX, id)),
+    }
+}
+
+fn allowed_classes(classes: &str) -> Option<String> {
+    let classes: Vec<&str> = classes
+        .split_ascii_whitespace()
+        .filter(|class| class.starts_with("language-") || ALLOWED_CLASSES.contains(class))
+        .collect();
+
+    match classes.is_empty() {
+        true => None,
+        false => Some(classes.join(" ")),
+    }
+}
+
+struct Tag {
+    name: String,
+    attributes: Vec<(String, String)>,
+    closing: bool,
+    self_closing: bool,
+    /// The length of the tag in bytes, from `<` to `>`
+    len: usize,
+}
+
+/// Parse a start or end tag at the start of the input
+fn parse_tag(input: &str) -> Option<Tag> {
+    let bytes = input.as_bytes();
+    let mut i = 1;
+
+    let closing = bytes.get(i) == Some(&b'/');
+    if closing {
+        i += 1;
+    }
+
+    let name_start = i;
+    if !bytes.get(i)?.is_ascii_alphabetic() {
+        return None;
+    }
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+        i += 1;
+    }
+    let name = input[name_start..i].to_ascii_lowercase();
+
+    let mut attributes = Vec::new();
+    let mut self_closing = false;
+
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        match bytes.get(i)? {
+            b'>' => break,
+            b'/' => {
+                self_closing = true;
+                i += 1;
+                continue;
+            }
+            _ => self_closing = false,
+        }
+
+        let attribute_start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'=' | b'>' | b'/') {
+            i += 1;
+        }
+        let attribute = input[attribute_start..i].to_ascii_lowercase();
+
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        let mut value = String::new();
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+
+            match bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let end = input[i + 1..].find(*quote as char)?;
+                    value = input[i + 1..i + 1 + end].to_string();
+                    i += end + 2;
+                }
+                _ => {
+                    let value_start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    value = input[value_start..i].to_string();
+                }
+            }
+        }
+
+        if !attribute.is_empty() {
+            attributes.push((attribute, decode_entities(&value)));
+        }
+    }
+
+    Some(Tag { name, attributes, closing, self_closing, len: i + 1 })
+}
+
+/// Skip to just after the end tag with the given name, or to the end of the input
+fn skip_past_closing_tag<'a>(input: &'a str, name: &str) -> &'a str {
+    let lowercase = input.to_ascii_lowercase();
+    let closing = format!("</{}", name);
+
+    match lowercase.find(&closing) {
+        Some(start) => match input[start..].find('>') {
+            Some(end) => &input[start + end + 1..],
+            None => "",
+        },
+        None => "",
+    }
+}
+
+/// Whether a URL is relative or uses an allowed protocol.
+///
+/// Control characters and whitespace are ignored, as browsers do, so
+/// `java\tscript:` is caught as well.
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => ALLOWED_PROTOCOLS.contains(&&url[..i]),
+        _ => true,
+    }
+}
+
+/// Decode character references, so that encoded protocols such as `&#106;avascript:` are seen through
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "colon" => Some(':'),
+            "tab" => Some('\t'),
+            "newline" => Some('\n'),
+            _ => match entity.strip_prefix('#') {
+                Some(number) => match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => number.parse().ok(),
+                }
+                .and_then(char::from_u32),
+                None => None,
+            },
+        };
+
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_allowed_markup() {
+        let html = "<p>Hi <a href=\"https://example.com\" title=\"Site\">there</a></p>\n<img src=\"a.png\" alt=\"A\" />";
+        assert_eq!(sanitise(html), html);
+    }
+
+    #[test]
+    fn test_javascript_urls() {
+        assert_eq!(sanitise("<a href=\"javascript:alert(1)\">x</a>"), "<a>x</a>");
+        assert_eq!(sanitise("<a href=\"JaVaScRiPt:alert(1)\">x</a>"), "<a>x</a>");
+        assert_eq!(sanitise("<a href=\"java\tscript:alert(1)\">x</a>"), "<a>x</a>");
+        assert_eq!(sanitise("<a href=\"&#106;avascript&colon;alert(1)\">x</a>"), "<a>x</a>");
+        assert_eq!(sanitise("<img src=\"data:text/html,<script>\">"), "<img>");
+        assert_eq!(sanitise("<a href=\"/docs?a=b:c\">x</a>"), "<a href=\"/docs?a=b:c\">x</a>");
+    }
+
+    #[test]
+    fn test_event_handlers() {
+        assert_eq!(sanitise("<img src=x onerror=alert(1)>"), "<img src=\"x\">");
+        assert_eq!(sanitise("<p onclick='steal()' style=\"color: red\">a</p>"), "<p>a</p>");
+        assert_eq!(sanitise("<div/onmouseover=\"x()\">a</div>"), "<div>a</div>");
+    }
+
+    #[test]
+    fn test_iframes() {
+        assert_eq!(sanitise("a<iframe src=\"https://evil.example\"></iframe>b"), "ab");
+        assert_eq!(sanitise("a<IFRAME srcdoc=\"<script>x()</script>\">c</IFrame>b"), "ab");
+        assert_eq!(sanitise("a<iframe src=x />b"), "ab");
+    }
+
+    #[test]
+    fn test_dropped_tags() {
+        assert_eq!(sanitise("<script>alert(1)</script><p>ok</p>"), "<p>ok</p>");
+        assert_eq!(sanitise("<style>body{}</style>text"), "text");
+        assert_eq!(sanitise("<blink>text</blink>"), "text");
+        assert_eq!(sanitise("<!-- <script>x()</script> -->text"), "text");
+        assert_eq!(sanitise("<script>unclosed"), "");
+    }
+
+    #[test]
+    fn test_stray_angle_brackets() {
+        assert_eq!(sanitise("1 < 2 <3"), "1 &lt; 2 &lt;3");
+        assert_eq!(sanitise("<a href=\"x\"\">"), "<a href=\"x\">");
+        assert_eq!(sanitise("<a title=\"unclosed>x"), "&lt;a title=\"unclosed>x");
+    }
+
+    #[test]
+    fn test_ids_and_classes() {
+        assert_eq!(
+            sanitise("<div id=\"app\" class=\"modal modal-open fixed\">x</div>"),
+            "<div id=\"user-content-app\">x</div>"
+        );
+        assert_eq!(
+            sanitise("<code class=\"btn language-rust\">x</code><p class=\"\" id=\" \">y</p>"),
+            "<code class=\"language-rust\">x</code><p>y</p>"
+        );
+        // Footnotes as the markdown renderer writes them
+        let footnotes = "<section class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\
+                         <li id=\"user-content-fn-1\">x</li></section>";
+        assert_eq!(
+            sanitise(footnotes),
+            "<section class=\"footnotes\"><h2 id=\"user-content-footnote-label\" class=\"sr-only\">Footnotes</h2>\
+             <li id=\"user-content-fn-1\">x</li></section>"
+        );
+    }
+
+    #[test]
+    fn test_attribute_values_are_escaped() {
+        assert_eq!(
+            sanitise("<span title='\"><script>'>x</span>"),
+            "<span title=\"&quot;&gt;&lt;script&gt;\">x</span>"
+        );
+    }
+}
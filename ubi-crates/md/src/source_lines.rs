@@ -0,0 +1,214 @@
+use markdown::mdast::Node;
+
+use crate::render::RenderOptions;
+
+/// Elements that never have children, so never need closing
+static VOID_ELEMENTS: &[&str] = &["area", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "wbr"];
+
+/// A top level block of the markdown, with the line it starts on
+struct Block {
+    tags: &'static [&'static str],
+    line: usize,
+}
+
+/// Give every top level element of rendered HTML a `data-line` attribute with
+/// the source line of the block it came from.
+///
+/// Elements are paired with blocks of the syntax tree in order by their tag, so
+/// elements that came from raw HTML are skipped over rather than shifting
+/// every line after them.
+pub(crate) fn annotate_source_lines(html: &str, text: &str, options: &RenderOptions) -> Result<String, String> {
+    let tree = markdown::to_mdast(text, &options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+    let mut blocks = tree.children().into_iter().flatten().filter_map(block).peekable();
+
+    let mut output = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('>') {
+            Some(end) => end + 1,
+            None => break,
+        };
+        let tag = &rest[..end];
+        let closing = tag.starts_with("</");
+        let name: String = tag
+            .trim_start_matches("</")
+            .trim_start_matches('<')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if name.is_empty() {
+            output.push_str(tag);
+        } else if closing {
+            if let Some(i) = open.iter().rposition(|open| *open == name) {
+                open.truncate(i);
+            }
+            output.push_str(tag);
+        } else {
+            let top_level = open.is_empty();
+
+            match blocks.peek() {
+                Some(block) if top_level && block.tags.contains(&name.as_str()) => {
+                    let name_end = tag.find(|c: char| !c.is_ascii_alphanumeric() && c != '<').unwrap_or(tag.len());
+                    output.push_str(&tag[..name_end]);
+                    output.push_str(&format!(" data-line=\"{}\"", block.line));
+                    output.push_str(&tag[name_end..]);
+                    blocks.next();
+                }
+                _ => output.push_str(tag),
+            }
+
+            if !VOID_ELEMENTS.contains(&name.as_str()) && !tag.ends_with("/>") {
+                open.push(name);
+            }
+        }
+
+        rest = &rest[end..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// The tags a block can be rendered as, or `None` when it renders nothing of its own
+fn block(node: &Node) -> Option<Block> {
+    let tags: &'static [&'static str] = match node {
+        Node::Paragraph(_) => &["p"],
+        Node::Heading(heading) => match heading.depth {
+            1 => &["h1"],
+            2 => &["h2"],
+            3 => &["h3"],
+            4 => &["h4"],
+            5 => &["h5"],
+            _ => &["h6"],
+        },
+        Node::ThematicBreak(_) => &["hr"],
+        Node::Blockquote(_) => &["blockquote"],
+        Node::List(list) if list.ordered => &["ol"],
+        Node::List(_) => &["ul"],
+        Node::Code(_) => &["pre"],
+        Node::Math(_) => &["math", "pre"],
+        Node::Table(_) => &["table"],
+        _ => return None,
+    };
+
+    let line = node.position()?.start.line;
+    Some(Block { tags, line })
+}
+
+/// The byte offset of the start of a line, counting from 1
+pub fn line_offset(text: &str, line: usize) -> usize {
+    match line {
+        0 | 1 => 0,
+        _ => text
+            .match_indices('\n')
+            .nth(line - 2)
+            .map_or(text.len(), |(i, _)| i + 1),
+    }
+}
+
+/// The line containing a byte offset, counting from 1
+pub fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+/// The column of a byte offset within its line in characters, counting from 1
+pub fn column_at(text: &str, offset: usize) -> usize {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    before[line_start..].chars().count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render;
+
+    fn lines(html: &str) -> Vec<(String, usize)> {
+        html.match_indices(" data-line=\"")
+            .map(|(i, attribute)| {
+                let tag_start = html[..i].rfind('<').unwrap() + 1;
+                let value = &html[i + attribute.len()..];
+                let line = value[..value.find('"').unwrap()].parse().unwrap();
+                (html[tag_start..i].to_string(), line)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let text = "\
+# Title
+
+Some
+paragraph
+
+- a
+- b
+
+> quote
+
+---
+
+```rust
+fn main() {}
+```
+
+| a |
+| - |
+| b |
+";
+        let options = RenderOptions { source_lines: true, ..RenderOptions::default() };
+        let html = render(text, &options).unwrap().html;
+
+        assert_eq!(
+            lines(&html),
+            vec![
+                ("h1".to_string(), 1),
+                ("p".to_string(), 3),
+                ("ul".to_string(), 6),
+                ("blockquote".to_string(), 9),
+                ("hr".to_string(), 11),
+                ("pre".to_string(), 13),
+                ("table".to_string(), 17),
+            ]
+        );
+        assert!(html.contains("<h1 data-line=\"1\" id=\"user-content-title\">"));
+        assert!(html.contains("<pre data-line=\"13\"><code class=\"language-rust\"><span"));
+    }
+
+    #[test]
+    fn test_raw_html_is_skipped() {
+        let text = "<div>\n<p>raw</p>\n</div>\n\ntext\n\n$$\nx\n$$\n";
+        let options = RenderOptions { html: crate::HtmlPolicy::Trust, source_lines: true, ..RenderOptions::default() };
+        let html = render(text, &options).unwrap().html;
+
+        assert_eq!(lines(&html), vec![("p".to_string(), 5), ("math".to_string(), 7)]);
+    }
+
+    #[test]
+    fn test_without_source_lines() {
+        assert!(!render("text", &RenderOptions::default()).unwrap().html.contains("data-line"));
+    }
+
+    #[test]
+    fn test_line_offsets() {
+        let text = "a\nbc\n\nd";
+
+        assert_eq!(line_offset(text, 1), 0);
+        assert_eq!(line_offset(text, 2), 2);
+        assert_eq!(line_offset(text, 4), 6);
+        assert_eq!(line_offset(text, 9), text.len());
+        assert_eq!(line_at(text, 3), 2);
+        assert_eq!(line_at(text, 6), 4);
+        assert_eq!(column_at(text, 0), 1);
+        assert_eq!(column_at(text, 4), 3);
+        assert_eq!(column_at("é\nxé", 6), 3);
+    }
+}
@@ -0,0 +1,163 @@
+use markdown::mdast::Node;
+
+use crate::render::RenderOptions;
+
+/// The reading speed the reading time is estimated at
+pub const WORDS_PER_MINUTE: usize = 200;
+
+/// Counts of what a reader sees in a document
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub words: usize,
+    pub characters: usize,
+    /// Lines of markdown, as the editor shows them, so a trailing newline starts an empty last line
+    pub lines: usize,
+    pub paragraphs: usize,
+}
+
+impl Stats {
+    /// The estimated minutes to read the document, rounded up
+    pub fn reading_time(&self) -> usize {
+        self.words / WORDS_PER_MINUTE + usize::from(self.words % WORDS_PER_MINUTE != 0)
+    }
+}
+
+/// Count the words, characters, lines and paragraphs of markdown.
+///
+/// Words and characters are counted over the text the preview shows, so link
+/// URLs, code fences, HTML tags and front matter are left out, while the
+/// contents of code blocks and math are counted.
+pub fn stats(text: &str, options: &RenderOptions) -> Result<Stats, String> {
+    let tree = markdown::to_mdast(text, &options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+
+    let mut visible = Visible::default();
+    visible.collect(&tree);
+
+    let words = visible
+        .text
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count();
+
+    Ok(Stats {
+        words,
+        characters: visible.characters,
+        lines: text.matches('\n').count() + 1,
+        paragraphs: visible.paragraphs,
+    })
+}
+
+/// The visible text of a document, with blocks kept apart so their words do not run together
+#[derive(Default)]
+struct Visible {
+    text: String,
+    characters: usize,
+    paragraphs: usize,
+}
+
+impl Visible {
+    fn collect(&mut self, node: &Node) {
+        match node {
+            Node::Text(text) => self.push(&text.value),
+            Node::InlineCode(code) => self.push(&code.value),
+            Node::InlineMath(math) => self.push(&math.value),
+            Node::Code(code) => self.push(&code.value),
+            Node::Math(math) => self.push(&math.value),
+            Node::Break(_) => self.push(" "),
+            Node::Html(_) | Node::Image(_) | Node::ImageReference(_) | Node::Definition(_) | Node::Yaml(_) | Node::Toml(_) => {}
+            _ => {
+                for child in node.children().into_iter().flatten() {
+                    self.collect(child);
+                }
+            }
+        }
+
+        if let Node::Paragraph(_) = node {
+            self.paragraphs += 1;
+        }
+
+        // Blocks end with a separator that is not counted as a character
+        if !is_inline(node) {
+            self.text.push('\n');
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.text.push_str(text);
+        self.characters += text.chars().count();
+    }
+}
+
+fn is_inline(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::Text(_)
+            | Node::InlineCode(_)
+            | Node::InlineMath(_)
+            | Node::Emphasis(_)
+            | Node::Strong(_)
+            | Node::Delete(_)
+            | Node::Link(_)
+            | Node::LinkReference(_)
+            | Node::Image(_)
+            | Node::ImageReference(_)
+            | Node::FootnoteReference(_)
+            | Node::Break(_)
+            | Node::Html(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(text: &str) -> Stats {
+        stats(text, &RenderOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_stats() {
+        let text = "\
+# A *short* title
+
+Some **bold**text and a [link](https://example.com/a-very-long-url \"Title\").
+
+- one
+- two <span class=\"x\">three</span>
+
+```rust
+let x = 1;
+```
+";
+        let stats = count(text);
+
+        assert_eq!(stats.words, 14);
+        assert_eq!(
+            stats.characters,
+            "A short title".len() + "Some boldtext and a link.".len() + "onetwo three".len() + "let x = 1;".len()
+        );
+        assert_eq!(stats.lines, 11);
+        assert_eq!(stats.paragraphs, 3);
+    }
+
+    #[test]
+    fn test_markup_is_ignored() {
+        assert_eq!(count("![alt](image.png)\n\n<div>\n\nhidden?\n\n</div>\n").words, 1);
+        assert_eq!(count("---\ntitle: A long title\n---\n\nBody\n").words, 1);
+        assert_eq!(count("[ref][1]\n\n[1]: https://example.com\n").words, 1);
+        assert_eq!(count("a - b — c").words, 3);
+        assert_eq!(count("").words, 0);
+        assert_eq!(count("").lines, 1);
+        assert_eq!(count("a\r\nb").lines, 2);
+    }
+
+    #[test]
+    fn test_reading_time() {
+        let words = |words: usize| Stats { words, ..Stats::default() };
+
+        assert_eq!(words(0).reading_time(), 0);
+        assert_eq!(words(1).reading_time(), 1);
+        assert_eq!(words(WORDS_PER_MINUTE).reading_time(), 1);
+        assert_eq!(words(WORDS_PER_MINUTE + 1).reading_time(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use markdown::mdast::Node;
+
+use crate::outline::{outline, OutlineHeading};
+use crate::render::RenderOptions;
+
+pub const TOC_START: &str = "<!-- toc -->";
+pub const TOC_END: &str = "<!-- /toc -->";
+
+/// A nested list linking to each heading.
+///
+/// Nesting follows the heading levels, so skipping a level (a `###` straight
+/// after a `#`) only indents by one.
+pub fn toc(headings: &[OutlineHeading]) -> String {
+    let mut list = String::new();
+    let mut parents: Vec<u8> = Vec::new();
+
+    for heading in headings {
+        while parents.last().is_some_and(|level| *level >= heading.level) {
+            parents.pop();
+        }
+
+        list.push_str(&"  ".repeat(parents.len()));
+        list.push_str(&format!("- [{}](#{})\n", escape_link_text(&heading.text), heading.id));
+
+        parents.push(heading.level);
+    }
+
+    list
+}
+
+/// Refresh the table of contents between the toc markers, or insert one at
+/// the start of the line containing `offset` when the document has none.
+///
+/// Returns the new text, along with where `offset` is in it, so a caret after
+/// the table of contents stays next to the same text.
+pub fn insert_or_update_toc(text: &str, offset: usize, options: &RenderOptions) -> Result<(String, usize), String> {
+    let block = format!("{}\n{}{}", TOC_START, toc(&outline(text, options)?), TOC_END);
+    let offset = offset.min(text.len());
+
+    let (replaced, inserted) = match existing_toc(text, options)? {
+        Some(existing) => (existing, block),
+        None => {
+            let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+            let after = &text[line_start..];
+            let separator = if after.is_empty() || after.starts_with('\n') { "\n" } else { "\n\n" };
+            (line_start..line_start, block + separator)
+        }
+    };
+
+    let offset = match offset {
+        offset if offset < replaced.start => offset,
+        offset if offset >= replaced.end => offset - replaced.len() + inserted.len(),
+        _ => replaced.start,
+    };
+
+    Ok(([&text[..replaced.start], &inserted, &text[replaced.end..]].concat(), offset))
+}
+
+/// The table of contents from its first start marker to the end marker after it, if there is one.
+///
+/// Markers are found in the syntax tree, so ones in code blocks and code spans are left alone.
+fn existing_toc(text: &str, options: &RenderOptions) -> Result<Option<Range<usize>>, String> {
+    let tree = markdown::to_mdast(text, &options.to_markdown_options().parse).map_err(|err| err.to_string())?;
+
+    let mut html = Vec::new();
+    collect_html(&tree, &mut html);
+
+    let Some((_, start)) = html.iter().find(|(value, _)| *value == TOC_START) else {
+        return Ok(None);
+    };
+    match html.iter().find(|(value, range)| *value == TOC_END && range.start >= start.end) {
+        Some((_, end)) => Ok(Some(start.start..end.end)),
+        None => Err(format!("The table of contents is missing its closing {} marker", TOC_END)),
+    }
+}
+
+fn collect_html<'a>(node: &'a Node, html: &mut Vec<(&'a str, Range<usize>)>) {
+    if let Node::Html(raw) = node {
+        if let Some(position) = &raw.position {
+            html.push((raw.value.trim(), position.start.offset..position.end.offset));
+        }
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_html(child, html);
+    }
+}
+
+fn escape_link_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DOC: &str = "# Design\n\n## Goals\n\n### Non-goals\n\n## Goals\n\n#### Deep [draft]\n\n```md\n# Not a heading\n```\n";
+
+    #[test]
+    fn test_toc() {
+        let headings = outline(DOC, &RenderOptions::default()).unwrap();
+
+        assert_eq!(
+            toc(&headings),
+            "\
+- [Design](#design)
+  - [Goals](#goals)
+    - [Non-goals](#non-goals)
+  - [Goals](#goals-1)
+    - [Deep \\[draft\\]](#deep-draft)
+"
+        );
+    }
+
+    #[test]
+    fn test_insert_toc() {
+        let text = "# A\n\nIntro\n## B\n";
+        let offset = text.find("Intro").unwrap() + 2;
+
+        let (inserted, moved) = insert_or_update_toc(text, offset, &RenderOptions::default()).unwrap();
+
+        assert_eq!(inserted, "# A\n\n<!-- toc -->\n- [A](#a)\n  - [B](#b)\n<!-- /toc -->\n\nIntro\n## B\n");
+        assert_eq!(&inserted[moved..], "tro\n## B\n");
+        assert_eq!(
+            insert_or_update_toc("# A", 3, &RenderOptions::default()).unwrap(),
+            (String::from("<!-- toc -->\n- [A](#a)\n<!-- /toc -->\n\n# A"), 41)
+        );
+    }
+
+    #[test]
+    fn test_update_toc() {
+        let text = "<!-- toc -->\n- [Old](#old)\n<!-- /toc -->\n\n# Newer\n";
+        let (updated, moved) = insert_or_update_toc(text, text.len() - 3, &RenderOptions::default()).unwrap();
+
+        assert_eq!(updated, "<!-- toc -->\n- [Newer](#newer)\n<!-- /toc -->\n\n# Newer\n");
+        assert_eq!(&updated[moved..], "er\n");
+        assert_eq!(insert_or_update_toc(&updated, 0, &RenderOptions::default()).unwrap(), (updated.clone(), 0));
+        // A caret inside the old table of contents goes to its start
+        assert_eq!(insert_or_update_toc(text, 16, &RenderOptions::default()).unwrap().1, 0);
+
+        assert!(insert_or_update_toc("<!-- toc -->\n# A", 0, &RenderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_markers_in_code_are_ignored() {
+        let text = "# A\n\n```md\n<!-- toc -->\n<!-- /toc -->\n```\n\nSee `<!-- toc -->`.\n";
+        let (updated, _) = insert_or_update_toc(text, 0, &RenderOptions::default()).unwrap();
+
+        assert_eq!(updated, ["<!-- toc -->\n- [A](#a)\n<!-- /toc -->\n\n", text].concat());
+        assert!(insert_or_update_toc("```\n<!-- toc -->\n```\n# A\n", 0, &RenderOptions::default()).is_ok());
+    }
+}
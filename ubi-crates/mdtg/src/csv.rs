@@ -0,0 +1,257 @@
+use table::{Alignment, Table};
+
+/// Split CSV/TSV text into records of fields.
+///
+/// Fields may be quoted with `"`, in which case they can contain the delimiter,
+/// newlines and `""` for a literal quote.
+pub fn parse_delimited(input: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                let quote_line = line;
+
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => return Err(format!("Unclosed quote starting on line {}", quote_line)),
+                    }
+                }
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(field.clone());
+                records.push(record.clone());
+                record.clear();
+                field.clear();
+                line += 1;
+            }
+            c if c == delimiter => {
+                record.push(field.clone());
+                field.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+
+    Ok(records)
+}
+
+/// Guess the delimiter of CSV/TSV text from its first line
+pub fn detect_delimiter(input: &str) -> char {
+    let first_line = input.lines().next().unwrap_or_default();
+
+    [',', '\t', ';']
+        .iter()
+        .copied()
+        .max_by_key(|delimiter| first_line.matches(*delimiter).count())
+        .filter(|delimiter| first_line.contains(*delimiter))
+        .unwrap_or(',')
+}
+
+/// Whether pasted text looks like tab separated data, e.g. cells copied from a spreadsheet
+pub fn is_tsv(input: &str) -> bool {
+    let lines: Vec<&str> = input.lines().filter(|line| !line.trim().is_empty()).collect();
+
+    if lines.len() < 2 {
+        return false;
+    }
+
+    let tabs = lines[0].matches('\t').count();
+    tabs > 0 && lines.iter().all(|line| line.matches('\t').count() == tabs)
+}
+
+/// Whether a field is a number, optionally with a currency sign, thousands separators or a percent sign.
+/// Words that parse as floats, like `NaN` and `inf`, are not numbers here.
+pub fn is_numeric(field: &str) -> bool {
+    let field = field.trim();
+    let field = field.strip_suffix('%').unwrap_or(field);
+    let field = field.trim_start_matches(['$', '€', '£']);
+    field.replace(',', "").parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// The first record is treated as a header when none of its fields are empty or numeric
+fn has_header(records: &[Vec<String>]) -> bool {
+    match records.first() {
+        Some(first) => first.iter().all(|field| !field.trim().is_empty() && !is_numeric(field)),
+        None => false,
+    }
+}
+
+/// Turn a field into a single line table cell
+fn to_cell(field: &str) -> String {
+    field
+        .trim()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+impl Table {
+    /// Convert CSV/TSV text into a table.
+    ///
+    /// The delimiter and whether the first record is a header are guessed
+    /// when not given. Columns where every body cell is a number are right-aligned.
+    pub fn from_csv(input: &str, delimiter: Option<char>, header: Option<bool>) -> Result<Table, String> {
+        let delimiter = delimiter.unwrap_or_else(|| detect_delimiter(input));
+        let mut records = parse_delimited(input, delimiter)?;
+
+        if records.is_empty() {
+            return Err("Expected at least one record".to_string());
+        }
+
+        let columns = records.iter().map(Vec::len).max().unwrap_or_default();
+
+        let header = if header.unwrap_or_else(|| has_header(&records)) {
+            let mut header: Vec<String> = records.remove(0).iter().map(|field| to_cell(field)).collect();
+            header.resize(columns, String::new());
+            header
+        } else {
+            (1..=columns).map(|i| format!("Column {}", i)).collect()
+        };
+
+        let rows: Vec<Vec<String>> = records
+            .iter()
+            .map(|record| {
+                let mut row: Vec<String> = record.iter().map(|field| to_cell(field)).collect();
+                row.resize(columns, String::new());
+                row
+            })
+            .collect();
+
+        let alignments = (0..columns)
+            .map(|i| {
+                let mut fields = rows.iter().map(|row| &row[i]).filter(|cell| !cell.is_empty()).peekable();
+
+                if fields.peek().is_some() && fields.all(|cell| is_numeric(cell)) {
+                    Alignment::Right
+                } else {
+                    Alignment::None
+                }
+            })
+            .collect();
+
+        Ok(Table {
+            alignments,
+            header,
+            rows,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let input = "name,notes\n\"Kerr, Luka\",\"said \"\"hi\"\"\nthen left\"\r\nStephen,\n";
+
+        assert_eq!(
+            parse_delimited(input, ','),
+            Ok(vec![
+                vec!["name".to_string(), "notes".to_string()],
+                vec!["Kerr, Luka".to_string(), "said \"hi\"\nthen left".to_string()],
+                vec!["Stephen".to_string(), "".to_string()],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_unclosed_quote() {
+        assert_eq!(
+            parse_delimited("a,b\n\"c,d\n", ','),
+            Err("Unclosed quote starting on line 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_numeric() {
+        for field in ["42", " -3.5 ", "1,000", "$12.50", "€3", "15%", "1e3"] {
+            assert!(is_numeric(field), "{}", field);
+        }
+        for field in ["", "abc", "NaN", "inf", "-Infinity", "$", "%"] {
+            assert!(!is_numeric(field), "{}", field);
+        }
+    }
+
+    #[test]
+    fn test_detect_delimiter() {
+        assert_eq!(detect_delimiter("a\tb\tc\n1\t2\t3"), '\t');
+        assert_eq!(detect_delimiter("a;b;c"), ';');
+        assert_eq!(detect_delimiter("a,b"), ',');
+        assert_eq!(detect_delimiter("a"), ',');
+    }
+
+    #[test]
+    fn test_is_tsv() {
+        assert!(is_tsv("a\tb\n1\t2\n"));
+        assert!(!is_tsv("a\tb"));
+        assert!(!is_tsv("a\tb\n1\t2\t3"));
+        assert!(!is_tsv("just some\ntext"));
+    }
+
+    #[test]
+    fn test_csv_to_table() {
+        let input = "Item,Price,Note\nApple,$1.20,red | green\nPear,0.90,\"ripe\nsoon\"";
+
+        assert_eq!(
+            Table::from_csv(input, None, None).unwrap().to_string(),
+            "\
+            | Item  | Price | Note         |\n\
+            | ----- | -----:| ------------ |\n\
+            | Apple | $1.20 | red \\| green |\n\
+            | Pear  |  0.90 | ripe<br>soon |"
+        );
+    }
+
+    #[test]
+    fn test_csv_without_header() {
+        let table = Table::from_csv("1\t2\n3\t4", None, None).unwrap();
+
+        assert_eq!(table.header, vec!["Column 1".to_string(), "Column 2".to_string()]);
+        assert_eq!(table.alignments, vec![Alignment::Right, Alignment::Right]);
+        assert_eq!(table.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_csv_forced_header() {
+        let table = Table::from_csv("1,2\n3,4", Some(','), Some(true)).unwrap();
+
+        assert_eq!(table.header, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(table.rows, vec![vec!["3".to_string(), "4".to_string()]]);
+    }
+
+    #[test]
+    fn test_csv_ragged_records() {
+        let table = Table::from_csv("a,b,c\nx\n", None, None).unwrap();
+
+        assert_eq!(
+            table.rows,
+            vec![vec!["x".to_string(), String::new(), String::new()]]
+        );
+    }
+}
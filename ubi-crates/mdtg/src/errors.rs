@@ -0,0 +1,122 @@
+use std::fmt;
+
+use error::UbiquityError;
+
+/// A token that the table specification was expected to contain
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expected {
+    ColumnNumber,
+    Cross,
+    RowNumber,
+    OpenBracket,
+    CloseBracket,
+    EscapedCharacter,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expected::ColumnNumber => write!(f, "a column number"),
+            Expected::Cross => write!(f, "'x'"),
+            Expected::RowNumber => write!(f, "a row number"),
+            Expected::OpenBracket => write!(f, "'['"),
+            Expected::CloseBracket => write!(f, "']'"),
+            Expected::EscapedCharacter => write!(f, "a character after '\\'"),
+        }
+    }
+}
+
+/// An error in a table specification, carrying the byte offset of the offending input
+#[derive(Debug, Clone, PartialEq)]
+pub enum MdtgError {
+    /// A token was missing, or something else was found in its place
+    Expected { offset: usize, expected: Expected },
+    UnexpectedInput { offset: usize, found: char },
+    ZeroDimension { offset: usize },
+    TooManyPositions { offset: usize, columns: usize },
+    TooManyCells { offset: usize, columns: usize },
+    TooManyRecords { offset: usize, rows: usize },
+}
+
+impl MdtgError {
+    /// The byte offset into the specification where the error occurred
+    pub fn offset(&self) -> usize {
+        match self {
+            MdtgError::Expected { offset, .. }
+            | MdtgError::UnexpectedInput { offset, .. }
+            | MdtgError::ZeroDimension { offset }
+            | MdtgError::TooManyPositions { offset, .. }
+            | MdtgError::TooManyCells { offset, .. }
+            | MdtgError::TooManyRecords { offset, .. } => *offset,
+        }
+    }
+
+    /// Point at the error in the specification it came from, e.g.
+    ///
+    /// ```text
+    /// 3lcr 5
+    ///      ^ Expected 'x'
+    /// ```
+    pub fn annotate(&self, spec: &str) -> String {
+        let offset = self.offset().min(spec.len());
+        let column = spec
+            .char_indices()
+            .take_while(|(i, _)| *i < offset)
+            .count();
+
+        format!("{}\n{}^ {}", spec, " ".repeat(column), self)
+    }
+
+    /// Convert into a `UbiquityError`, with the annotated specification as the verbose description
+    pub fn to_ubiquity_error(&self, spec: &str) -> UbiquityError {
+        let mut error = UbiquityError::mdtg(self.annotate(spec));
+        error.human_description = format!("{}.", self);
+        error
+    }
+}
+
+impl fmt::Display for MdtgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MdtgError::Expected { expected, .. } => write!(f, "Expected {}", expected),
+            MdtgError::UnexpectedInput { found, .. } => write!(f, "Unexpected input '{}'", found),
+            MdtgError::ZeroDimension { .. } => write!(f, "Column/row number must be > 0"),
+            MdtgError::TooManyPositions { .. } => write!(f, "Number of positions exceed number of columns"),
+            MdtgError::TooManyCells { .. } => write!(f, "Number of cells exceed number of columns"),
+            MdtgError::TooManyRecords { .. } => write!(f, "Number of records exceed number of rows"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let error = MdtgError::Expected { offset: 5, expected: Expected::Cross };
+        assert_eq!(error.annotate("3lcr 5"), "3lcr 5\n     ^ Expected 'x'");
+    }
+
+    #[test]
+    fn test_annotate_multibyte() {
+        let spec = "2 x 1 [Größe, é] [a, b, c]";
+        let error = MdtgError::TooManyCells { offset: spec.rfind('c').unwrap(), columns: 2 };
+
+        assert_eq!(
+            error.annotate(spec),
+            "2 x 1 [Größe, é] [a, b, c]\n                        ^ Number of cells exceed number of columns"
+        );
+    }
+
+    #[test]
+    fn test_to_ubiquity_error() {
+        let error = MdtgError::ZeroDimension { offset: 0 }.to_ubiquity_error("0 x 1");
+
+        assert_eq!(error.human_description, "Column/row number must be > 0.");
+        assert_eq!(
+            error.verbose_description,
+            Some("0 x 1\n^ Column/row number must be > 0".to_string())
+        );
+    }
+}
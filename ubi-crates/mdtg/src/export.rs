@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use table::{find_tables, Table};
+
+/// A format that tables can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// The file extension of the format, without a leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Export the tables of a markdown document.
+///
+/// When an offset is given only the table containing it is exported,
+/// otherwise every table in the document is. Several CSV tables are separated
+/// by a blank line, while several JSON tables are wrapped in an outer array.
+pub fn export_tables(text: &str, offset: Option<usize>, format: ExportFormat) -> Result<String, String> {
+    let ranges = match offset {
+        Some(offset) => match find_tables(text).into_iter().find(|range| range.contains(&offset) || range.end == offset) {
+            Some(range) => vec![range],
+            None => return Err("No table under the cursor".to_string()),
+        },
+        None => find_tables(text),
+    };
+
+    if ranges.is_empty() {
+        return Err("No tables found".to_string());
+    }
+
+    let tables = ranges
+        .into_iter()
+        .map(|range| Table::parse(&text[range]))
+        .collect::<Result<Vec<Table>, String>>()?;
+
+    Ok(match format {
+        ExportFormat::Csv => tables.iter().map(Table::to_csv).collect::<Vec<String>>().join("\n"),
+        ExportFormat::Json if tables.len() == 1 => tables[0].to_json(),
+        ExportFormat::Json => {
+            let tables: Vec<String> = tables.iter().map(Table::to_json).collect();
+            format!("[{}]", tables.join(","))
+        }
+    })
+}
+
+impl Table {
+    /// Write the table as CSV, with inline markdown stripped from every cell
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        for row in Some(&self.header).into_iter().chain(self.rows.iter()) {
+            let fields: Vec<String> = row
+                .iter()
+                .take(self.columns())
+                .map(|cell| quote_csv(&strip_inline(cell)))
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Write the table as a JSON array of records keyed by the header labels,
+    /// with inline markdown stripped from every cell
+    pub fn to_json(&self) -> String {
+        let keys = self.keys();
+        let records: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let fields: Vec<String> = keys
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(key, cell)| format!("{}:{}", quote_json(key), quote_json(&strip_inline(cell))))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            })
+            .collect();
+
+        format!("[{}]", records.join(","))
+    }
+
+    /// Unique, non-empty record keys from the header labels
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::with_capacity(self.columns());
+
+        for (i, label) in self.header.iter().enumerate() {
+            let label = strip_inline(label);
+            let mut key = if label.is_empty() { format!("Column {}", i + 1) } else { label.clone() };
+
+            let mut n = 2;
+            while keys.contains(&key) {
+                key = format!("{} {}", label, n);
+                n += 1;
+            }
+
+            keys.push(key);
+        }
+
+        keys
+    }
+}
+
+/// Reduce the inline markdown of a table cell to plain text.
+///
+/// Emphasis and code markers are removed, links and images are replaced by
+/// their text, `<br>` becomes a newline and other HTML tags are dropped.
+pub fn strip_inline(cell: &str) -> String {
+    let chars: Vec<char> = cell.chars().collect();
+    let mut text = String::with_capacity(cell.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            '\\' if i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() => {
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            '`' => {
+                let run = run_length(&chars, i);
+                let closing = (i + run..chars.len()).find(|&j| chars[j] == '`' && chars[j - 1] != '`' && run_length(&chars, j) == run);
+
+                match closing {
+                    Some(end) => {
+                        let code: String = chars[i + run..end].iter().collect();
+                        text.push_str(code.trim());
+                        i = end + run;
+                    }
+                    None => {
+                        text.extend(&chars[i..i + run]);
+                        i += run;
+                    }
+                }
+            }
+            '!' if chars.get(i + 1) == Some(&'[') => match link_text(&chars, i + 1) {
+                Some((alt, end)) => {
+                    text.push_str(&alt);
+                    i = end;
+                }
+                None => {
+                    text.push(c);
+                    i += 1;
+                }
+            },
+            '[' => match link_text(&chars, i) {
+                Some((label, end)) => {
+                    text.push_str(&strip_inline(&label));
+                    i = end;
+                }
+                None => {
+                    text.push(c);
+                    i += 1;
+                }
+            },
+            '<' if chars.get(i + 1).is_some_and(|next| next.is_ascii_alphabetic() || *next == '/') => {
+                match chars[i..].iter().position(|c| *c == '>') {
+                    Some(len) => {
+                        let tag: String = chars[i + 1..i + len].iter().collect();
+                        let name = tag.trim_end_matches('/').trim().to_ascii_lowercase();
+                        if name == "br" {
+                            text.push('\n');
+                        }
+                        i += len + 1;
+                    }
+                    None => {
+                        text.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '*' | '_' | '~' => {
+                let run = run_length(&chars, i);
+                let before = if i == 0 { None } else { Some(chars[i - 1]) };
+                let after = chars.get(i + run).copied();
+                let is_space = |c: Option<char>| c.filter(|c| !c.is_whitespace()).is_none();
+                let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+
+                let flanking = !(is_space(before) && is_space(after));
+                let intraword = c == '_' && is_word(before) && is_word(after);
+                let single_tilde = c == '~' && run == 1;
+
+                if !flanking || intraword || single_tilde {
+                    text.extend(&chars[i..i + run]);
+                }
+                i += run;
+            }
+            _ => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    text.trim().to_string()
+}
+
+/// The number of times the character at `start` repeats
+fn run_length(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|c| **c == chars[start]).count()
+}
+
+/// Read `[text](destination)` or `[text][reference]` starting at an opening bracket,
+/// returning the text and the index after the link
+fn link_text(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut depth = 0;
+    let mut close = None;
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let close = close?;
+    let (open, end) = match chars.get(close + 1) {
+        Some('(') => ('(', ')'),
+        Some('[') => ('[', ']'),
+        _ => return None,
+    };
+
+    let mut depth = 0;
+    for (j, c) in chars.iter().enumerate().skip(close + 1) {
+        if *c == open {
+            depth += 1;
+        } else if *c == end {
+            depth -= 1;
+            if depth == 0 {
+                return Some((chars[start + 1..close].iter().collect(), j + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Quote a CSV field when it contains a comma, quote or line break
+fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Quote a string as a JSON string literal
+fn quote_json(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static DOCUMENT: &str = "\
+        # Prices\n\
+        \n\
+        | Item | Price |\n\
+        | --- | --: |\n\
+        | **Apple**, red | $1.20 |\n\
+        | [Pear](https://example.com) | `0.90` |\n\
+        \n\
+        Some text | with a pipe\n\
+        \n\
+        | Name | |\n\
+        | --- | --- |\n\
+        | snake_case | a<br>\"b\" |";
+
+    #[test]
+    fn test_strip_inline() {
+        assert_eq!(strip_inline("**bold** and _em_ and ~~gone~~"), "bold and em and gone");
+        assert_eq!(strip_inline("`a | b` \\| c"), "a | b | c");
+        assert_eq!(strip_inline("![logo](a.png) [site][ref] [not a link]"), "logo site [not a link]");
+        assert_eq!(strip_inline("snake_case_name 2 * 3 < 4"), "snake_case_name 2 * 3 < 4");
+        assert_eq!(strip_inline("one<br/>two <span class=\"x\">three</span>"), "one\ntwo three");
+    }
+
+    #[test]
+    fn test_export_csv() {
+        assert_eq!(
+            export_tables(DOCUMENT, None, ExportFormat::Csv),
+            Ok("\
+                Item,Price\n\
+                \"Apple, red\",$1.20\n\
+                Pear,0.90\n\
+                \n\
+                Name,\n\
+                snake_case,\"a\n\"\"b\"\"\"\n"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_export_json_under_cursor() {
+        let offset = DOCUMENT.find("snake_case").unwrap();
+
+        assert_eq!(
+            export_tables(DOCUMENT, Some(offset), ExportFormat::Json),
+            Ok(r#"[{"Name":"snake_case","Column 2":"a\n\"b\""}]"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_export_json_all_tables() {
+        let json = export_tables(DOCUMENT, None, ExportFormat::Json).unwrap();
+
+        assert!(json.starts_with(r#"[[{"Item":"Apple, red","Price":"$1.20"},"#));
+        assert!(json.ends_with(r#"}]]"#));
+    }
+
+    #[test]
+    fn test_export_without_tables() {
+        assert_eq!(
+            export_tables("# No tables", None, ExportFormat::Csv),
+            Err("No tables found".to_string())
+        );
+        assert_eq!(
+            export_tables(DOCUMENT, Some(0), ExportFormat::Csv),
+            Err("No table under the cursor".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let table = Table::parse("| a | a |\n| - | - |\n| 1 | 2 |").unwrap();
+        assert_eq!(table.to_json(), r#"[{"a":"1","a 2":"2"}]"#);
+    }
+}
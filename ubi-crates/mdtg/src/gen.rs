@@ -1,9 +1,11 @@
 use parse::{Node, AST};
+use table::display_width;
 
 static SPACE: &str = " ";
 static COLON: &str = ":";
-static DASHES: &str = "------";
-static INDENT: &str = "        ";
+static DASH: &str = "-";
+static PIPE: &str = "|";
+static MIN_WIDTH: usize = 6;
 
 /// Generate a markdown table given an AST
 pub fn gen(ast: &Node) -> String {
@@ -15,69 +17,134 @@ pub fn gen(ast: &Node) -> String {
 }
 
 fn gen_table(ast: &Node, output: &mut String) {
-    gen_header(&ast, output);
-    gen_rows(&ast, output);
-}
+    let layout = Layout::from(ast);
 
-fn gen_header(ast: &Node, output: &mut String) {
-    let column_node = &ast.children[0];
+    gen_header(ast, &layout, output);
+    gen_rows(ast, &layout, output);
+}
 
-    if let AST::Column(c) = column_node.item {
-        gen_row(c, true, output);
-        gen_positions(&column_node, output);
-    }
+/// The alignment and padded width of every column in the table
+struct Layout {
+    positions: Vec<char>,
+    widths: Vec<usize>,
 }
 
-fn gen_positions(ast: &Node, output: &mut String) {
-    if let AST::Column(c) = ast.item {
-        let positional_row = |left, right| [left, DASHES, right].concat();
-
-        for i in 0..c {
-            output.push_str("|");
-
-            if let Some(position_node) = ast.children.get(i) {
-                if let AST::Position(p) = position_node.item {
-                    match p {
-                        'l' => output.push_str(&positional_row(SPACE, SPACE)),
-                        'c' => output.push_str(&positional_row(COLON, COLON)),
-                        'r' => output.push_str(&positional_row(SPACE, COLON)),
-                        _ => {}
-                    }
+impl Layout {
+    fn from(ast: &Node) -> Layout {
+        let column_node = &ast.children[0];
+        let mut positions = Vec::new();
+        let mut widths = Vec::new();
+
+        if let AST::Column(c) = column_node.item {
+            for i in 0..c {
+                let position = match column_node.children.get(i) {
+                    Some(Node { item: AST::Position(p), .. }) => *p,
+                    _ => 'l',
+                };
+                positions.push(position);
+                widths.push(MIN_WIDTH);
+            }
+        }
+
+        for node in ast.children.iter().skip(3) {
+            for (i, cell) in cells(node).iter().enumerate() {
+                let width = display_width(&escape_pipes(cell));
+                if let Some(w) = widths.get_mut(i) {
+                    *w = width.max(*w);
                 }
-            } else {
-                output.push_str(&positional_row(SPACE, SPACE));
             }
         }
 
-        output.push_str("|\n");
+        Layout { positions, widths }
     }
 }
 
-fn gen_rows(ast: &Node, output: &mut String) {
-    let column_node = &ast.children[0];
+fn gen_header(ast: &Node, layout: &Layout, output: &mut String) {
+    let header = match ast.children.get(3) {
+        Some(header_node) if header_node.item == AST::Header => cells(header_node),
+        _ => Vec::new(),
+    };
+
+    gen_row(&header, layout, true, output);
+    gen_positions(layout, output);
+}
+
+fn gen_positions(layout: &Layout, output: &mut String) {
+    let positional_row = |left: &str, width: usize, right: &str| [left, &DASH.repeat(width), right].concat();
+
+    for (position, width) in layout.positions.iter().zip(layout.widths.iter()) {
+        output.push_str(PIPE);
+
+        match position {
+            'c' => output.push_str(&positional_row(COLON, *width, COLON)),
+            'r' => output.push_str(&positional_row(SPACE, *width, COLON)),
+            _ => output.push_str(&positional_row(SPACE, *width, SPACE)),
+        }
+    }
+
+    output.push_str("|\n");
+}
+
+fn gen_rows(ast: &Node, layout: &Layout, output: &mut String) {
     let row_node = &ast.children[2];
 
-    if let AST::Column(c) = column_node.item {
-        if let AST::Row(r) = row_node.item {
-            for _ in 0..(r - 1) {
-                gen_row(c, true, output);
-            }
+    let records: Vec<Vec<String>> = ast
+        .children
+        .iter()
+        .filter(|node| node.item == AST::Record)
+        .map(cells)
+        .collect();
 
-            gen_row(c, false, output);
+    if let AST::Row(r) = row_node.item {
+        for i in 0..r {
+            let record = records.get(i).cloned().unwrap_or_default();
+            gen_row(&record, layout, i < r - 1, output);
         }
     }
 }
 
-fn gen_row(n: usize, newline: bool, output: &mut String) {
-    for _ in 0..n {
-        output.push_str("|");
-        output.push_str(INDENT);
+fn gen_row(cells: &[String], layout: &Layout, newline: bool, output: &mut String) {
+    for (i, (position, width)) in layout.positions.iter().zip(layout.widths.iter()).enumerate() {
+        let cell = cells.get(i).map_or(String::new(), |cell| escape_pipes(cell));
+
+        output.push_str(PIPE);
+        output.push_str(SPACE);
+        output.push_str(&pad(&cell, *position, *width));
+        output.push_str(SPACE);
     }
 
     let last = if newline { "|\n" } else { "|" };
     output.push_str(last);
 }
 
+/// Pad a cell to the given width, honouring the column's position
+fn pad(cell: &str, position: char, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(cell));
+
+    let (left, right) = match position {
+        'c' => (padding / 2, padding - padding / 2),
+        'r' => (padding, 0),
+        _ => (0, padding),
+    };
+
+    [&SPACE.repeat(left), cell, &SPACE.repeat(right)].concat()
+}
+
+/// A cell containing `|` would end the cell early, so escape it
+fn escape_pipes(cell: &str) -> String {
+    cell.replace(PIPE, "\\|")
+}
+
+fn cells(node: &Node) -> Vec<String> {
+    node.children
+        .iter()
+        .filter_map(|child| match &child.item {
+            AST::Cell(cell) => Some(cell.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,4 +200,75 @@ mod tests {
                 .to_string()
         );
     }
+
+    #[test]
+    fn test_header_table() {
+        let mut table_node = Node::new(AST::Table);
+        let mut column_node = Node::new(AST::Column(3));
+        let cross_node = Node::new(AST::Cross);
+        let row_node = Node::new(AST::Row(2));
+        let mut header_node = Node::new(AST::Header);
+        let mut record_node = Node::new(AST::Record);
+
+        column_node.add_children(&[
+            Node::new(AST::Position('l')),
+            Node::new(AST::Position('c')),
+            Node::new(AST::Position('r')),
+        ]);
+        header_node.add_children(&[
+            Node::new(AST::Cell("Name".to_string())),
+            Node::new(AST::Cell("Age".to_string())),
+            Node::new(AST::Cell("City".to_string())),
+        ]);
+        record_node.add_children(&[
+            Node::new(AST::Cell("Luka".to_string())),
+            Node::new(AST::Cell("23".to_string())),
+            Node::new(AST::Cell("Sydney | NSW".to_string())),
+        ]);
+        table_node.add_children(&[column_node, cross_node, row_node, header_node, record_node]);
+
+        assert_eq!(
+            gen(&table_node),
+            "\
+             | Name   |  Age   |          City |\n\
+             | ------ |:------:| -------------:|\n\
+             | Luka   |   23   | Sydney \\| NSW |\n\
+             |        |        |               |"
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_wide_characters_table() {
+        let mut table_node = Node::new(AST::Table);
+        let mut column_node = Node::new(AST::Column(2));
+        let mut header_node = Node::new(AST::Header);
+        let mut record_node = Node::new(AST::Record);
+
+        column_node.add_children(&[Node::new(AST::Position('c'))]);
+        header_node.add_children(&[
+            Node::new(AST::Cell("東京都庁".to_string())),
+            Node::new(AST::Cell("Emoji".to_string())),
+        ]);
+        record_node.add_children(&[
+            Node::new(AST::Cell("渋谷".to_string())),
+            Node::new(AST::Cell("👩‍💻".to_string())),
+        ]);
+        table_node.add_children(&[
+            column_node,
+            Node::new(AST::Cross),
+            Node::new(AST::Row(1)),
+            header_node,
+            record_node,
+        ]);
+
+        assert_eq!(
+            gen(&table_node),
+            "\
+             | 東京都庁 | Emoji  |\n\
+             |:--------:| ------ |\n\
+             |   渋谷   | 👩‍💻     |"
+                .to_string()
+        );
+    }
 }
@@ -1,19 +1,64 @@
+extern crate error;
+extern crate unicode_width;
+
+mod csv;
+mod errors;
+mod export;
 mod gen;
+mod ops;
 mod parse;
+mod table;
 mod tokenize;
 
 use gen::gen;
 use parse::parse;
 use tokenize::tokenize;
 
-pub fn get_table(input: String) -> Result<String, String> {
-    match tokenize(&input) {
-        Ok(tokens) => match parse(tokens) {
-            Ok(ast) => {
-                Ok(gen(&ast))
-            }
-            Err(err) => Err(err),
-        },
-        Err(err) => Err(err),
+pub use errors::{Expected, MdtgError};
+pub use csv::{detect_delimiter, is_tsv};
+pub use export::{export_tables, strip_inline, ExportFormat};
+pub use ops::{edit_table, position_at, Position, TableOp};
+pub use table::{display_width, find_table, find_tables, format_table, format_tables, Alignment, Table};
+pub use tokenize::escape_cell;
+
+/// Generate a markdown table from a specification such as `3lcr x 2 [Name, Age, City]`.
+///
+/// The first `[...]` group after the row count holds the header labels and
+/// every further group fills one row of the table.
+pub fn get_table(input: String) -> Result<String, MdtgError> {
+    let tokens = tokenize(&input)?;
+    let ast = parse(tokens)?;
+
+    Ok(gen(&ast))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_table() {
+        let spec = format!("2cr x 1 [Name, {}] [Luka]", escape_cell("Age, years"));
+
+        assert_eq!(
+            get_table(spec),
+            Ok("\
+                |  Name  | Age, years |\n\
+                |:------:| ----------:|\n\
+                |  Luka  |            |"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_get_table_error() {
+        let spec = "2 x 1 [a, b] [c, d, e]".to_string();
+        let error = get_table(spec.clone()).unwrap_err();
+
+        assert_eq!(error, MdtgError::TooManyCells { offset: 20, columns: 2 });
+        assert_eq!(
+            error.annotate(&spec),
+            "2 x 1 [a, b] [c, d, e]\n                    ^ Number of cells exceed number of columns"
+        );
     }
 }
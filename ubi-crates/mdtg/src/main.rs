@@ -0,0 +1,128 @@
+extern crate mdtg;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use mdtg::{format_tables, get_table, Table};
+
+static USAGE: &str = "\
+Usage:
+    mdtg [SPEC]...                      Generate a table from a spec, e.g. `3lcr x 2 [Name, Age, City]`
+    mdtg --csv [FILE]                   Convert CSV or TSV into a table
+    mdtg --format [--in-place] FILE...  Re-align every table in markdown files
+    mdtg --format --check FILE...       Exit with an error if any table is not aligned
+
+The spec, CSV or markdown is read from stdin when no argument or FILE is given.";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("mdtg: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some("--csv") => {
+            let input = match &args[1..] {
+                [] => read_stdin()?,
+                [flag, ..] if flag.starts_with('-') => return Err(unknown_option(flag)),
+                [path] => read_file(path)?,
+                [_, extra, ..] => return Err(format!("Unexpected argument '{}'\n\n{}", extra, USAGE)),
+            };
+            println!("{}", Table::from_csv(&input, None, None)?);
+            Ok(())
+        }
+        Some("--format") => format(&args[1..]),
+        Some(flag) if flag.starts_with('-') => Err(unknown_option(flag)),
+        Some(_) => generate(&args.join(" ")),
+        None => generate(read_stdin()?.trim()),
+    }
+}
+
+fn generate(spec: &str) -> Result<(), String> {
+    match get_table(spec.to_string()) {
+        Ok(table) => {
+            println!("{}", table);
+            Ok(())
+        }
+        Err(err) => Err(format!("invalid spec\n{}", err.annotate(spec))),
+    }
+}
+
+/// Options are checked up front, so a mistyped one is never taken for another mode
+fn unknown_option(flag: &str) -> String {
+    format!("Unknown option '{}'\n\n{}", flag, USAGE)
+}
+
+fn format(args: &[String]) -> Result<(), String> {
+    let mut in_place = false;
+    let mut check = false;
+    let mut paths: Vec<&String> = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--in-place" | "-i" => in_place = true,
+            "--check" => check = true,
+            flag if flag.starts_with('-') => return Err(unknown_option(flag)),
+            _ => paths.push(arg),
+        }
+    }
+
+    if in_place && check {
+        return Err("--in-place and --check cannot be used together".to_string());
+    }
+
+    if paths.is_empty() {
+        if in_place || check {
+            return Err("Expected at least one FILE".to_string());
+        }
+        print!("{}", format_tables(&read_stdin()?)?);
+        return Ok(());
+    }
+
+    let mut unformatted = Vec::new();
+
+    for path in paths {
+        let text = read_file(path)?;
+        let formatted = format_tables(&text).map_err(|err| format!("{}: {}", path, err))?;
+
+        if check {
+            if formatted != text {
+                unformatted.push(path.as_str());
+            }
+        } else if in_place {
+            if formatted != text {
+                fs::write(path, formatted).map_err(|err| format!("{}: {}", path, err))?;
+            }
+        } else {
+            print!("{}", formatted);
+        }
+    }
+
+    if unformatted.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("tables are not aligned in:\n    {}", unformatted.join("\n    ")))
+    }
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|err| err.to_string())?;
+    Ok(input)
+}
@@ -0,0 +1,417 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use csv::is_numeric;
+use export::strip_inline;
+use table::{find_table, split_row, Alignment, Table};
+
+/// A structural edit of the table containing the caret
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableOp {
+    InsertRow,
+    DeleteRow,
+    InsertColumn,
+    DeleteColumn,
+    MoveColumnLeft,
+    MoveColumnRight,
+    Align(Alignment),
+    Sort { descending: bool },
+    Transpose,
+}
+
+/// The position of a cell, where row 0 is the header and row 1 the first body row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Table {
+    /// Insert an empty body row, so that it ends up at the given row
+    pub fn insert_row(&mut self, row: usize) {
+        let index = row.saturating_sub(1).min(self.rows.len());
+        self.rows.insert(index, vec![String::new(); self.columns()]);
+    }
+
+    pub fn delete_row(&mut self, row: usize) -> Result<(), String> {
+        if row == 0 {
+            return Err("The header row cannot be deleted".to_string());
+        }
+
+        if row > self.rows.len() {
+            return Err(format!("Row {} does not exist", row));
+        }
+
+        self.rows.remove(row - 1);
+        Ok(())
+    }
+
+    /// Insert an empty column at the given index
+    pub fn insert_column(&mut self, column: usize) {
+        let column = column.min(self.columns());
+
+        self.alignments.insert(column, Alignment::None);
+        self.header.insert(column, String::new());
+        for row in &mut self.rows {
+            row.insert(column.min(row.len()), String::new());
+        }
+    }
+
+    pub fn delete_column(&mut self, column: usize) -> Result<(), String> {
+        if column >= self.columns() {
+            return Err(format!("Column {} does not exist", column + 1));
+        }
+
+        if self.columns() == 1 {
+            return Err("A table needs at least one column".to_string());
+        }
+
+        self.alignments.remove(column);
+        self.header.remove(column);
+        for row in &mut self.rows {
+            if column < row.len() {
+                row.remove(column);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Swap a column with the one at another index
+    pub fn swap_columns(&mut self, a: usize, b: usize) -> Result<(), String> {
+        for column in [a, b] {
+            if column >= self.columns() {
+                return Err(format!("Column {} does not exist", column + 1));
+            }
+        }
+
+        self.alignments.swap(a, b);
+        self.header.swap(a, b);
+        for row in &mut self.rows {
+            row.swap(a, b);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_alignment(&mut self, column: usize, alignment: Alignment) -> Result<(), String> {
+        match self.alignments.get_mut(column) {
+            Some(current) => {
+                *current = alignment;
+                Ok(())
+            }
+            None => Err(format!("Column {} does not exist", column + 1)),
+        }
+    }
+
+    /// Sort the body rows by a column.
+    ///
+    /// Cells are compared as numbers when both are numeric and as case-insensitive
+    /// plain text otherwise. Empty cells always sort last and the sort is stable.
+    pub fn sort_by_column(&mut self, column: usize, descending: bool) -> Result<(), String> {
+        if column >= self.columns() {
+            return Err(format!("Column {} does not exist", column + 1));
+        }
+
+        self.rows.sort_by(|a, b| {
+            let a = strip_inline(&a[column]);
+            let b = strip_inline(&b[column]);
+
+            match (a.is_empty(), b.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    let ordering = compare_cells(&a, &b);
+                    if descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Swap the rows and columns of the table, so that the header becomes the first column
+    pub fn transpose(&mut self) {
+        let columns = self.columns();
+        let mut transposed: Vec<Vec<String>> = vec![Vec::with_capacity(self.rows.len() + 1); columns];
+
+        for row in Some(&self.header).into_iter().chain(self.rows.iter()) {
+            for (column, cells) in transposed.iter_mut().enumerate() {
+                cells.push(row.get(column).cloned().unwrap_or_default());
+            }
+        }
+
+        let mut rows = transposed.into_iter();
+        self.header = rows.next().unwrap_or_default();
+        self.rows = rows.collect();
+        self.alignments = vec![Alignment::None; self.header.len()];
+    }
+
+    /// Apply an operation at the given cell, returning where the caret should end up
+    pub fn apply(&mut self, op: TableOp, at: Position) -> Result<Position, String> {
+        let Position { row, column } = at;
+
+        match op {
+            TableOp::InsertRow => {
+                self.insert_row(row + 1);
+                return Ok(Position { row: row + 1, column });
+            }
+            TableOp::DeleteRow => {
+                self.delete_row(row)?;
+                return Ok(Position { row: row.min(self.rows.len()), column });
+            }
+            TableOp::InsertColumn => {
+                self.insert_column(column + 1);
+                return Ok(Position { row, column: column + 1 });
+            }
+            TableOp::DeleteColumn => {
+                self.delete_column(column)?;
+                return Ok(Position { row, column: column.min(self.columns() - 1) });
+            }
+            TableOp::MoveColumnLeft => {
+                if column == 0 {
+                    return Err("The first column cannot be moved left".to_string());
+                }
+                self.swap_columns(column, column - 1)?;
+                return Ok(Position { row, column: column - 1 });
+            }
+            TableOp::MoveColumnRight => {
+                if column + 1 >= self.columns() {
+                    return Err("The last column cannot be moved right".to_string());
+                }
+                self.swap_columns(column, column + 1)?;
+                return Ok(Position { row, column: column + 1 });
+            }
+            TableOp::Align(alignment) => self.set_alignment(column, alignment)?,
+            TableOp::Sort { descending } => self.sort_by_column(column, descending)?,
+            TableOp::Transpose => {
+                self.transpose();
+                return Ok(Position { row: column, column: row });
+            }
+        }
+
+        Ok(at)
+    }
+}
+
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    if is_numeric(a) && is_numeric(b) {
+        let number = |cell: &str| -> f64 {
+            cell.trim()
+                .trim_end_matches('%')
+                .trim_start_matches(['$', '€', '£'])
+                .replace(',', "")
+                .parse()
+                .unwrap_or_default()
+        };
+
+        return number(a).partial_cmp(&number(b)).unwrap_or(Ordering::Equal);
+    }
+
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Find the cell of a table containing the given byte offset into the table text.
+///
+/// An offset on the delimiter row is treated as being in the header.
+pub fn position_at(table: &str, offset: usize) -> Position {
+    let offset = offset.min(table.len());
+    let line_start = table[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = table[..line_start].matches('\n').count();
+
+    let before = table[line_start..offset].trim_start();
+    let before = before.strip_prefix('|').unwrap_or(before);
+    let pipes = before.match_indices('|').filter(|(i, _)| !before[..*i].ends_with('\\')).count();
+    let line_end = table[line_start..].find('\n').map_or(table.len(), |i| line_start + i);
+    let columns = split_row(&table[line_start..line_end]).len();
+
+    Position {
+        row: line.saturating_sub(1),
+        column: pipes.min(columns.saturating_sub(1)),
+    }
+}
+
+/// The byte offset of the start of a cell's text in a pretty-printed table
+fn cell_offset(table: &str, at: Position) -> usize {
+    let line = if at.row == 0 { 0 } else { at.row + 1 };
+    let mut offset = 0;
+
+    for (i, text) in table.split('\n').enumerate() {
+        if i == line {
+            let mut pipes = text.match_indices('|').filter(|(i, _)| !text[..*i].ends_with('\\'));
+            return match pipes.nth(at.column) {
+                Some((pipe, _)) => offset + pipe + 2,
+                None => offset,
+            }
+            .min(offset + text.len());
+        }
+        offset += text.len() + 1;
+    }
+
+    table.len()
+}
+
+/// Apply an operation to the table containing the byte offset of the caret.
+///
+/// Returns the range of the original text that was replaced, the new table text
+/// and the byte offset of the caret within that new text.
+pub fn edit_table(text: &str, offset: usize, op: TableOp) -> Result<(Range<usize>, String, usize), String> {
+    let range = match find_table(text, offset) {
+        Some(range) => range,
+        None => return Err("The caret is not inside of a table".to_string()),
+    };
+
+    let mut table = Table::parse(&text[range.clone()])?;
+    let position = position_at(&text[range.clone()], offset - range.start);
+    let position = table.apply(op, position)?;
+
+    let table = table.to_string();
+    let caret = cell_offset(&table, position);
+
+    Ok((range, table, caret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TABLE: &str = "\
+        | Name | Age |\n\
+        | --- | --: |\n\
+        | Luka | 23 |\n\
+        | stephen | 9 |\n\
+        | Aoife | |";
+
+    fn table() -> Table {
+        Table::parse(TABLE).unwrap()
+    }
+
+    fn column(table: &Table, column: usize) -> Vec<&str> {
+        table.rows.iter().map(|row| row[column].as_str()).collect()
+    }
+
+    #[test]
+    fn test_insert_and_delete_row() {
+        let mut table = table();
+
+        table.insert_row(1);
+        assert_eq!(table.rows[0], vec![String::new(), String::new()]);
+        assert_eq!(table.rows.len(), 4);
+
+        table.delete_row(1).unwrap();
+        assert_eq!(table, self::table());
+        assert!(table.delete_row(0).is_err());
+        assert!(table.delete_row(4).is_err());
+    }
+
+    #[test]
+    fn test_insert_and_delete_column() {
+        let mut table = table();
+
+        table.insert_column(1);
+        assert_eq!(table.header, vec!["Name".to_string(), String::new(), "Age".to_string()]);
+        assert_eq!(table.alignments, vec![Alignment::None, Alignment::None, Alignment::Right]);
+        assert_eq!(column(&table, 1), vec!["", "", ""]);
+
+        table.delete_column(1).unwrap();
+        assert_eq!(table, self::table());
+
+        table.delete_column(0).unwrap();
+        assert_eq!(
+            table.delete_column(0),
+            Err("A table needs at least one column".to_string())
+        );
+    }
+
+    #[test]
+    fn test_move_column() {
+        let mut table = table();
+
+        assert_eq!(table.apply(TableOp::MoveColumnRight, Position { row: 1, column: 0 }), Ok(Position { row: 1, column: 1 }));
+        assert_eq!(table.header, vec!["Age".to_string(), "Name".to_string()]);
+        assert_eq!(table.alignments, vec![Alignment::Right, Alignment::None]);
+        assert_eq!(column(&table, 0), vec!["23", "9", ""]);
+        assert!(table.apply(TableOp::MoveColumnRight, Position { row: 1, column: 1 }).is_err());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut table = table();
+
+        table.sort_by_column(0, false).unwrap();
+        assert_eq!(column(&table, 0), vec!["Aoife", "Luka", "stephen"]);
+
+        table.sort_by_column(1, false).unwrap();
+        assert_eq!(column(&table, 1), vec!["9", "23", ""]);
+
+        table.sort_by_column(1, true).unwrap();
+        assert_eq!(column(&table, 1), vec!["23", "9", ""]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut table = Table::parse("| a | b |\n| :-: | --- |\n| 1 | 2 |\n| 3 | 4 |").unwrap();
+
+        table.transpose();
+        assert_eq!(
+            table.to_string(),
+            "\
+            | a   | 1   | 3   |\n\
+            | --- | --- | --- |\n\
+            | b   | 2   | 4   |"
+        );
+
+        table.transpose();
+        assert_eq!(table.header, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(table.rows, vec![vec!["1".to_string(), "2".to_string()], vec!["3".to_string(), "4".to_string()]]);
+    }
+
+    #[test]
+    fn test_position_at() {
+        let offset = TABLE.find("23").unwrap();
+        assert_eq!(position_at(TABLE, offset), Position { row: 1, column: 1 });
+        assert_eq!(position_at(TABLE, 0), Position { row: 0, column: 0 });
+        assert_eq!(position_at(TABLE, TABLE.find("--:").unwrap()), Position { row: 0, column: 1 });
+        assert_eq!(position_at(TABLE, TABLE.len()), Position { row: 3, column: 1 });
+    }
+
+    #[test]
+    fn test_position_at_short_row() {
+        let table = "| a | b | c |\n| - | - | - |\n| 1 |\n| x | y | z |";
+        let offset = table.find("| 1 |").unwrap() + "| 1 |".len();
+        assert_eq!(position_at(table, offset), Position { row: 1, column: 0 });
+    }
+
+    #[test]
+    fn test_edit_table() {
+        let text = format!("Intro\n\n{}\n\nOutro", TABLE);
+        let offset = text.find("Luka").unwrap();
+
+        let (range, table, caret) = edit_table(&text, offset, TableOp::Align(Alignment::Center)).unwrap();
+        assert_eq!(&text[range], TABLE);
+        assert_eq!(
+            table,
+            "\
+            |  Name   | Age |\n\
+            |:-------:| ---:|\n\
+            |  Luka   |  23 |\n\
+            | stephen |   9 |\n\
+            |  Aoife  |     |"
+        );
+        assert_eq!(&table[caret..caret + 7], " Luka  ");
+
+        let (_, table, caret) = edit_table(&text, offset, TableOp::InsertRow).unwrap();
+        assert_eq!(table.lines().nth(3), Some("|         |     |"));
+        assert_eq!(table[..caret].lines().count(), 4);
+
+        assert_eq!(
+            edit_table(&text, 0, TableOp::InsertRow),
+            Err("The caret is not inside of a table".to_string())
+        );
+    }
+}
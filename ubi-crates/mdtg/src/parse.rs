@@ -1,4 +1,5 @@
-use tokenize::Token;
+use errors::{Expected, MdtgError};
+use tokenize::{Spanned, Token};
 
 /// Represents an AST type
 #[derive(Debug, Clone, PartialEq)]
@@ -7,6 +8,9 @@ pub enum AST {
     Row(usize),
     Position(char),
     Cross,
+    Header,
+    Record,
+    Cell(String),
     Table,
 }
 
@@ -35,11 +39,11 @@ impl Node {
 /// Parse a list of tokens into an AST.
 ///
 /// Returns a node representing the markdown table specification AST,
-/// or a string representing an error that has occurred during parsing.
+/// or an error pointing at the token where parsing failed.
 ///
-/// For example, an AST returned could look like
+/// For example, the AST returned for `3cr x 5 [Name, Age]` looks like
 ///
-/// ```
+/// ```text
 /// Node {
 ///   children: [
 ///     Node {
@@ -47,86 +51,158 @@ impl Node {
 ///         Node { children: [], item: Position('c') },
 ///         Node { children: [], item: Position('r') }
 ///       ],
-///       item: Columns(3)
+///       item: Column(3)
 ///     },
 ///     Node { children: [], item: Cross },
-///     Node { children: [], item: Rows(5) }
+///     Node { children: [], item: Row(5) },
+///     Node {
+///       children: [
+///         Node { children: [], item: Cell("Name") },
+///         Node { children: [], item: Cell("Age") }
+///       ],
+///       item: Header
+///     }
 ///   ],
 ///   item: Table
 /// }
 /// ```
-pub fn parse(mut tokens: Vec<Token>) -> Result<Node, String> {
+///
+/// Any `[...]` groups after the header are parsed into `Record` nodes,
+/// one per table row.
+pub fn parse(mut tokens: Vec<Spanned>) -> Result<Node, MdtgError> {
+    let end = tokens.last().map_or(0, |(_, span)| span.end);
     tokens.reverse();
-    parse_table(&mut tokens)
+    parse_table(&mut tokens, end)
 }
 
-fn parse_table(tokens: &mut Vec<Token>) -> Result<Node, String> {
-    let columns_node = parse_columns(tokens)?;
-    let cross_node = parse_cross(tokens)?;
-    let rows_node = parse_rows(tokens)?;
+/// The offset of the next token, or of the end of the input when there are none left
+fn next_offset(tokens: &[Spanned], end: usize) -> usize {
+    tokens.last().map_or(end, |(_, span)| span.start)
+}
+
+fn parse_table(tokens: &mut Vec<Spanned>, end: usize) -> Result<Node, MdtgError> {
+    let columns_node = parse_columns(tokens, end)?;
+    let cross_node = parse_cross(tokens, end)?;
+    let rows_node = parse_rows(tokens, end)?;
 
     let mut table_node = Node::new(AST::Table);
 
+    let (columns, rows) = match (&columns_node.item, &rows_node.item) {
+        (AST::Column(c), AST::Row(r)) => (*c, *r),
+        _ => unreachable!(),
+    };
+
     table_node.add_children(&[columns_node, cross_node, rows_node]);
 
+    if !tokens.is_empty() {
+        let header_node = parse_cells(tokens, end, AST::Header, columns)?;
+        table_node.add_children(&[header_node]);
+    }
+
+    let mut records = 0;
+
+    while !tokens.is_empty() {
+        let offset = next_offset(tokens, end);
+        let record_node = parse_cells(tokens, end, AST::Record, columns)?;
+        table_node.add_children(&[record_node]);
+        records += 1;
+
+        if records > rows {
+            return Err(MdtgError::TooManyRecords { offset, rows });
+        }
+    }
+
     Ok(table_node)
 }
 
-fn parse_columns(tokens: &mut Vec<Token>) -> Result<Node, String> {
-    let tok = tokens.pop();
+fn parse_columns(tokens: &mut Vec<Spanned>, end: usize) -> Result<Node, MdtgError> {
+    let offset = next_offset(tokens, end);
 
-    fn is_position(tok: &Token) -> bool {
-        if let Token::Position(_) = tok {
+    fn is_position(tok: &Spanned) -> bool {
+        if let (Token::Position(_), _) = tok {
             true
         } else {
             false
         }
     }
 
-    if let Some(Token::Num(n)) = tok {
+    if let Some((Token::Num(n), _)) = tokens.pop() {
         let mut columns_node = Node::new(AST::Column(n));
 
         while tokens.last().map_or(false, is_position) {
-            if let Some(Token::Position(p)) = tokens.pop() {
+            let offset = next_offset(tokens, end);
+
+            if let Some((Token::Position(p), _)) = tokens.pop() {
+                if columns_node.children.len() == n {
+                    return Err(MdtgError::TooManyPositions { offset, columns: n });
+                }
+
                 let position_node = Node::new(AST::Position(p));
                 columns_node.add_children(&[position_node]);
             }
         }
 
-        if columns_node.children.len() > n as usize {
-            Err("Number of positions exceed number of columns".to_string())
-        } else {
-            Ok(columns_node)
-        }
+        Ok(columns_node)
     } else {
-        Err("Expected a column number".to_string())
+        Err(MdtgError::Expected { offset, expected: Expected::ColumnNumber })
     }
 }
 
-fn parse_cross(tokens: &mut Vec<Token>) -> Result<Node, String> {
-    let tok = tokens.pop();
+fn parse_cross(tokens: &mut Vec<Spanned>, end: usize) -> Result<Node, MdtgError> {
+    let offset = next_offset(tokens, end);
 
-    if let Some(Token::Cross) = tok {
+    if let Some((Token::Cross, _)) = tokens.pop() {
         Ok(Node::new(AST::Cross))
     } else {
-        Err("Expected 'x'".to_string())
+        Err(MdtgError::Expected { offset, expected: Expected::Cross })
     }
 }
 
-fn parse_rows(tokens: &mut Vec<Token>) -> Result<Node, String> {
-    let tok = tokens.pop();
+fn parse_rows(tokens: &mut Vec<Spanned>, end: usize) -> Result<Node, MdtgError> {
+    let offset = next_offset(tokens, end);
 
-    if let Some(Token::Num(n)) = tok {
+    if let Some((Token::Num(n), _)) = tokens.pop() {
         Ok(Node::new(AST::Row(n)))
     } else {
-        Err("Expected a row number".to_string())
+        Err(MdtgError::Expected { offset, expected: Expected::RowNumber })
     }
 }
 
+fn parse_cells(tokens: &mut Vec<Spanned>, end: usize, item: AST, columns: usize) -> Result<Node, MdtgError> {
+    let mut cells_node = Node::new(item);
+    let offset = next_offset(tokens, end);
+
+    if let Some((Token::OpenBracket, _)) = tokens.pop() {
+        loop {
+            let offset = next_offset(tokens, end);
+
+            match tokens.pop() {
+                Some((Token::Cell(cell), _)) => {
+                    if cells_node.children.len() == columns {
+                        return Err(MdtgError::TooManyCells { offset, columns });
+                    }
+                    cells_node.add_children(&[Node::new(AST::Cell(cell))]);
+                }
+                Some((Token::CloseBracket, _)) => break,
+                _ => return Err(MdtgError::Expected { offset, expected: Expected::CloseBracket }),
+            }
+        }
+    } else {
+        return Err(MdtgError::Expected { offset, expected: Expected::OpenBracket });
+    }
+
+    Ok(cells_node)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Give every token a one byte span, as if each was a single character of input
+    fn spanned(tokens: Vec<Token>) -> Vec<Spanned> {
+        tokens.into_iter().enumerate().map(|(i, token)| (token, i..i + 1)).collect()
+    }
+
     #[test]
     fn test_simple_table() {
         let tokens = vec![Token::Num(3), Token::Cross, Token::Num(5)];
@@ -138,7 +214,7 @@ mod tests {
 
         table_node.add_children(&[column_node, cross_node, row_node]);
 
-        assert_eq!(parse(tokens), Ok(table_node));
+        assert_eq!(parse(spanned(tokens)), Ok(table_node));
     }
 
     #[test]
@@ -168,25 +244,25 @@ mod tests {
         ]);
         table_node.add_children(&[column_node, cross_node, row_node]);
 
-        assert_eq!(parse(tokens), Ok(table_node));
+        assert_eq!(parse(spanned(tokens)), Ok(table_node));
     }
 
     #[test]
     fn test_missing_x_table() {
         let tokens = vec![Token::Num(3), Token::Num(5)];
-        assert_eq!(parse(tokens), Err("Expected 'x'".to_string()));
+        assert_eq!(parse(spanned(tokens)), Err(MdtgError::Expected { offset: 1, expected: Expected::Cross }));
     }
 
     #[test]
     fn test_missing_column_table() {
         let tokens = vec![Token::Cross, Token::Num(5)];
-        assert_eq!(parse(tokens), Err("Expected a column number".to_string()));
+        assert_eq!(parse(spanned(tokens)), Err(MdtgError::Expected { offset: 0, expected: Expected::ColumnNumber }));
     }
 
     #[test]
     fn test_missing_row_table() {
         let tokens = vec![Token::Num(3), Token::Cross];
-        assert_eq!(parse(tokens), Err("Expected a row number".to_string()));
+        assert_eq!(parse(spanned(tokens)), Err(MdtgError::Expected { offset: 2, expected: Expected::RowNumber }));
     }
 
     #[test]
@@ -198,8 +274,86 @@ mod tests {
             Token::Cross,
         ];
         assert_eq!(
-            parse(tokens),
-            Err("Number of positions exceed number of columns".to_string())
+            parse(spanned(tokens)),
+            Err(MdtgError::TooManyPositions { offset: 2, columns: 1 })
         );
     }
+
+    #[test]
+    fn test_header_table() {
+        let tokens = vec![
+            Token::Num(2),
+            Token::Cross,
+            Token::Num(1),
+            Token::OpenBracket,
+            Token::Cell("Name".to_string()),
+            Token::Cell("Age".to_string()),
+            Token::CloseBracket,
+            Token::OpenBracket,
+            Token::Cell("Luka".to_string()),
+            Token::CloseBracket,
+        ];
+
+        let mut table_node = Node::new(AST::Table);
+        let column_node = Node::new(AST::Column(2));
+        let cross_node = Node::new(AST::Cross);
+        let row_node = Node::new(AST::Row(1));
+
+        let mut header_node = Node::new(AST::Header);
+        header_node.add_children(&[
+            Node::new(AST::Cell("Name".to_string())),
+            Node::new(AST::Cell("Age".to_string())),
+        ]);
+
+        let mut record_node = Node::new(AST::Record);
+        record_node.add_children(&[Node::new(AST::Cell("Luka".to_string()))]);
+
+        table_node.add_children(&[column_node, cross_node, row_node, header_node, record_node]);
+
+        assert_eq!(parse(spanned(tokens)), Ok(table_node));
+    }
+
+    #[test]
+    fn test_excess_cells_table() {
+        let tokens = vec![
+            Token::Num(1),
+            Token::Cross,
+            Token::Num(1),
+            Token::OpenBracket,
+            Token::Cell("Name".to_string()),
+            Token::Cell("Age".to_string()),
+            Token::CloseBracket,
+        ];
+        assert_eq!(
+            parse(spanned(tokens)),
+            Err(MdtgError::TooManyCells { offset: 5, columns: 1 })
+        );
+    }
+
+    #[test]
+    fn test_excess_records_table() {
+        let tokens = vec![
+            Token::Num(1),
+            Token::Cross,
+            Token::Num(1),
+            Token::OpenBracket,
+            Token::CloseBracket,
+            Token::OpenBracket,
+            Token::Cell("a".to_string()),
+            Token::CloseBracket,
+            Token::OpenBracket,
+            Token::Cell("b".to_string()),
+            Token::CloseBracket,
+        ];
+        assert_eq!(
+            parse(spanned(tokens)),
+            Err(MdtgError::TooManyRecords { offset: 8, rows: 1 })
+        );
+    }
+
+    #[test]
+    fn test_missing_bracket_table() {
+        let tokens = vec![Token::Num(1), Token::Cross, Token::Num(1), Token::Num(2)];
+        assert_eq!(parse(spanned(tokens)), Err(MdtgError::Expected { offset: 3, expected: Expected::OpenBracket }));
+    }
 }
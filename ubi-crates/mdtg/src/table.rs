@@ -0,0 +1,528 @@
+use std::fmt;
+use std::ops::Range;
+
+use unicode_width::UnicodeWidthStr;
+
+static PIPE: char = '|';
+static MIN_WIDTH: usize = 3;
+
+/// The alignment of a table column, as given by its delimiter cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+/// A GFM table, with every cell trimmed of its surrounding whitespace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    pub alignments: Vec<Alignment>,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Parse the text of a GFM table, including its delimiter row.
+    ///
+    /// Rows with fewer cells than the header are padded with empty cells.
+    pub fn parse(input: &str) -> Result<Table, String> {
+        let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+
+        let header = match lines.next() {
+            Some(line) => split_row(line),
+            None => return Err("Expected a header row".to_string()),
+        };
+
+        let alignments = match lines.next().and_then(parse_delimiter_row) {
+            Some(alignments) => alignments,
+            None => return Err("Expected a delimiter row".to_string()),
+        };
+
+        if header.len() != alignments.len() {
+            return Err("Number of header cells does not match the delimiter row".to_string());
+        }
+
+        let rows = lines
+            .map(|line| {
+                let mut row = split_row(line);
+                if row.len() < header.len() {
+                    row.resize(header.len(), String::new());
+                }
+                row
+            })
+            .collect();
+
+        Ok(Table {
+            alignments,
+            header,
+            rows,
+        })
+    }
+
+    /// The number of columns in the table
+    pub fn columns(&self) -> usize {
+        self.header.len()
+    }
+
+    /// The padded width of every column, i.e. the width of its widest cell
+    fn widths(&self) -> Vec<usize> {
+        let mut widths = vec![MIN_WIDTH; self.columns()];
+
+        for row in Some(&self.header).into_iter().chain(self.rows.iter()) {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = display_width(cell).max(*width);
+            }
+        }
+
+        widths
+    }
+}
+
+impl fmt::Display for Table {
+    /// Pretty-print the table, padding every column to its widest cell
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let widths = self.widths();
+
+        write_row(f, &self.header, &self.alignments, &widths)?;
+        writeln!(f)?;
+
+        for (alignment, width) in self.alignments.iter().zip(widths.iter()) {
+            let (left, right) = match alignment {
+                Alignment::None => (' ', ' '),
+                Alignment::Left => (':', ' '),
+                Alignment::Center => (':', ':'),
+                Alignment::Right => (' ', ':'),
+            };
+            write!(f, "{}{}{}{}", PIPE, left, "-".repeat(*width), right)?;
+        }
+        write!(f, "{}", PIPE)?;
+
+        for row in &self.rows {
+            writeln!(f)?;
+            write_row(f, row, &self.alignments, &widths)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_row(
+    f: &mut fmt::Formatter,
+    row: &[String],
+    alignments: &[Alignment],
+    widths: &[usize],
+) -> fmt::Result {
+    for (i, cell) in row.iter().enumerate() {
+        match (alignments.get(i), widths.get(i)) {
+            (Some(alignment), Some(width)) => write!(f, "{} {} ", PIPE, pad(cell, *alignment, *width))?,
+            // Cells beyond the header are not rendered, but keep them rather than lose text.
+            _ => write!(f, "{} {} ", PIPE, cell)?,
+        }
+    }
+
+    write!(f, "{}", PIPE)
+}
+
+/// Pad a cell to the given width, honouring the column's alignment
+fn pad(cell: &str, alignment: Alignment, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(cell));
+
+    let (left, right) = match alignment {
+        Alignment::Center => (padding / 2, padding - padding / 2),
+        Alignment::Right => (padding, 0),
+        Alignment::None | Alignment::Left => (0, padding),
+    };
+
+    [&" ".repeat(left), cell, &" ".repeat(right)].concat()
+}
+
+/// The number of columns the text takes up in a monospace font.
+///
+/// East Asian wide characters count as two columns, while combining marks and
+/// zero width joiners take up none, so emoji sequences count as a single emoji.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Split a table row into its trimmed cells, ignoring escaped pipes
+/// and the optional leading and trailing pipes.
+pub fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix(PIPE).unwrap_or(line);
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                cell.push(c);
+                if let Some(escaped) = chars.next() {
+                    cell.push(escaped);
+                }
+            }
+            '|' => {
+                cells.push(cell.trim().to_string());
+                cell.clear();
+            }
+            _ => cell.push(c),
+        }
+    }
+
+    if !cell.trim().is_empty() || !line.ends_with(PIPE) || line.ends_with("\\|") {
+        cells.push(cell.trim().to_string());
+    }
+
+    cells
+}
+
+/// Parse a delimiter row such as `| :--- | :-: | --: |` into its alignments
+fn parse_delimiter_row(line: &str) -> Option<Vec<Alignment>> {
+    split_row(line)
+        .iter()
+        .map(|cell| {
+            let left = cell.starts_with(':');
+            let right = cell.ends_with(':') && cell.len() > 1;
+            let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+
+            if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                return None;
+            }
+
+            Some(match (left, right) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            })
+        })
+        .collect()
+}
+
+/// Find the byte range of the GFM table containing the given byte offset.
+///
+/// The range spans whole lines, from the start of the header row to the end
+/// of the last body row, without the trailing newline. Tables in code blocks
+/// are skipped, as they are by [`find_tables`].
+pub fn find_table(text: &str, offset: usize) -> Option<Range<usize>> {
+    find_tables(text).into_iter().find(|range| range.start <= offset && offset <= range.end)
+}
+
+/// Find the byte ranges of every GFM table in the given text, in document order.
+///
+/// Tables inside of fenced and indented code blocks are skipped. The text is
+/// read once, line by line, so this stays fast on long documents.
+pub fn find_tables(text: &str) -> Vec<Range<usize>> {
+    let lines = line_ranges(text);
+    let is_row = |i: usize| {
+        let line = &text[lines[i].clone()];
+        !line.trim().is_empty() && line.contains(PIPE)
+    };
+
+    let mut tables = Vec::new();
+    let mut fence: Option<String> = None;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = &text[lines[i].clone()];
+
+        if let Some(marker) = fence_marker(line) {
+            match fence {
+                Some(ref open) if marker.starts_with(open.as_str()) && line.trim().len() == marker.len() => fence = None,
+                Some(_) => {}
+                None => fence = Some(marker),
+            }
+        } else if fence.is_none()
+            && i + 1 < lines.len()
+            && !is_indented_code(line)
+            && !is_indented_code(&text[lines[i + 1].clone()])
+            && is_row(i)
+            && is_row(i + 1)
+            && parse_delimiter_row(&text[lines[i + 1].clone()]).is_some()
+        {
+            let mut last = i + 1;
+            while last + 1 < lines.len() && is_row(last + 1) {
+                last += 1;
+            }
+            tables.push(lines[i].start..lines[last].end);
+            i = last + 1;
+            continue;
+        }
+
+        i += 1;
+    }
+
+    tables
+}
+
+/// The byte range of each line, without its `\n` or `\r\n`
+fn line_ranges(text: &str) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+
+    for line in text.split('\n') {
+        lines.push(start..start + line.strip_suffix('\r').unwrap_or(line).len());
+        start += line.len() + 1;
+    }
+    lines
+}
+
+/// The line ending of the first line, which re-aligned tables are written with
+fn line_ending(text: &str) -> &'static str {
+    match text.find('\n') {
+        Some(end) if text[..end].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+/// Whether a line is indented far enough to be code. A table's header and
+/// delimiter rows never are, so tables in indented code blocks are skipped.
+fn is_indented_code(line: &str) -> bool {
+    let mut columns = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => columns += 1,
+            '\t' => columns += 4 - columns % 4,
+            _ => break,
+        }
+    }
+    columns >= 4
+}
+
+/// The backticks or tildes opening or closing a fenced code block
+fn fence_marker(line: &str) -> Option<String> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let trimmed = line.trim_start_matches(' ');
+    let c = trimmed.chars().next()?;
+
+    if indent > 3 || (c != '`' && c != '~') {
+        return None;
+    }
+
+    let marker: String = trimmed.chars().take_while(|m| *m == c).collect();
+    if marker.len() >= 3 {
+        Some(marker)
+    } else {
+        None
+    }
+}
+
+/// Re-align every GFM table in a markdown document, leaving the rest of the text untouched
+pub fn format_tables(text: &str) -> Result<String, String> {
+    let mut formatted = String::with_capacity(text.len());
+    let newline = line_ending(text);
+    let mut last = 0;
+
+    for range in find_tables(text) {
+        formatted.push_str(&text[last..range.start]);
+        formatted.push_str(&format_table(&text[range.clone()])?.replace('\n', newline));
+        last = range.end;
+    }
+
+    formatted.push_str(&text[last..]);
+    Ok(formatted)
+}
+
+/// Re-align the GFM table in the given text
+pub fn format_table(input: &str) -> Result<String, String> {
+    Ok(Table::parse(input)?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_table() {
+        let input = "\
+            | Name | Age |\n\
+            |:--|--:|\n\
+            | Luka | 23\n\
+            Stephen | 24 |";
+
+        let table = Table {
+            alignments: vec![Alignment::Left, Alignment::Right],
+            header: vec!["Name".to_string(), "Age".to_string()],
+            rows: vec![
+                vec!["Luka".to_string(), "23".to_string()],
+                vec!["Stephen".to_string(), "24".to_string()],
+            ],
+        };
+
+        assert_eq!(Table::parse(input), Ok(table));
+    }
+
+    #[test]
+    fn test_parse_escaped_pipes() {
+        assert_eq!(
+            split_row(r"| a \| b | `c` |"),
+            vec![r"a \| b".to_string(), "`c`".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_short_rows() {
+        let table = Table::parse("a | b | c\n--- | --- | ---\n1").unwrap();
+        assert_eq!(
+            table.rows,
+            vec![vec!["1".to_string(), String::new(), String::new()]]
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_delimiter() {
+        assert_eq!(
+            Table::parse("| a | b |\n| c | d |"),
+            Err("Expected a delimiter row".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_mismatched_delimiter() {
+        assert_eq!(
+            Table::parse("| a | b |\n| --- |"),
+            Err("Number of header cells does not match the delimiter row".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_table() {
+        let input = "\
+            | Platform | Link |\n\
+            | :-: | --- |\n\
+            | Linux | [tar.gz](https://example.com)\n\
+            | Arch | AUR |";
+
+        assert_eq!(
+            format_table(input),
+            Ok("\
+                | Platform | Link                          |\n\
+                |:--------:| ----------------------------- |\n\
+                |  Linux   | [tar.gz](https://example.com) |\n\
+                |   Arch   | AUR                           |"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_wide_characters() {
+        let input = "\
+            | 名前 | Emoji |\n\
+            | :-: | --- |\n\
+            | 東京 | 👨‍👩‍👧 |\n\
+            | Zoë | e\u{301} |";
+
+        assert_eq!(
+            format_table(input),
+            Ok("\
+                | 名前 | Emoji |\n\
+                |:----:| ----- |\n\
+                | 東京 | 👨‍👩‍👧    |\n\
+                | Zoë  | e\u{301}     |"
+                .to_string())
+        );
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("👩‍💻"), 2);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let input = "a|b\n-:|:-\nlong cell|x";
+        let formatted = format_table(input).unwrap();
+        assert_eq!(format_table(&formatted), Ok(formatted));
+    }
+
+    #[test]
+    fn test_find_table() {
+        let text = "Some text | with a pipe\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\nMore text";
+        let start = text.find("| a").unwrap();
+        let end = text.find("\n\nMore").unwrap();
+
+        assert_eq!(find_table(text, start), Some(start..end));
+        assert_eq!(find_table(text, end - 1), Some(start..end));
+        assert_eq!(find_table(text, 0), None);
+        assert_eq!(find_table(text, text.len()), None);
+    }
+
+    #[test]
+    fn test_find_tables() {
+        let text = "| a | b |\n|---|---|\n\ntext\n\nx | y\n- | -\n1 | 2";
+        let second = text.find("x |").unwrap();
+
+        assert_eq!(find_tables(text), vec![0..19, second..text.len()]);
+        assert_eq!(find_tables("no | tables\nhere"), vec![]);
+    }
+
+    #[test]
+    fn test_find_tables_in_long_documents() {
+        let text = "a | b\n| h1 | h2 |\n| -- | -- |\n| 1 | 2 |\n\nsome | text\n\n".repeat(5000);
+        let tables = find_tables(&text);
+
+        assert_eq!(tables.len(), 5000);
+        assert_eq!(&text[tables[1].clone()], "| h1 | h2 |\n| -- | -- |\n| 1 | 2 |");
+    }
+
+    #[test]
+    fn test_find_tables_skips_code_blocks() {
+        let text = "````md\n| a | b |\n|---|---|\n```\n````\n\n| c |\n|---|";
+        let start = text.find("| c").unwrap();
+
+        assert_eq!(find_tables(text), vec![start..text.len()]);
+    }
+
+    #[test]
+    fn test_find_tables_skips_indented_code() {
+        let text = "Some code:\n\n    | a | b |\n    |---|---|\n    | 1 | 2 |\n\n\t| c |\n\t|---|\n\n   | d |\n   |---|";
+        let start = text.find("   | d").unwrap();
+
+        assert_eq!(find_tables(text), vec![start..text.len()]);
+        assert_eq!(find_table(text, text.find("| a").unwrap()), None);
+        assert_eq!(find_table(text, text.find("| c").unwrap()), None);
+
+        let code = "Some code:\n\n    | a | b |\n    |---|---|\n    | 1 | 2 |\n";
+        assert_eq!(format_tables(code), Ok(code.to_string()));
+    }
+
+    #[test]
+    fn test_find_table_skips_fenced_code() {
+        let text = "```\n| a | b |\n|---|---|\n```\n";
+        assert_eq!(find_table(text, text.find("| a").unwrap()), None);
+    }
+
+    #[test]
+    fn test_format_tables() {
+        let text = "# Title\n\na|b\n-|:-:\n1|2\n\n~~~\nx|y\n-|-\n~~~\n";
+
+        assert_eq!(
+            format_tables(text),
+            Ok("# Title\n\n| a   |  b  |\n| --- |:---:|\n| 1   |  2  |\n\n~~~\nx|y\n-|-\n~~~\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_tables_keeps_crlf() {
+        let text = "# Title\r\n\r\na|b\r\n-|:-:\r\n1|2\r\n\r\nText\r\n";
+        let formatted = "# Title\r\n\r\n| a   |  b  |\r\n| --- |:---:|\r\n| 1   |  2  |\r\n\r\nText\r\n";
+
+        assert_eq!(find_tables(text), vec![11..26]);
+        assert_eq!(format_tables(text), Ok(formatted.to_string()));
+        assert_eq!(format_tables(formatted), Ok(formatted.to_string()));
+    }
+
+    #[test]
+    fn test_find_table_below_paragraph() {
+        let text = "a | b\n| h1 | h2 |\n| -- | -- |\n| 1 | 2 |";
+        let start = text.find("| h1").unwrap();
+
+        assert_eq!(find_table(text, text.len()), Some(start..text.len()));
+    }
+}
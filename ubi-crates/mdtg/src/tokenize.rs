@@ -1,4 +1,8 @@
 use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+use errors::{Expected, MdtgError};
 
 /// Represents an input token
 #[derive(Debug, PartialEq)]
@@ -6,61 +10,148 @@ pub enum Token {
     Cross,
     Num(usize),
     Position(char),
+    OpenBracket,
+    Cell(String),
+    CloseBracket,
 }
 
+/// A token along with the byte range of the input it was read from
+pub type Spanned = (Token, Range<usize>);
+
 /// Given a string, return a list of tokens representing that string,
 /// or an error if one has occurred
-pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
-    let mut tokens = Vec::<Token>::new();
+pub fn tokenize(input: &str) -> Result<Vec<Spanned>, MdtgError> {
+    let mut tokens = Vec::<Spanned>::new();
 
-    let mut it = input.chars().peekable();
+    let mut it = input.char_indices().peekable();
 
-    while let Some(&c) = it.peek() {
+    while let Some(&(offset, c)) = it.peek() {
         match c {
             'x' => {
-                tokens.push(Token::Cross);
+                tokens.push((Token::Cross, offset..offset + 1));
                 it.next();
             }
             'l' | 'c' | 'r' => {
-                tokens.push(Token::Position(c));
+                tokens.push((Token::Position(c), offset..offset + 1));
                 it.next();
             }
             '1'..='9' => {
                 it.next();
-                let n = get_num(c, &mut it);
-                tokens.push(Token::Num(n));
+                let (n, end) = get_num(c, offset, &mut it);
+                tokens.push((Token::Num(n), offset..end));
+            }
+            '[' => {
+                it.next();
+                tokens.push((Token::OpenBracket, offset..offset + 1));
+                get_cells(&mut it, &mut tokens, input.len())?;
             }
             ' ' => {
                 it.next();
             }
-            '0' => return Err("Column/row number must be > 0".to_string()),
-            _ => return Err(format!("Unexpected input '{}'", c)),
+            '0' => return Err(MdtgError::ZeroDimension { offset }),
+            _ => return Err(MdtgError::UnexpectedInput { offset, found: c }),
         }
     }
 
     Ok(tokens)
 }
 
-fn get_num<T: Iterator<Item = char>>(c: char, iter: &mut Peekable<T>) -> usize {
+/// Read the rest of a number, returning it and the byte offset after its last digit
+fn get_num(c: char, offset: usize, iter: &mut Peekable<CharIndices>) -> (usize, usize) {
     let mut n = c.to_digit(10).unwrap() as usize;
+    let mut end = offset + 1;
 
-    while let Some(Ok(digit)) = iter.peek().map(|c| c.to_string().parse::<usize>()) {
-        n = n * 10 + digit;
-        iter.next();
+    while let Some(&(i, digit)) = iter.peek() {
+        match digit.to_digit(10) {
+            Some(digit) => {
+                n = n * 10 + digit as usize;
+                end = i + 1;
+                iter.next();
+            }
+            None => break,
+        }
     }
 
-    n
+    (n, end)
+}
+
+/// Read the comma separated cells of a `[...]` group, up to and including
+/// the closing bracket.
+///
+/// A backslash escapes the next character, so `\,`, `\[`, `\]` and `\\`
+/// can be used inside of a cell.
+fn get_cells(iter: &mut Peekable<CharIndices>, tokens: &mut Vec<Spanned>, end: usize) -> Result<(), MdtgError> {
+    let mut cell = String::new();
+    let mut start = None;
+    let mut cell_end = 0;
+    let mut is_empty = true;
+
+    loop {
+        match iter.next() {
+            Some((offset, '\\')) => match iter.next() {
+                Some((i, escaped)) => {
+                    cell.push(escaped);
+                    start = start.or(Some(offset));
+                    cell_end = i + escaped.len_utf8();
+                    is_empty = false;
+                }
+                None => return Err(MdtgError::Expected { offset: end, expected: Expected::EscapedCharacter }),
+            },
+            Some((offset, ',')) => {
+                let start = start.take().unwrap_or(offset);
+                tokens.push((Token::Cell(cell.trim().to_string()), start..cell_end.max(start)));
+                cell.clear();
+                is_empty = false;
+            }
+            Some((offset, ']')) => {
+                if !is_empty || !cell.trim().is_empty() {
+                    let start = start.unwrap_or(offset);
+                    tokens.push((Token::Cell(cell.trim().to_string()), start..cell_end.max(start)));
+                }
+                tokens.push((Token::CloseBracket, offset..offset + 1));
+                return Ok(());
+            }
+            Some((offset, '[')) => return Err(MdtgError::UnexpectedInput { offset, found: '[' }),
+            Some((offset, c)) => {
+                cell.push(c);
+                if c != ' ' {
+                    start = start.or(Some(offset));
+                    cell_end = offset + c.len_utf8();
+                    is_empty = false;
+                }
+            }
+            None => return Err(MdtgError::Expected { offset: end, expected: Expected::CloseBracket }),
+        }
+    }
+}
+
+/// Escape a cell so that it can be used inside of a `[...]` group
+pub fn escape_cell(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+
+    for c in cell.chars() {
+        if let '\\' | ',' | '[' | ']' = c {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn tokens(input: &str) -> Result<Vec<Token>, MdtgError> {
+        tokenize(input).map(|tokens| tokens.into_iter().map(|(token, _)| token).collect())
+    }
+
     #[test]
     fn test_simple_table() {
         let input = "3 x 5";
         assert_eq!(
-            tokenize(&input),
+            tokens(&input),
             Ok(vec![Token::Num(3), Token::Cross, Token::Num(5)])
         );
     }
@@ -69,7 +160,7 @@ mod tests {
     fn test_complex_table() {
         let input = "6lcr x 2";
         assert_eq!(
-            tokenize(&input),
+            tokens(&input),
             Ok(vec![
                 Token::Num(6),
                 Token::Position('l'),
@@ -84,6 +175,101 @@ mod tests {
     #[test]
     fn test_invalid_table() {
         let input = "3a x 5";
-        assert!(tokenize(&input).is_err());
+        assert!(tokens(&input).is_err());
+    }
+
+    #[test]
+    fn test_header_table() {
+        let input = "3lcr x 2 [Name, Age, City]";
+        assert_eq!(
+            tokens(&input),
+            Ok(vec![
+                Token::Num(3),
+                Token::Position('l'),
+                Token::Position('c'),
+                Token::Position('r'),
+                Token::Cross,
+                Token::Num(2),
+                Token::OpenBracket,
+                Token::Cell("Name".to_string()),
+                Token::Cell("Age".to_string()),
+                Token::Cell("City".to_string()),
+                Token::CloseBracket,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_escaped_cells() {
+        let input = r"2 x 1 [a\, b, \[x\]] [, c\\]";
+        assert_eq!(
+            tokens(&input),
+            Ok(vec![
+                Token::Num(2),
+                Token::Cross,
+                Token::Num(1),
+                Token::OpenBracket,
+                Token::Cell("a, b".to_string()),
+                Token::Cell("[x]".to_string()),
+                Token::CloseBracket,
+                Token::OpenBracket,
+                Token::Cell("".to_string()),
+                Token::Cell(r"c\".to_string()),
+                Token::CloseBracket,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_empty_cells() {
+        assert_eq!(
+            tokens("1 x 1 []"),
+            Ok(vec![
+                Token::Num(1),
+                Token::Cross,
+                Token::Num(1),
+                Token::OpenBracket,
+                Token::CloseBracket,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unclosed_cells() {
+        assert_eq!(
+            tokens("1 x 1 [Name"),
+            Err(MdtgError::Expected { offset: 11, expected: Expected::CloseBracket })
+        );
+    }
+
+    #[test]
+    fn test_unexpected_input() {
+        assert_eq!(
+            tokens("3a x 5"),
+            Err(MdtgError::UnexpectedInput { offset: 1, found: 'a' })
+        );
+        assert_eq!(tokens("2 x 0"), Err(MdtgError::ZeroDimension { offset: 4 }));
+    }
+
+    #[test]
+    fn test_spans() {
+        assert_eq!(
+            tokenize("12c x 3 [ Größe , \\,b]"),
+            Ok(vec![
+                (Token::Num(12), 0..2),
+                (Token::Position('c'), 2..3),
+                (Token::Cross, 4..5),
+                (Token::Num(3), 6..7),
+                (Token::OpenBracket, 8..9),
+                (Token::Cell("Größe".to_string()), 10..17),
+                (Token::Cell(",b".to_string()), 20..23),
+                (Token::CloseBracket, 23..24),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_escape_cell() {
+        assert_eq!(escape_cell(r"a, [b] \ c"), r"a\, \[b\] \\ c");
     }
 }